@@ -0,0 +1,95 @@
+use opendal::{Buffer, Error, ErrorKind, Operator};
+
+/// Append bytes to an object, creating it if it does not exist.
+///
+/// Uses the native append capability of the backend when available.
+/// Otherwise the object is read and rewritten with the new bytes at the end:
+/// the rewrite is conditional on the ETag of the object (or on the object not existing yet)
+/// where the backend supports it, so a concurrent update fails with [`ErrorKind::ConditionNotMatch`]
+/// instead of being silently lost. Such failures can be retried with a [`RetryPolicy`](crate::RetryPolicy).
+///
+/// The fallback rewrites the whole object, so it is only suitable for small, log-style objects.
+pub async fn append(
+    operator: &Operator,
+    path: &str,
+    bytes: impl Into<Buffer>,
+) -> Result<(), Error> {
+    let bytes = bytes.into();
+    let capability = operator.info().full_capability();
+
+    if capability.write_can_append {
+        operator.write_with(path, bytes).append(true).await?;
+
+        return Ok(());
+    }
+
+    let meta = match operator.stat(path).await {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            operator
+                .write_with(path, bytes)
+                .if_not_exists(capability.write_with_if_not_exists)
+                .await?;
+
+            return Ok(());
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut content = operator.read(path).await?.to_vec();
+    content.extend_from_slice(&bytes.to_vec());
+
+    let mut write = operator.write_with(path, content);
+
+    if capability.write_with_if_match
+        && let Some(etag) = meta.etag()
+    {
+        write = write.if_match(etag);
+    }
+
+    if let Some(content_type) = meta.content_type() {
+        write = write.content_type(content_type);
+    }
+
+    write.await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        append(&operator, "log.txt", "foo\n").await?;
+        append(&operator, "log.txt", "bar\n").await?;
+
+        let buffer = operator.read("log.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo\nbar\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_append_preserves_content_type() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write_with("events.jsonl", "{}\n")
+            .content_type("application/jsonl")
+            .await?;
+
+        append(&operator, "events.jsonl", "{}\n").await?;
+
+        let stat = operator.stat("events.jsonl").await?;
+        assert_eq!(stat.content_type(), Some("application/jsonl"));
+        assert_eq!(stat.content_length(), 6);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use futures::TryStreamExt;
+use opendal::{Error, Metadata, options::ListOptions};
+
+use crate::copy::normalize_dir;
+use crate::{
+    ByteTransform, Copier, CopyOptions, Digest, HashAlgorithm, Hasher, MANIFEST_VERSION, Manifest,
+    ManifestEntry, ManifestKind, checksum, list,
+};
+
+/// Outcome of an [`incremental`] backup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Full manifest of the source at the time of the backup, to pass to the next backup.
+    pub manifest: Manifest,
+
+    /// Files copied because they are new or changed (relative to the source root).
+    pub copied: Vec<String>,
+
+    /// Number of unchanged files that were not copied.
+    pub unchanged: u64,
+
+    /// Files of the previous manifest that no longer exist in the source.
+    pub removed: Vec<String>,
+}
+
+/// Copy the files under `source_root` that are new or changed since a previous backup to `destination_root`.
+///
+/// Files are compared with the previous manifest by size, then by ETag or modification time (whichever is available).
+/// Files that can't be compared are copied.
+/// Paths are relative to the roots of the copier, and changed files are hashed while they are copied.
+///
+/// Returns a full manifest of the source (copying the digests of unchanged files from the previous manifest),
+/// to be written next to the backup (see [`Manifest::write`]) and passed to the next backup,
+/// giving generational backups without transferring everything every time.
+pub async fn incremental(
+    copier: &Copier,
+    source_root: &str,
+    destination_root: &str,
+    previous: Option<&Manifest>,
+) -> Result<BackupReport, Error> {
+    let source_root = normalize_dir(source_root);
+    let destination_root = normalize_dir(destination_root);
+
+    // Path of the source root on the source operator
+    let listed_root = normalize_dir(&copier.source_path(&source_root)?);
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(&copier.source, &listed_root, Some(list_options)).await?;
+
+    let digest: Arc<Mutex<Option<Digest>>> = Arc::default();
+    let hashing = copier.clone().with_source_transform({
+        let digest = digest.clone();
+
+        move || DigestTransform {
+            hasher: Some(Hasher::new(HashAlgorithm::Sha256)),
+            digest: digest.clone(),
+        }
+    });
+
+    let copy_options = CopyOptions {
+        recursive: false,
+        disable_glob: true,
+        ..Default::default()
+    };
+
+    let mut entries = BTreeMap::new();
+    let mut copied = Vec::new();
+    let mut unchanged = 0;
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let path = entry.path().to_string();
+        let relative = path.strip_prefix(&listed_root).unwrap_or(&path).to_string();
+
+        // Listing does not necessarily return every metadata field
+        let meta = copier.source.stat(&path).await?;
+
+        let previous_entry = previous.and_then(|previous| previous.entries.get(&relative));
+
+        if let Some(prev) = previous_entry
+            && !changed(prev, &meta)
+        {
+            entries.insert(relative, prev.clone());
+            unchanged += 1;
+
+            continue;
+        }
+
+        digest.lock().unwrap().take();
+
+        hashing
+            .copy_options(
+                format!("{}{}", source_root, relative),
+                format!("{}{}", destination_root, relative),
+                copy_options,
+            )
+            .await?;
+
+        // Files skipped by the copier (e.g. already in its journal) are not hashed while copying
+        let copied_digest = digest.lock().unwrap().take();
+        let digest = match copied_digest {
+            Some(digest) => digest,
+            None => checksum(&copier.source, &path, HashAlgorithm::Sha256).await?,
+        };
+
+        entries.insert(
+            relative.clone(),
+            ManifestEntry {
+                size: meta.content_length(),
+                digest,
+                content_type: meta.content_type().map(String::from),
+                etag: meta.etag().map(String::from),
+                last_modified: meta.last_modified().map(|ts| ts.to_string()),
+            },
+        );
+        copied.push(relative);
+    }
+
+    let seen: HashSet<&String> = entries.keys().collect();
+
+    let removed = previous
+        .map(|previous| {
+            previous
+                .entries
+                .keys()
+                .filter(|path| !seen.contains(path))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(BackupReport {
+        manifest: Manifest {
+            version: MANIFEST_VERSION,
+            kind: ManifestKind::Full,
+            root: source_root.trim_end_matches('/').to_string(),
+            created_at: SystemTime::now(),
+            entries,
+            removed: Vec::new(),
+        },
+        copied,
+        unchanged,
+        removed,
+    })
+}
+
+// Hashes the source content of a file while it is copied.
+struct DigestTransform {
+    hasher: Option<Hasher>,
+    digest: Arc<Mutex<Option<Digest>>>,
+}
+
+impl ByteTransform for DigestTransform {
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error> {
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&chunk);
+        }
+
+        Ok(chunk)
+    }
+
+    fn finish(&mut self) -> Result<Bytes, Error> {
+        if let Some(hasher) = self.hasher.take() {
+            *self.digest.lock().unwrap() = Some(hasher.finalize());
+        }
+
+        Ok(Bytes::new())
+    }
+}
+
+// Whether a file changed since it was recorded in a manifest.
+fn changed(entry: &ManifestEntry, meta: &Metadata) -> bool {
+    if entry.size != meta.content_length() {
+        return true;
+    }
+
+    if let (Some(previous), Some(current)) = (&entry.etag, meta.etag()) {
+        return previous != current;
+    }
+
+    if let (Some(previous), Some(current)) = (&entry.last_modified, meta.last_modified()) {
+        return *previous != current.to_string();
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::Operator;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_incremental() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "a").await?;
+        source.write("data/sub/b.txt", "b").await?;
+
+        let copier = Copier::new(source.clone(), destination.clone());
+
+        let first = incremental(&copier, "data", "backups/1/", None).await?;
+        assert_eq!(first.copied, vec!["a.txt", "sub/b.txt"]);
+        assert_eq!(first.manifest.entries.len(), 2);
+        assert!(destination.exists("backups/1/sub/b.txt").await?);
+
+        source.write("data/a.txt", "changed").await?;
+        source.write("data/c.txt", "c").await?;
+        source.delete("data/sub/b.txt").await?;
+
+        let second = incremental(&copier, "data/", "backups/2", Some(&first.manifest)).await?;
+        assert_eq!(second.copied, vec!["a.txt", "c.txt"]);
+        assert_eq!(second.removed, vec!["sub/b.txt"]);
+        assert_eq!(
+            destination.read("backups/2/a.txt").await?.to_vec(),
+            b"changed"
+        );
+        assert!(!destination.exists("backups/2/sub/b.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incremental_rooted() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("tenant/data/a.txt", "a").await?;
+        source.write("data/b.txt", "outside the root").await?;
+
+        let copier = Copier::builder(source.clone(), destination.clone())
+            .source_root("tenant/")
+            .destination_root("backups/")
+            .build();
+
+        let report = incremental(&copier, "data", "1/", None).await?;
+        assert_eq!(report.copied, vec!["a.txt"]);
+        assert_eq!(report.manifest.root, "data");
+        assert_eq!(destination.read("backups/1/a.txt").await?.to_vec(), b"a");
+
+        // Digests are computed while copying
+        assert_eq!(
+            report.manifest.entries["a.txt"].digest,
+            checksum(&source, "tenant/data/a.txt", HashAlgorithm::Sha256).await?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_changed() {
+        let entry = ManifestEntry {
+            size: 1,
+            digest: crate::Digest::new(HashAlgorithm::Sha256, ""),
+            content_type: None,
+            etag: Some("v1".to_string()),
+            last_modified: None,
+        };
+
+        let meta = |size: u64, etag: Option<&str>| {
+            let mut meta = Metadata::new(opendal::EntryMode::FILE).with_content_length(size);
+
+            if let Some(etag) = etag {
+                meta = meta.with_etag(etag.to_string());
+            }
+
+            meta
+        };
+
+        assert!(!changed(&entry, &meta(1, Some("v1"))));
+        assert!(changed(&entry, &meta(1, Some("v2"))));
+        assert!(changed(&entry, &meta(2, Some("v1"))));
+        assert!(changed(&entry, &meta(1, None)));
+    }
+}
@@ -0,0 +1,964 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::TryStreamExt;
+use opendal::{Buffer, Error, ErrorKind, Metadata, Operator, options::ListOptions};
+use tokio::sync::{Mutex, watch};
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Copier, CopyOptions, list};
+
+// Cached content, entry metadata and the journal of pending writes live in separate trees
+// of the cache operator, so cached paths can never collide with bookkeeping objects.
+const DATA_PREFIX: &str = "data/";
+const META_PREFIX: &str = "meta/";
+const JOURNAL_PREFIX: &str = "pending/";
+
+/// Options for controlling what a [`CachedReader`] keeps in its cache.
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CacheOptions {
+    /// How long cached objects are served before they are read from the origin again.
+    ///
+    /// `None` means cached objects never expire.
+    pub ttl: Option<Duration>,
+
+    /// Objects larger than this (in bytes) are never cached.
+    ///
+    /// `None` means no limit.
+    pub max_object_size: Option<u64>,
+
+    /// Maximum total size of the cache in bytes.
+    /// The oldest entries are evicted when the cache grows beyond it.
+    ///
+    /// `None` means no limit.
+    pub max_size: Option<u64>,
+}
+
+/// Metadata of a cached object.
+///
+/// Entries are stored as plain text next to the cached content:
+///
+/// ```text
+/// cached_at 1700000000000
+/// size 42
+/// content_type text/plain
+/// etag "abc"
+/// ```
+///
+/// The time is stored in milliseconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub cached_at: SystemTime,
+
+    /// Size of the cached object in bytes.
+    pub size: u64,
+
+    pub content_type: Option<String>,
+
+    /// Etag of the object in the origin when it was cached.
+    pub etag: Option<String>,
+}
+
+impl CacheEntry {
+    pub fn is_expired(&self, ttl: Option<Duration>) -> bool {
+        let Some(ttl) = ttl else {
+            return false;
+        };
+
+        SystemTime::now()
+            .duration_since(self.cached_at)
+            .unwrap_or_default()
+            >= ttl
+    }
+}
+
+impl fmt::Display for CacheEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cached_at = self
+            .cached_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        writeln!(f, "cached_at {}", cached_at)?;
+        writeln!(f, "size {}", self.size)?;
+
+        if let Some(content_type) = &self.content_type {
+            writeln!(f, "content_type {}", content_type)?;
+        }
+
+        if let Some(etag) = &self.etag {
+            writeln!(f, "etag {}", etag)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for CacheEntry {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::new(ErrorKind::Unexpected, "Invalid cache entry");
+
+        let mut cached_at = None;
+        let mut size = None;
+        let mut content_type = None;
+        let mut etag = None;
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+
+            match key {
+                "cached_at" => {
+                    let millis: u64 = value
+                        .trim()
+                        .parse()
+                        .map_err(|err| invalid().with_context("line", line).set_source(err))?;
+
+                    cached_at = Some(UNIX_EPOCH + Duration::from_millis(millis));
+                }
+                "size" => {
+                    size = Some(
+                        value
+                            .trim()
+                            .parse()
+                            .map_err(|err| invalid().with_context("line", line).set_source(err))?,
+                    );
+                }
+                "content_type" => content_type = Some(value.to_string()),
+                "etag" => etag = Some(value.to_string()),
+                _ => return Err(invalid().with_context("line", line)),
+            }
+        }
+
+        Ok(CacheEntry {
+            cached_at: cached_at.ok_or_else(invalid)?,
+            size: size.ok_or_else(invalid)?,
+            content_type,
+            etag,
+        })
+    }
+}
+
+/// Reads objects from an origin operator through a cache operator (e.g. the local filesystem).
+///
+/// Objects are served from the cache while they are fresh (see [`CacheOptions::ttl`]),
+/// otherwise they are read from the origin and stored in the cache.
+///
+/// The cache is best effort: failing to read from or write to the cache
+/// falls back to the origin instead of failing the read.
+#[derive(Debug, Clone)]
+pub struct CachedReader {
+    origin: Operator,
+    cache: Operator,
+    options: CacheOptions,
+
+    // Estimated size of the cache (unknown until the first prune), shared by clones
+    size: Arc<std::sync::Mutex<Option<u64>>>,
+}
+
+impl CachedReader {
+    pub fn new(origin: Operator, cache: Operator) -> Self {
+        Self {
+            origin,
+            cache,
+            options: CacheOptions::default(),
+            size: Arc::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: CacheOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn origin(&self) -> &Operator {
+        &self.origin
+    }
+
+    pub fn cache(&self) -> &Operator {
+        &self.cache
+    }
+
+    /// Read an object, from the cache if possible.
+    pub async fn read(&self, path: &str) -> Result<Buffer, Error> {
+        if let Ok(Some(buffer)) = self.read_cached(path).await {
+            return Ok(buffer);
+        }
+
+        let meta = self.origin.stat(path).await?;
+        let buffer = self.origin.read(path).await?;
+
+        let entry = CacheEntry {
+            cached_at: SystemTime::now(),
+            size: buffer.len() as u64,
+            content_type: meta.content_type().map(String::from),
+            etag: meta.etag().map(String::from),
+        };
+
+        let _ = self.populate(path, buffer.clone(), entry).await;
+
+        Ok(buffer)
+    }
+
+    /// Return the cache entry of an object, if it is cached (fresh or not).
+    pub async fn entry(&self, path: &str) -> Result<Option<CacheEntry>, Error> {
+        read_entry(&self.cache, path).await
+    }
+
+    /// Remove an object from the cache.
+    pub async fn invalidate(&self, path: &str) -> Result<(), Error> {
+        // Remove the entry first, so a partially removed object is never served
+        self.cache.delete(&meta_path(path)).await?;
+        self.cache.delete(&data_path(path)).await
+    }
+
+    /// Remove expired objects and evict the oldest objects
+    /// until the cache fits [`CacheOptions::max_size`].
+    ///
+    /// Caching an object only prunes the cache when the estimated size of the cache exceeds the limit
+    /// (the estimate counts the objects cached by this reader and its clones since the last prune).
+    pub async fn prune(&self) -> Result<(), Error> {
+        let options = ListOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let mut lister = list::lister(&self.cache, META_PREFIX, Some(options)).await?;
+
+        let mut entries = Vec::new();
+
+        while let Some(item) = lister.try_next().await? {
+            if !item.metadata().is_file() {
+                continue;
+            }
+
+            let path = item.path();
+            let path = path.strip_prefix(META_PREFIX).unwrap_or(path).to_string();
+
+            match self.entry(&path).await {
+                Ok(Some(entry)) if !entry.is_expired(self.options.ttl) => {
+                    entries.push((path, entry))
+                }
+                Ok(None) => {}
+                // Expired or unreadable entries
+                _ => self.invalidate(&path).await?,
+            }
+        }
+
+        let mut size: u64 = entries.iter().map(|(_, entry)| entry.size).sum();
+
+        let Some(max_size) = self.options.max_size else {
+            self.set_size(Some(size));
+
+            return Ok(());
+        };
+
+        entries.sort_by(|(a_path, a), (b_path, b)| {
+            a.cached_at
+                .cmp(&b.cached_at)
+                .then_with(|| a_path.cmp(b_path))
+        });
+
+        for (path, entry) in entries {
+            if size <= max_size {
+                break;
+            }
+
+            self.invalidate(&path).await?;
+
+            size -= entry.size;
+        }
+
+        self.set_size(Some(size));
+
+        Ok(())
+    }
+
+    async fn read_cached(&self, path: &str) -> Result<Option<Buffer>, Error> {
+        let Some(entry) = self.entry(path).await? else {
+            return Ok(None);
+        };
+
+        if entry.is_expired(self.options.ttl) {
+            return Ok(None);
+        }
+
+        self.cache.read(&data_path(path)).await.map(Some)
+    }
+
+    async fn populate(&self, path: &str, buffer: Buffer, entry: CacheEntry) -> Result<(), Error> {
+        if self
+            .options
+            .max_object_size
+            .is_some_and(|max_object_size| entry.size > max_object_size)
+        {
+            return Ok(());
+        }
+
+        // Write the entry last, so partially written content is never served
+        self.cache.write(&data_path(path), buffer).await?;
+        self.cache
+            .write(&meta_path(path), entry.to_string())
+            .await?;
+
+        if let Some(max_size) = self.options.max_size {
+            let size = {
+                let mut size = self.size.lock().unwrap();
+                *size = size.map(|size| size + entry.size);
+                *size
+            };
+
+            // Overwritten objects are counted twice, which only makes pruning happen earlier
+            if size.is_none_or(|size| size > max_size) {
+                self.prune().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_size(&self, size: Option<u64>) {
+        *self.size.lock().unwrap() = size;
+    }
+}
+
+/// Reads objects from a primary operator (e.g. a slow cold-storage tier) through a cache operator.
+///
+/// Unlike [`CachedReader`], objects are copied to the cache with a [`Copier`] instead of being buffered,
+/// and expired objects are revalidated: when the etag of an object in the primary did not change,
+/// the cached copy is served again without reading the object from the primary.
+/// Objects without an etag are read from the primary again once they expire.
+///
+/// The cache is best effort: failing to read from or write to the cache
+/// falls back to the primary instead of failing the read.
+///
+/// ```no_run
+/// # async fn example(archive: opendal::Operator, local: opendal::Operator) -> Result<(), opendal::Error> {
+/// use std::time::Duration;
+///
+/// use opendal_util::cache::ReadThrough;
+///
+/// let reader = ReadThrough::new(archive, local).with_ttl(Duration::from_secs(3600));
+///
+/// let buffer = reader.read("reports/2024.csv").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReadThrough {
+    primary: Operator,
+    cache: Operator,
+    ttl: Option<Duration>,
+}
+
+impl ReadThrough {
+    pub fn new(primary: Operator, cache: Operator) -> Self {
+        Self {
+            primary,
+            cache,
+            ttl: None,
+        }
+    }
+
+    /// Revalidate cached objects against the primary once they are older than `ttl`.
+    ///
+    /// Cached objects never expire by default.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn primary(&self) -> &Operator {
+        &self.primary
+    }
+
+    pub fn cache(&self) -> &Operator {
+        &self.cache
+    }
+
+    /// Read an object, from the cache if possible.
+    pub async fn read(&self, path: &str) -> Result<Buffer, Error> {
+        let entry = read_entry(&self.cache, path).await.ok().flatten();
+
+        if let Some(entry) = &entry
+            && !entry.is_expired(self.ttl)
+            && let Ok(buffer) = self.cache.read(&data_path(path)).await
+        {
+            return Ok(buffer);
+        }
+
+        let meta = self.primary.stat(path).await?;
+
+        if let Some(entry) = entry
+            && entry.etag.is_some()
+            && entry.etag.as_deref() == meta.etag()
+            && let Ok(buffer) = self.revalidate(path, entry).await
+        {
+            return Ok(buffer);
+        }
+
+        if self.populate(path, &meta).await.is_ok()
+            && let Ok(buffer) = self.cache.read(&data_path(path)).await
+        {
+            return Ok(buffer);
+        }
+
+        self.primary.read(path).await
+    }
+
+    // Serve an unchanged object from the cache again, for another ttl.
+    async fn revalidate(&self, path: &str, entry: CacheEntry) -> Result<Buffer, Error> {
+        let entry = CacheEntry {
+            cached_at: SystemTime::now(),
+            ..entry
+        };
+
+        self.cache
+            .write(&meta_path(path), entry.to_string())
+            .await?;
+
+        self.cache.read(&data_path(path)).await
+    }
+
+    async fn populate(&self, path: &str, meta: &Metadata) -> Result<(), Error> {
+        // Remove the entry first, so partially copied content is never served
+        self.cache.delete(&meta_path(path)).await?;
+
+        let options = CopyOptions {
+            recursive: false,
+            disable_glob: true,
+            ..Default::default()
+        };
+
+        Copier::new(self.primary.clone(), self.cache.clone())
+            .copy_options(path, data_path(path), options)
+            .await?;
+
+        let entry = CacheEntry {
+            cached_at: SystemTime::now(),
+            size: meta.content_length(),
+            content_type: meta.content_type().map(String::from),
+            etag: meta.etag().map(String::from),
+        };
+
+        self.cache
+            .write(&meta_path(path), entry.to_string())
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// A write-back cache in front of an origin operator.
+///
+/// Writes land in the cache operator immediately and are recorded in a journal of pending writes
+/// (stored in the cache operator as well), which are copied to the origin by [`WriteBackCache::flush`]
+/// or periodically in the background (see [`WriteBackCache::start`]).
+/// Pending writes survive restarts: they are flushed by the next cache opened on the same cache operator.
+///
+/// Reads are served from the cache when the object is cached, and from the origin otherwise.
+pub struct WriteBackCache {
+    inner: Arc<WriteBackInner>,
+    task: Option<(watch::Sender<bool>, JoinHandle<()>)>,
+}
+
+struct WriteBackInner {
+    origin: Operator,
+    cache: Operator,
+
+    // Serializes background and explicit flushes
+    flush: Mutex<()>,
+}
+
+impl WriteBackCache {
+    pub fn new(origin: Operator, cache: Operator) -> Self {
+        Self {
+            inner: Arc::new(WriteBackInner {
+                origin,
+                cache,
+                flush: Mutex::new(()),
+            }),
+            task: None,
+        }
+    }
+
+    /// Write an object to the cache and schedule it for writing to the origin.
+    pub async fn write(&self, path: &str, content: impl Into<Buffer>) -> Result<(), Error> {
+        let buffer = content.into();
+
+        let entry = CacheEntry {
+            cached_at: SystemTime::now(),
+            size: buffer.len() as u64,
+            content_type: None,
+            etag: None,
+        };
+
+        let cache = &self.inner.cache;
+
+        cache.write(&data_path(path), buffer).await?;
+        cache.write(&meta_path(path), entry.to_string()).await?;
+
+        // A unique token lets flushes detect writes that happened while flushing
+        let token = format!("{}{:08x}", entry, fastrand::u32(..));
+        cache.write(&journal_path(path), token).await?;
+
+        Ok(())
+    }
+
+    /// Read an object, from the cache if possible.
+    pub async fn read(&self, path: &str) -> Result<Buffer, Error> {
+        match self.inner.cache.read(&data_path(path)).await {
+            Ok(buffer) => Ok(buffer),
+            Err(err) if err.kind() == ErrorKind::NotFound => self.inner.origin.read(path).await,
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Paths of the writes not flushed to the origin yet, sorted.
+    pub async fn pending(&self) -> Result<Vec<String>, Error> {
+        self.inner.pending().await
+    }
+
+    /// Write every pending write to the origin.
+    ///
+    /// Returns the number of flushed objects.
+    /// A failing write doesn't block the other pending writes: if any write fails,
+    /// the first error is returned (with the number of failed and flushed writes as context)
+    /// once every pending write has been attempted.
+    pub async fn flush(&self) -> Result<usize, Error> {
+        self.inner.flush().await
+    }
+
+    /// Start flushing pending writes in the background every `interval`.
+    ///
+    /// Failed flushes are retried on the next tick.
+    /// Calling `start` on a cache that is already flushing in the background has no effect.
+    pub fn start(&mut self, interval: Duration) {
+        if self.task.is_some() {
+            return;
+        }
+
+        let (shutdown, mut shutdown_rx) = watch::channel(false);
+        let inner = self.inner.clone();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown_rx.changed() => break,
+                }
+
+                let _ = inner.flush().await;
+            }
+        });
+
+        self.task = Some((shutdown, task));
+    }
+
+    /// Stop flushing in the background and flush the remaining pending writes.
+    pub async fn stop(&mut self) -> Result<usize, Error> {
+        if let Some((shutdown, task)) = self.task.take() {
+            let _ = shutdown.send(true);
+            let _ = task.await;
+        }
+
+        self.flush().await
+    }
+}
+
+impl Drop for WriteBackCache {
+    fn drop(&mut self) {
+        if let Some((shutdown, _)) = self.task.take() {
+            let _ = shutdown.send(true);
+        }
+    }
+}
+
+impl WriteBackInner {
+    async fn pending(&self) -> Result<Vec<String>, Error> {
+        let options = ListOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let mut paths: Vec<String> = list::list(&self.cache, JOURNAL_PREFIX, Some(options))
+            .await?
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .map(|entry| {
+                let path = entry.path();
+
+                path.strip_prefix(JOURNAL_PREFIX)
+                    .unwrap_or(path)
+                    .to_string()
+            })
+            .collect();
+
+        paths.sort();
+
+        Ok(paths)
+    }
+
+    async fn flush(&self) -> Result<usize, Error> {
+        let _guard = self.flush.lock().await;
+
+        let mut flushed = 0;
+        let mut failures = Vec::new();
+
+        for path in self.pending().await? {
+            match self.flush_path(&path).await {
+                Ok(()) => flushed += 1,
+                Err(err) => failures.push((path, err)),
+            }
+        }
+
+        let failed = failures.len();
+
+        match failures.into_iter().next() {
+            Some((path, err)) => Err(err
+                .with_context("path", path)
+                .with_context("failed", failed)
+                .with_context("flushed", flushed)),
+            None => Ok(flushed),
+        }
+    }
+
+    async fn flush_path(&self, path: &str) -> Result<(), Error> {
+        let journal_path = journal_path(path);
+
+        let token = self.cache.read(&journal_path).await?;
+        let buffer = self.cache.read(&data_path(path)).await?;
+
+        self.origin.write(path, buffer).await?;
+
+        // Keep the journal entry if the object was written again in the meantime
+        if self.cache.read(&journal_path).await?.to_bytes() == token.to_bytes() {
+            self.cache.delete(&journal_path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_entry(cache: &Operator, path: &str) -> Result<Option<CacheEntry>, Error> {
+    match cache.read(&meta_path(path)).await {
+        Ok(buffer) => {
+            let content = String::from_utf8(buffer.to_vec()).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "Invalid cache entry").set_source(err)
+            })?;
+
+            Ok(Some(content.parse()?))
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn data_path(path: &str) -> String {
+    format!("{}{}", DATA_PREFIX, path.trim_start_matches('/'))
+}
+
+fn meta_path(path: &str) -> String {
+    format!("{}{}", META_PREFIX, path.trim_start_matches('/'))
+}
+
+fn journal_path(path: &str) -> String {
+    format!("{}{}", JOURNAL_PREFIX, path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::raw::{
+        Access, Layer, LayeredAccess, OpList, OpRead, OpStat, OpWrite, RpDelete, RpList, RpRead,
+        RpStat, RpWrite,
+    };
+    use opendal::services::Memory;
+
+    use super::*;
+
+    // Reports the size of files as their etag (the memory service has no etags).
+    #[derive(Debug, Clone, Copy)]
+    struct EtagLayer;
+
+    #[derive(Debug)]
+    struct EtagAccessor<A> {
+        inner: A,
+    }
+
+    impl<A: Access> Layer<A> for EtagLayer {
+        type LayeredAccess = EtagAccessor<A>;
+
+        fn layer(&self, inner: A) -> Self::LayeredAccess {
+            EtagAccessor { inner }
+        }
+    }
+
+    impl<A: Access> LayeredAccess for EtagAccessor<A> {
+        type Inner = A;
+        type Reader = A::Reader;
+        type Writer = A::Writer;
+        type Lister = A::Lister;
+        type Deleter = A::Deleter;
+        type Copier = A::Copier;
+
+        fn inner(&self) -> &Self::Inner {
+            &self.inner
+        }
+
+        async fn stat(&self, path: &str, args: OpStat) -> opendal::Result<RpStat> {
+            let meta = self.inner.stat(path, args).await?.into_metadata();
+            let etag = format!("\"{}\"", meta.content_length());
+
+            Ok(RpStat::new(meta.with_etag(etag)))
+        }
+
+        async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+            self.inner.read(path, args).await
+        }
+
+        async fn write(
+            &self,
+            path: &str,
+            args: OpWrite,
+        ) -> opendal::Result<(RpWrite, Self::Writer)> {
+            self.inner.write(path, args).await
+        }
+
+        async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+            self.inner.delete().await
+        }
+
+        async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+            self.inner.list(path, args).await
+        }
+    }
+
+    #[test]
+    fn test_cache_entry_roundtrip() {
+        let entry = CacheEntry {
+            cached_at: UNIX_EPOCH + Duration::from_millis(1_700_000_000_000),
+            size: 42,
+            content_type: Some("text/plain".to_string()),
+            etag: None,
+        };
+
+        let text = entry.to_string();
+        assert_eq!(
+            text,
+            "cached_at 1700000000000\nsize 42\ncontent_type text/plain\n"
+        );
+        assert_eq!(text.parse::<CacheEntry>().unwrap(), entry);
+    }
+
+    #[tokio::test]
+    async fn test_cached_reader() -> Result<(), Error> {
+        let origin = Operator::new(Memory::default())?.finish();
+        let cache = Operator::new(Memory::default())?.finish();
+
+        origin.write("file.txt", "foo").await?;
+
+        let reader = CachedReader::new(origin.clone(), cache.clone());
+
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"foo");
+        assert_eq!(reader.entry("file.txt").await?.unwrap().size, 3);
+
+        // Served from the cache
+        origin.write("file.txt", "bar").await?;
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"foo");
+
+        reader.invalidate("file.txt").await?;
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"bar");
+
+        // Entries expire immediately
+        let reader = reader.with_options(CacheOptions {
+            ttl: Some(Duration::ZERO),
+            ..Default::default()
+        });
+
+        origin.write("file.txt", "baz").await?;
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"baz");
+
+        assert!(
+            reader
+                .read("missing.txt")
+                .await
+                .is_err_and(|err| err.kind() == ErrorKind::NotFound)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cached_reader_size_limits() -> Result<(), Error> {
+        let origin = Operator::new(Memory::default())?.finish();
+        let cache = Operator::new(Memory::default())?.finish();
+
+        origin.write("small.txt", "a").await?;
+        origin.write("large.txt", "aaaaaaaaaa").await?;
+        origin.write("first.txt", "aaa").await?;
+        origin.write("second.txt", "bbb").await?;
+
+        let reader = CachedReader::new(origin, cache).with_options(CacheOptions {
+            max_object_size: Some(5),
+            max_size: Some(5),
+            ..Default::default()
+        });
+
+        reader.read("large.txt").await?;
+        assert!(reader.entry("large.txt").await?.is_none());
+
+        reader.read("first.txt").await?;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        reader.read("small.txt").await?;
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        reader.read("second.txt").await?;
+
+        // The oldest entry is evicted
+        assert!(reader.entry("first.txt").await?.is_none());
+        assert!(reader.entry("small.txt").await?.is_some());
+        assert!(reader.entry("second.txt").await?.is_some());
+
+        // The cache is only pruned once its estimated size exceeds the limit
+        let reader = reader.with_options(CacheOptions {
+            ttl: Some(Duration::ZERO),
+            max_size: Some(100),
+            ..Default::default()
+        });
+
+        reader.read("first.txt").await?;
+        reader.read("small.txt").await?;
+        assert!(reader.entry("first.txt").await?.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_back_cache() -> Result<(), Error> {
+        let origin = Operator::new(Memory::default())?.finish();
+        let cache = Operator::new(Memory::default())?.finish();
+
+        origin.write("existing.txt", "origin").await?;
+
+        let cache = WriteBackCache::new(origin.clone(), cache);
+
+        cache.write("dir/file.txt", "foo").await?;
+        cache.write("other.txt", "bar").await?;
+
+        // Writes are visible before they reach the origin
+        assert_eq!(cache.read("dir/file.txt").await?.to_vec(), b"foo");
+        assert_eq!(cache.read("existing.txt").await?.to_vec(), b"origin");
+        assert!(!origin.exists("dir/file.txt").await?);
+
+        assert_eq!(cache.pending().await?, vec!["dir/file.txt", "other.txt"]);
+
+        assert_eq!(cache.flush().await?, 2);
+        assert!(cache.pending().await?.is_empty());
+
+        assert_eq!(origin.read("dir/file.txt").await?.to_vec(), b"foo");
+        assert_eq!(origin.read("other.txt").await?.to_vec(), b"bar");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_back_cache_flush_failures() -> Result<(), Error> {
+        let origin = Operator::new(Memory::default())?.finish();
+        let cache_op = Operator::new(Memory::default())?.finish();
+
+        let cache = WriteBackCache::new(origin.clone(), cache_op.clone());
+
+        // Paths starting like the bookkeeping prefixes
+        cache.write("pending/report", "report").await?;
+        cache.write("broken.txt", "broken").await?;
+        cache.write("other.txt", "other").await?;
+
+        assert_eq!(
+            cache.pending().await?,
+            vec!["broken.txt", "other.txt", "pending/report"]
+        );
+
+        cache_op.delete("data/broken.txt").await?;
+
+        let err = cache.flush().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // The other writes are flushed anyway
+        assert_eq!(origin.read("pending/report").await?.to_vec(), b"report");
+        assert_eq!(origin.read("other.txt").await?.to_vec(), b"other");
+        assert_eq!(cache.pending().await?, vec!["broken.txt"]);
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_write_back_cache_background_flush() -> Result<(), Error> {
+        let origin = Operator::new(Memory::default())?.finish();
+        let cache = Operator::new(Memory::default())?.finish();
+
+        let mut cache = WriteBackCache::new(origin.clone(), cache);
+        cache.start(Duration::from_secs(10));
+
+        cache.write("file.txt", "foo").await?;
+
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        assert_eq!(origin.read("file.txt").await?.to_vec(), b"foo");
+
+        cache.write("file.txt", "bar").await?;
+        assert_eq!(cache.stop().await?, 1);
+        assert_eq!(origin.read("file.txt").await?.to_vec(), b"bar");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_through() -> Result<(), Error> {
+        let primary = Operator::new(Memory::default())?.layer(EtagLayer).finish();
+        let cache = Operator::new(Memory::default())?.finish();
+
+        primary.write("file.txt", "foo").await?;
+
+        let reader = ReadThrough::new(primary.clone(), cache.clone());
+
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"foo");
+        assert_eq!(cache.read("data/file.txt").await?.to_vec(), b"foo");
+
+        let entry = read_entry(&cache, "file.txt").await?.unwrap();
+        assert_eq!(entry.size, 3);
+        assert_eq!(entry.etag.as_deref(), Some("\"3\""));
+
+        // Served from the cache
+        primary.write("file.txt", "bar").await?;
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"foo");
+
+        // Entries expire immediately, but the etag did not change
+        let reader = reader.with_ttl(Duration::ZERO);
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"foo");
+
+        primary.write("file.txt", "quux").await?;
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"quux");
+        assert_eq!(cache.read("data/file.txt").await?.to_vec(), b"quux");
+
+        assert!(
+            reader
+                .read("missing.txt")
+                .await
+                .is_err_and(|err| err.kind() == ErrorKind::NotFound)
+        );
+
+        Ok(())
+    }
+}
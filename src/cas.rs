@@ -0,0 +1,163 @@
+use futures::{Stream, TryStreamExt};
+use opendal::{Buffer, Error, ErrorKind, Operator};
+
+use crate::{Digest, HashAlgorithm, Hasher};
+
+/// Path of the object with the given digest under `prefix` (`<prefix>/<algorithm>/<hash>`).
+pub fn path(prefix: &str, digest: &Digest) -> String {
+    let prefix = prefix.trim_end_matches('/');
+
+    if prefix.is_empty() {
+        return format!("{}/{}", digest.algorithm, digest.hex);
+    }
+
+    format!("{}/{}/{}", prefix, digest.algorithm, digest.hex)
+}
+
+/// Store content under `prefix`, addressed by its digest.
+///
+/// Identical content is only ever stored once, regardless of how many times it is put.
+///
+/// The content is hashed with SHA-256 while being written to a staging object,
+/// which is then moved to its content address. If an object with the same digest already exists,
+/// the staged copy is discarded.
+pub async fn put<S>(operator: &Operator, prefix: &str, stream: S) -> Result<Digest, Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    let staging = staging_path(prefix);
+
+    let result = stage_and_promote(operator, prefix, &staging, stream).await;
+
+    // Rename already removed the staging object on success, but cleanup is harmless
+    let _ = operator.delete(&staging).await;
+
+    result
+}
+
+/// Read the object with the given digest from `prefix`.
+pub async fn get(operator: &Operator, prefix: &str, digest: &Digest) -> Result<Buffer, Error> {
+    operator.read(&path(prefix, digest)).await
+}
+
+/// Returns `true` if an object with the given digest exists under `prefix`.
+pub async fn contains(operator: &Operator, prefix: &str, digest: &Digest) -> Result<bool, Error> {
+    match operator.stat(&path(prefix, digest)).await {
+        Ok(_) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+async fn stage_and_promote<S>(
+    operator: &Operator,
+    prefix: &str,
+    staging: &str,
+    mut stream: S,
+) -> Result<Digest, Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    let mut hasher = Hasher::new(HashAlgorithm::Sha256);
+    let mut writer = operator.writer(staging).await?;
+
+    while let Some(chunk) = stream.try_next().await? {
+        for bytes in chunk.clone() {
+            hasher.update(&bytes);
+        }
+
+        if let Err(err) = writer.write(chunk).await {
+            let _ = writer.abort().await;
+
+            return Err(err);
+        }
+    }
+
+    writer.close().await?;
+
+    let digest = hasher.finalize();
+
+    if contains(operator, prefix, &digest).await? {
+        return Ok(digest);
+    }
+
+    let target = path(prefix, &digest);
+    let capability = operator.info().full_capability();
+
+    if capability.rename {
+        operator.rename(staging, &target).await?;
+    } else if capability.copy {
+        operator.copy(staging, &target).await?;
+    } else {
+        let mut stream = operator.reader(staging).await?.into_stream(..).await?;
+        let mut writer = operator.writer(&target).await?;
+
+        while let Some(chunk) = stream.try_next().await? {
+            writer.write(chunk).await?;
+        }
+
+        writer.close().await?;
+    }
+
+    Ok(digest)
+}
+
+fn staging_path(prefix: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    let name = format!(".tmp/{:016x}", fastrand::u64(..));
+
+    if prefix.is_empty() {
+        return name;
+    }
+
+    format!("{}/{}", prefix, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_get() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let chunks = vec![Ok(Buffer::from("fo")), Ok(Buffer::from("o"))];
+        let digest = put(&operator, "store/", stream::iter(chunks)).await?;
+
+        assert_eq!(
+            digest.to_string(),
+            "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert_eq!(
+            path("store/", &digest),
+            "store/sha256/2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+
+        let buffer = get(&operator, "store", &digest).await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        // Storing the same content again yields the same digest
+        let again = put(
+            &operator,
+            "store",
+            stream::iter(vec![Ok(Buffer::from("foo"))]),
+        )
+        .await?;
+        assert_eq!(again, digest);
+
+        // No staging objects are left behind
+        let entries = operator
+            .list_with("store/")
+            .recursive(true)
+            .await?
+            .into_iter()
+            .filter(|entry| entry.metadata().is_file())
+            .count();
+        assert_eq!(entries, 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,486 @@
+use std::fmt;
+use std::str::FromStr;
+
+use futures::{StreamExt, TryFutureExt, TryStreamExt};
+use md5::Md5;
+use opendal::{Error, ErrorKind, Operator};
+use sha2::{Digest as _, Sha256};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::Mismatch;
+use crate::copy::{IoErrorExt, normalize_path};
+
+// Number of files verified at the same time by verify_manifest.
+const CONCURRENCY: usize = 8;
+
+/// Hash algorithms supported for checksums.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum HashAlgorithm {
+    Md5,
+    #[default]
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            _ => Err(
+                Error::new(ErrorKind::Unsupported, "Unsupported hash algorithm")
+                    .with_context("algorithm", s),
+            ),
+        }
+    }
+}
+
+/// The digest of some content, computed with a specific [`HashAlgorithm`].
+///
+/// Digests are formatted (and parsed) as `<algorithm>:<lowercase hex>`, e.g. `sha256:2c26b4...`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Digest {
+    pub algorithm: HashAlgorithm,
+
+    /// Lowercase hex encoded hash.
+    pub hex: String,
+}
+
+impl Digest {
+    pub fn new(algorithm: HashAlgorithm, hex: impl Into<String>) -> Self {
+        Self {
+            algorithm,
+            hex: hex.into().to_lowercase(),
+        }
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.hex)
+    }
+}
+
+impl FromStr for Digest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, hex) = s.split_once(':').ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "Invalid digest").with_context("digest", s)
+        })?;
+
+        Ok(Digest::new(algorithm.parse()?, hex))
+    }
+}
+
+/// Incrementally computes a [`Digest`].
+#[derive(Clone)]
+pub enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            Hasher::Md5(_) => HashAlgorithm::Md5,
+            Hasher::Sha256(_) => HashAlgorithm::Sha256,
+        }
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> Digest {
+        match self {
+            Hasher::Md5(hasher) => Digest::new(HashAlgorithm::Md5, hex::encode(hasher.finalize())),
+            Hasher::Sha256(hasher) => {
+                Digest::new(HashAlgorithm::Sha256, hex::encode(hasher.finalize()))
+            }
+        }
+    }
+}
+
+/// Compute the digest of an object by streaming its content.
+pub async fn checksum(
+    operator: &Operator,
+    path: &str,
+    algorithm: HashAlgorithm,
+) -> Result<Digest, Error> {
+    let reader = operator.reader(path).await?;
+    let mut stream = reader.into_bytes_stream(..).await?;
+
+    let mut hasher = Hasher::new(algorithm);
+
+    while let Some(chunk) = stream
+        .try_next()
+        .map_err(IoErrorExt::into_opendal_error)
+        .await?
+    {
+        hasher.update(&chunk);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Incrementally computes an S3 compatible multipart ETag.
+///
+/// Objects uploaded to S3 in multiple parts don't have the MD5 of their content as ETag,
+/// but the MD5 of the concatenated MD5s of every part, followed by `-` and the number of parts
+/// (e.g. `0546e2414cde17fed52cd47c263757e7-2`).
+/// Computing the same value locally allows verifying such objects without downloading them.
+#[derive(Clone)]
+pub struct MultipartEtagHasher {
+    part_size: u64,
+    part: Md5,
+    part_len: u64,
+    digests: Vec<u8>,
+}
+
+impl MultipartEtagHasher {
+    /// Create a hasher for objects uploaded with parts of `part_size` bytes (the last part may be smaller).
+    pub fn new(part_size: u64) -> Self {
+        Self {
+            part_size: part_size.max(1),
+            part: Md5::new(),
+            part_len: 0,
+            digests: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let mut data = data.as_ref();
+
+        while !data.is_empty() {
+            let remaining = (self.part_size - self.part_len) as usize;
+            let (head, tail) = data.split_at(remaining.min(data.len()));
+
+            self.part.update(head);
+            self.part_len += head.len() as u64;
+            data = tail;
+
+            if self.part_len == self.part_size {
+                self.finish_part();
+            }
+        }
+    }
+
+    /// Return the ETag (without surrounding quotes).
+    pub fn finalize(mut self) -> String {
+        if self.part_len > 0 || self.digests.is_empty() {
+            self.finish_part();
+        }
+
+        let parts = self.digests.len() / 16;
+
+        format!("{}-{}", hex::encode(Md5::digest(&self.digests)), parts)
+    }
+
+    fn finish_part(&mut self) {
+        let part = std::mem::replace(&mut self.part, Md5::new());
+
+        self.digests.extend_from_slice(&part.finalize());
+        self.part_len = 0;
+    }
+}
+
+/// Compute the S3 multipart ETag of an object by streaming its content
+/// (see [`MultipartEtagHasher`]).
+pub async fn multipart_etag(
+    operator: &Operator,
+    path: &str,
+    part_size: u64,
+) -> Result<String, Error> {
+    let reader = operator.reader(path).await?;
+    let mut stream = reader.into_bytes_stream(..).await?;
+
+    let mut hasher = MultipartEtagHasher::new(part_size);
+
+    while let Some(chunk) = stream
+        .try_next()
+        .map_err(IoErrorExt::into_opendal_error)
+        .await?
+    {
+        hasher.update(&chunk);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Check whether an ETag is a multipart ETag matching the content of an object.
+///
+/// The ETag may be quoted. Returns `false` for ETags that aren't multipart ETags.
+pub async fn verify_multipart_etag(
+    operator: &Operator,
+    path: &str,
+    etag: &str,
+    part_size: u64,
+) -> Result<bool, Error> {
+    let etag = etag.trim_matches('"');
+
+    if !etag.contains('-') {
+        return Ok(false);
+    }
+
+    Ok(multipart_etag(operator, path, part_size)
+        .await?
+        .eq_ignore_ascii_case(etag))
+}
+
+/// Result of [`verify_manifest`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Number of files listed in the manifest.
+    pub total: usize,
+
+    /// Files that are missing or whose content does not match the manifest, in the order of the manifest.
+    pub mismatches: Vec<(String, Mismatch)>,
+}
+
+impl VerifyReport {
+    pub fn is_success(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Verify the files listed in a checksum manifest stored in the operator (like `sha256sum -c`).
+///
+/// Every line of the manifest has the form `<digest>  <path>`, as written by `sha256sum` and `md5sum`.
+/// The algorithm is inferred from the length of the hex digest, unless the digest has an algorithm prefix
+/// (e.g. `sha256:2c26b4...`). Paths are relative to the directory of the manifest.
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// Files are streamed and verified concurrently. Fails if the manifest can't be read or parsed;
+/// files that are missing or don't match are reported in the [`VerifyReport`].
+pub async fn verify_manifest(
+    operator: &Operator,
+    manifest_path: &str,
+) -> Result<VerifyReport, Error> {
+    let content = operator.read(manifest_path).await?.to_vec();
+    let content = String::from_utf8(content).map_err(|err| {
+        Error::new(
+            ErrorKind::Unexpected,
+            "Checksum manifest is not valid UTF-8",
+        )
+        .with_context("path", manifest_path)
+        .set_source(err)
+    })?;
+
+    let dir = match normalize_path(manifest_path).as_str().rsplit_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => String::new(),
+    };
+
+    let entries = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            parse_manifest_line(line).map_err(|err| {
+                err.with_context("path", manifest_path)
+                    .with_context("line", index + 1)
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let total = entries.len();
+
+    let results: Vec<Option<(String, Mismatch)>> = futures::stream::iter(entries)
+        .map(|(expected, path)| {
+            let full_path = format!("{}{}", dir, path);
+
+            async move {
+                match checksum(operator, &full_path, expected.algorithm).await {
+                    Ok(actual) if actual == expected => Ok(None),
+                    Ok(_) => Ok(Some((path, Mismatch::Content))),
+                    Err(err) if err.kind() == ErrorKind::NotFound => {
+                        Ok(Some((path, Mismatch::Missing)))
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+        })
+        .buffered(CONCURRENCY)
+        .try_collect()
+        .await?;
+
+    Ok(VerifyReport {
+        total,
+        mismatches: results.into_iter().flatten().collect(),
+    })
+}
+
+// Parse a `<digest>  <path>` line of a checksum manifest.
+fn parse_manifest_line(line: &str) -> Result<(Digest, String), Error> {
+    let invalid = || Error::new(ErrorKind::ConfigInvalid, "Invalid checksum manifest line");
+
+    let (digest, path) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+
+    // A leading `*` marks files hashed in binary mode
+    let path = path.trim_start();
+    let path = path.strip_prefix('*').unwrap_or(path);
+
+    if path.is_empty() {
+        return Err(invalid());
+    }
+
+    let digest = match digest.split_once(':') {
+        Some(_) => digest.parse()?,
+        None => {
+            let algorithm = match digest.len() {
+                32 => HashAlgorithm::Md5,
+                64 => HashAlgorithm::Sha256,
+                _ => return Err(invalid().with_context("digest", digest)),
+            };
+
+            Digest::new(algorithm, digest)
+        }
+    };
+
+    if !digest.hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid().with_context("digest", digest.to_string()));
+    }
+
+    Ok((digest, path.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_checksum() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("file.txt", "foo").await?;
+
+        let digest = checksum(&operator, "file.txt", HashAlgorithm::Sha256).await?;
+        assert_eq!(
+            digest.to_string(),
+            "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+
+        let digest = checksum(&operator, "file.txt", HashAlgorithm::Md5).await?;
+        assert_eq!(digest.to_string(), "md5:acbd18db4cc2f85cedef654fccc4a4d8");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_manifest() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("release/foo.txt", "foo").await?;
+        operator.write("release/bar.txt", "changed").await?;
+        operator
+            .write(
+                "release/SHA256SUMS",
+                "# release checksums\n\
+                 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae  foo.txt\n\
+                 fcde2b2edba56bf408601fb721fe9b5c338d10ee429ea04fae5511b68fbf8fb9 *bar.txt\n\
+                 \n\
+                 md5:acbd18db4cc2f85cedef654fccc4a4d8  missing.txt\n",
+            )
+            .await?;
+
+        let report = verify_manifest(&operator, "release/SHA256SUMS").await?;
+        assert_eq!(report.total, 3);
+        assert_eq!(
+            report.mismatches,
+            vec![
+                ("bar.txt".to_string(), Mismatch::Content),
+                ("missing.txt".to_string(), Mismatch::Missing),
+            ]
+        );
+
+        operator.write("bad", "abc  file.txt").await?;
+        let err = verify_manifest(&operator, "bad").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multipart_etag() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("file.txt", "foobar").await?;
+
+        let etag = multipart_etag(&operator, "file.txt", 4).await?;
+        assert_eq!(etag, "0546e2414cde17fed52cd47c263757e7-2");
+
+        // Part boundaries don't depend on chunk boundaries
+        let mut hasher = MultipartEtagHasher::new(3);
+        hasher.update("fo");
+        hasher.update("ob");
+        hasher.update("ar");
+        assert_eq!(hasher.finalize(), "0105fcbc9eea8193de8e1834677b6c6b-2");
+
+        assert!(
+            verify_multipart_etag(
+                &operator,
+                "file.txt",
+                "\"0546E2414CDE17FED52CD47C263757E7-2\"",
+                4
+            )
+            .await?
+        );
+        assert!(
+            !verify_multipart_etag(&operator, "file.txt", "0546e2414cde17fed52cd47c263757e7", 4)
+                .await?
+        );
+
+        let mut hasher = MultipartEtagHasher::new(10);
+        hasher.update("foo");
+        assert_eq!(hasher.finalize(), "47847ae721df523d6388aebc9c94d656-1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_digest() {
+        let digest: Digest = "sha256:ABCDEF".parse().unwrap();
+
+        assert_eq!(digest, Digest::new(HashAlgorithm::Sha256, "abcdef"));
+        assert!("crc32:abcdef".parse::<Digest>().is_err());
+        assert!("abcdef".parse::<Digest>().is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use typed_path::Utf8UnixPath;
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("7z", "application/x-7z-compressed"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("bz2", "application/x-bzip2"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("gif", "image/gif"),
+    ("gz", "application/gzip"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("js", "text/javascript"),
+    ("json", "application/json"),
+    ("jsonl", "application/jsonl"),
+    ("md", "text/markdown"),
+    ("mjs", "text/javascript"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("ndjson", "application/x-ndjson"),
+    ("parquet", "application/vnd.apache.parquet"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("svg", "image/svg+xml"),
+    ("tar", "application/x-tar"),
+    ("toml", "application/toml"),
+    ("tsv", "text/tab-separated-values"),
+    ("txt", "text/plain"),
+    ("wasm", "application/wasm"),
+    ("webm", "video/webm"),
+    ("webp", "image/webp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("zip", "application/zip"),
+    ("zst", "application/zstd"),
+];
+
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"PAR1", "application/vnd.apache.parquet"),
+    (b"\0asm", "application/wasm"),
+    (b"wOFF", "font/woff"),
+    (b"wOF2", "font/woff2"),
+    (b"ID3", "audio/mpeg"),
+    (b"\x1a\x45\xdf\xa3", "video/webm"),
+];
+
+/// Guess the content type of a file from the extension of its name.
+pub(crate) fn from_extension(path: &str) -> Option<&'static str> {
+    let extension = Utf8UnixPath::new(path).extension()?.to_ascii_lowercase();
+
+    EXTENSIONS
+        .iter()
+        .find(|(candidate, _)| *candidate == extension)
+        .map(|(_, content_type)| *content_type)
+}
+
+/// Guess the content type of a file from its first bytes (magic numbers).
+pub(crate) fn from_magic(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return Some(match &bytes[8..12] {
+            b"avif" | b"avis" => "image/avif",
+            _ => "video/mp4",
+        });
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map(|(_, content_type)| *content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection() {
+        assert_eq!(from_extension("site/index.HTML"), Some("text/html"));
+        assert_eq!(
+            from_extension("data/archive.tar.gz"),
+            Some("application/gzip")
+        );
+        assert_eq!(from_extension("README"), None);
+        assert_eq!(from_extension("file.unknown"), None);
+
+        assert_eq!(from_magic(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(from_magic(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(from_magic(b"\0\0\0\x20ftypisom"), Some("video/mp4"));
+        assert_eq!(from_magic(b"hello"), None);
+    }
+}
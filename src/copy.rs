@@ -1,9 +1,16 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::fmt;
+use std::future::Future;
 use std::io;
+use std::sync::Arc;
+use std::time::Instant;
 
 use content_disposition::parse_content_disposition;
-use futures::{TryFutureExt, TryStreamExt};
-use opendal::{EntryMode, Error, ErrorKind, Metadata, Operator, options::ListOptions};
+use futures::{StreamExt, TryFutureExt, TryStreamExt};
+use opendal::raw::{Accessor, Layer};
+use opendal::{
+    Entry, EntryMode, Error, ErrorKind, Metadata, Operator, Writer, options::ListOptions,
+};
 use typed_path::Utf8UnixPathBuf;
 
 #[cfg(feature = "schemars")]
@@ -11,11 +18,110 @@ use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{glob, list};
-
+#[cfg(feature = "serde")]
+use crate::StatusWriter;
+use crate::crc32c::Crc32c;
+use crate::quota::{Quota, Reservation};
+use crate::stats::Backend;
+use crate::template::DestinationTemplate;
+use crate::{
+    CopiedEntry, CopyCheckpoint, CopyJournal, DirCreator, HashAlgorithm, LayerConfig, Pipeline,
+    RateLimiter, RetryPolicy, Sink, SinkWriter, TransferBudget, TransferStats, checksum,
+    content_type, glob, list, telemetry, write,
+};
+
+#[derive(Clone)]
 pub struct Copier {
-    source: Operator,
-    destination: Operator,
+    pub(crate) source: Operator,
+    pub(crate) destination: Operator,
+    rate_limiter: Option<RateLimiter>,
+    budget: Option<TransferBudget>,
+    retry_policy: Option<RetryPolicy>,
+    pipeline: Option<Pipeline>,
+    sink: Option<Arc<dyn Sink>>,
+    observer: Option<Arc<dyn CopyObserver>>,
+    journal: Option<CopyJournal>,
+    #[cfg(feature = "serde")]
+    status: Option<StatusWriter>,
+    atomic_writes: bool,
+    detect_content_type: bool,
+    dedup: bool,
+    integrity_check: bool,
+    quota: Option<Quota>,
+    source_root: String,
+    destination_root: String,
+    options: CopyOptions,
+    stats: TransferStats,
+}
+
+/// Progress of a copy, reported to a [`CopyObserver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyEvent {
+    /// A file transfer started (or restarted after a failed attempt).
+    FileStarted {
+        source: String,
+        destination: String,
+
+        /// Size of the source file in bytes (`0` if unknown).
+        size: u64,
+    },
+
+    /// A chunk of a file was transferred.
+    FileProgress {
+        source: String,
+
+        /// Number of bytes transferred since the previous event of the file.
+        bytes: u64,
+    },
+
+    /// A file was transferred successfully.
+    FileCompleted {
+        source: String,
+        destination: String,
+
+        /// Total number of bytes transferred.
+        bytes: u64,
+    },
+
+    /// A file was not transferred because the destination already contains the same content
+    /// (see [`Copier::with_dedup`]).
+    FileSkipped { source: String, destination: String },
+
+    /// A file transfer attempt failed.
+    FileFailed {
+        source: String,
+        destination: String,
+        error: String,
+    },
+}
+
+/// Receives [`CopyEvent`]s while a [`Copier`] is copying.
+///
+/// Observers are called inline, so they should return quickly.
+/// Closures and unbounded channel senders implement this trait.
+pub trait CopyObserver: Send + Sync {
+    fn on_event(&self, event: &CopyEvent);
+}
+
+impl<F> CopyObserver for F
+where
+    F: Fn(&CopyEvent) + Send + Sync,
+{
+    fn on_event(&self, event: &CopyEvent) {
+        self(event)
+    }
+}
+
+impl<T: CopyObserver + ?Sized> CopyObserver for Arc<T> {
+    fn on_event(&self, event: &CopyEvent) {
+        (**self).on_event(event)
+    }
+}
+
+impl CopyObserver for tokio::sync::mpsc::UnboundedSender<CopyEvent> {
+    fn on_event(&self, event: &CopyEvent) {
+        let _ = self.send(event.clone());
+    }
 }
 
 /// Options for controlling copy behavior.
@@ -37,6 +143,230 @@ pub struct CopyOptions {
     /// When `true`, paths are treated literally, allowing copying of files whose
     /// names contain glob characters.
     pub disable_glob: bool,
+
+    /// Whether to copy a source directory itself rather than its contents (like `cp -r dir dest/`).
+    ///
+    /// When `true`, copying `data/` to `backup/` creates `backup/data/...`.
+    /// When `false` (the default), it creates `backup/...`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub copy_dir_itself: bool,
+
+    /// How to handle links found while copying a directory or glob (see [`LinkPolicy`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub links: LinkPolicy,
+
+    /// Whether to expand placeholders in the destination (see [`Copier::copy_options`]).
+    ///
+    /// Ignored when `disable_glob` is set, since literal paths may contain braces.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub template: bool,
+}
+
+/// How links (e.g. symbolic links of filesystem services) are handled when copying.
+///
+/// Services list links as entries of unknown type (see [`EntryMode::Unknown`]),
+/// so every entry of unknown type is treated as a link.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum LinkPolicy {
+    /// Copy the content of the files links point to.
+    ///
+    /// Links to directories are skipped, since following them could loop forever.
+    #[default]
+    Follow,
+
+    /// Skip links.
+    Skip,
+
+    /// Fail the copy when a link is found.
+    Error,
+}
+
+impl LinkPolicy {
+    // Whether to follow a link (or fail).
+    fn follow(self, path: &str) -> Result<bool, CopyError> {
+        match self {
+            LinkPolicy::Follow => Ok(true),
+            LinkPolicy::Skip => Ok(false),
+            LinkPolicy::Error => Err(CopyError::new(
+                CopyPhase::List,
+                Error::new(ErrorKind::Unsupported, "Source contains a link"),
+            )
+            .with_source_path(path)),
+        }
+    }
+}
+
+/// Stage of a copy at which a [`CopyError`] occurred.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CopyPhase {
+    /// Looking up the source or the destination.
+    Stat,
+
+    /// Listing the source.
+    List,
+
+    /// Reading a source file.
+    Read,
+
+    /// Writing a destination file (or creating a directory).
+    Write,
+
+    /// Completing a destination file.
+    Close,
+}
+
+impl fmt::Display for CopyPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CopyPhase::Stat => "stat",
+            CopyPhase::List => "list",
+            CopyPhase::Read => "read",
+            CopyPhase::Write => "write",
+            CopyPhase::Close => "close",
+        })
+    }
+}
+
+/// Error returned by [`Copier`] copies.
+///
+/// Carries the paths of the file being copied, the [`CopyPhase`] that failed
+/// and the files copied before the error. Converts into an [`opendal::Error`] (with the same context)
+/// for callers that don't care about the details.
+#[derive(Debug)]
+pub struct CopyError(Box<CopyErrorInner>);
+
+#[derive(Debug)]
+struct CopyErrorInner {
+    phase: CopyPhase,
+    source_path: Option<String>,
+    destination_path: Option<String>,
+    completed: Vec<CopiedEntry>,
+    error: Error,
+}
+
+impl CopyError {
+    pub fn new(phase: CopyPhase, error: Error) -> Self {
+        Self(Box::new(CopyErrorInner {
+            phase,
+            source_path: None,
+            destination_path: None,
+            completed: Vec::new(),
+            error,
+        }))
+    }
+
+    pub fn phase(&self) -> CopyPhase {
+        self.0.phase
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.0.error.kind()
+    }
+
+    /// Path of the source file (or directory) being copied, if known.
+    pub fn source_path(&self) -> Option<&str> {
+        self.0.source_path.as_deref()
+    }
+
+    /// Path of the destination file (or directory) being written, if known.
+    pub fn destination_path(&self) -> Option<&str> {
+        self.0.destination_path.as_deref()
+    }
+
+    /// Files copied before the error
+    /// (including files skipped because the journal of the copier already contained them).
+    pub fn completed(&self) -> &[CopiedEntry] {
+        &self.0.completed
+    }
+
+    /// The underlying OpenDAL error.
+    pub fn inner(&self) -> &Error {
+        &self.0.error
+    }
+
+    pub fn into_inner(self) -> Error {
+        self.0.error
+    }
+
+    // Set the paths unless a more specific one is already known.
+    fn with_paths(self, source: &str, destination: &str) -> Self {
+        self.with_source_path(source)
+            .with_destination_path(destination)
+    }
+
+    fn with_source_path(mut self, source: &str) -> Self {
+        self.0.source_path.get_or_insert_with(|| source.to_string());
+        self
+    }
+
+    fn with_destination_path(mut self, destination: &str) -> Self {
+        self.0
+            .destination_path
+            .get_or_insert_with(|| destination.to_string());
+        self
+    }
+
+    fn with_completed(mut self, completed: Vec<CopiedEntry>) -> Self {
+        self.0.completed = completed;
+        self
+    }
+}
+
+impl fmt::Display for CopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "copy failed during {}", self.0.phase)?;
+
+        match (&self.0.source_path, &self.0.destination_path) {
+            (Some(source), Some(destination)) => write!(f, " ({} -> {})", source, destination)?,
+            (Some(path), None) | (None, Some(path)) => write!(f, " ({})", path)?,
+            (None, None) => (),
+        }
+
+        write!(f, ": {}", self.0.error)
+    }
+}
+
+impl std::error::Error for CopyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0.error)
+    }
+}
+
+impl From<CopyError> for Error {
+    fn from(err: CopyError) -> Self {
+        let CopyErrorInner {
+            phase,
+            source_path,
+            destination_path,
+            error,
+            ..
+        } = *err.0;
+
+        let mut error = error.with_context("phase", phase);
+
+        if let Some(source) = source_path {
+            error = error.with_context("source", source);
+        }
+
+        if let Some(destination) = destination_path {
+            error = error.with_context("destination", destination);
+        }
+
+        error
+    }
+}
+
+trait PhaseExt<T> {
+    fn phase(self, phase: CopyPhase) -> Result<T, CopyError>;
+}
+
+impl<T> PhaseExt<T> for Result<T, Error> {
+    fn phase(self, phase: CopyPhase) -> Result<T, CopyError> {
+        self.map_err(|err| CopyError::new(phase, err))
+    }
 }
 
 impl Copier {
@@ -44,42 +374,492 @@ impl Copier {
         Self {
             source,
             destination,
+            rate_limiter: None,
+            budget: None,
+            retry_policy: None,
+            pipeline: None,
+            sink: None,
+            observer: None,
+            journal: None,
+            #[cfg(feature = "serde")]
+            status: None,
+            atomic_writes: false,
+            detect_content_type: false,
+            dedup: false,
+            integrity_check: false,
+            quota: None,
+            source_root: String::new(),
+            destination_root: String::new(),
+            options: CopyOptions::default(),
+            stats: TransferStats::new(),
+        }
+    }
+
+    /// Configure a copier with path roots, layers, default options and observers.
+    ///
+    /// ```no_run
+    /// # fn example(source: opendal::Operator, destination: opendal::Operator) {
+    /// use opendal_util::{Copier, CopyOptions};
+    ///
+    /// let copier = Copier::builder(source, destination)
+    ///     .source_root("tenant-a/")
+    ///     .destination_root("backups/tenant-a/")
+    ///     .options(CopyOptions {
+    ///         recursive: true,
+    ///         ..Default::default()
+    ///     })
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn builder(source: Operator, destination: Operator) -> CopierBuilder {
+        CopierBuilder {
+            source,
+            destination,
+            source_root: String::new(),
+            destination_root: String::new(),
+            options: CopyOptions::default(),
+            observers: Vec::new(),
         }
     }
 
+    /// Throttle transfers using a (possibly shared) [`RateLimiter`].
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Share a [`TransferBudget`] with other copiers: files are only transferred when the budget allows it,
+    /// and transfers are throttled by its rate limiter (replacing the one set with [`Copier::with_rate_limiter`]).
+    pub fn with_budget(mut self, budget: TransferBudget) -> Self {
+        self.rate_limiter = Some(budget.rate_limiter().clone());
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Retry failed operations according to a [`RetryPolicy`].
+    ///
+    /// Stats, listings and individual file transfers are retried separately,
+    /// so a transient failure does not restart the whole copy.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Pass the content of every copied file through a transform [`Pipeline`].
+    pub fn with_pipeline(mut self, pipeline: Pipeline) -> Self {
+        self.pipeline = Some(pipeline);
+        self
+    }
+
+    // Pass the source content through a transform ahead of the pipeline (e.g. to hash it while copying).
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_source_transform<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: crate::ByteTransform + 'static,
+    {
+        self.pipeline = Some(self.pipeline.take().unwrap_or_default().prepend(factory));
+        self
+    }
+
+    /// Write files to a [`Sink`] instead of the destination operator (e.g. to copy files matching a glob into an archive).
+    ///
+    /// The sink receives the destination paths as resolved by the copier. The destination operator is not touched:
+    /// directories are not created, and deduplication and atomic writes are disabled.
+    pub fn with_sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Report the progress of copies to a [`CopyObserver`].
+    pub fn with_observer(mut self, observer: impl CopyObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Record completed files in a [`CopyJournal`] and skip files it already contains.
+    pub fn with_journal(mut self, journal: CopyJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Report progress to a [`StatusWriter`], which also writes the status once every copy finishes.
+    #[cfg(feature = "serde")]
+    pub fn with_status(mut self, status: StatusWriter) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Write every file to a temporary sibling object and rename it once complete
+    /// (see [`write_atomic_stream`](crate::write_atomic_stream)), so a file is never visible partially written.
+    ///
+    /// Ignored when the destination does not support renaming:
+    /// most object stores only make an object visible once it is completely written anyway.
+    pub fn with_atomic_writes(mut self, atomic_writes: bool) -> Self {
+        self.atomic_writes = atomic_writes;
+        self
+    }
+
+    /// Set the content type of files copied without one (common with filesystem sources),
+    /// guessing it from the extension of the file name or from the first bytes of the file.
+    ///
+    /// Content types of the source are always preserved.
+    pub fn with_content_type_detection(mut self, detect_content_type: bool) -> Self {
+        self.detect_content_type = detect_content_type;
+        self
+    }
+
+    /// Skip files whose destination already has identical content, even if their modification times differ.
+    ///
+    /// Files of the same size are compared by the MD5 reported by both services when available,
+    /// otherwise their SHA-256 digests are computed by reading both of them
+    /// (cheaper than uploading on most object stores, where downloads cost less than uploads).
+    ///
+    /// Ignored when a [`Pipeline`] is set, since the copied content differs from the source.
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Compute a rolling CRC-32C of the bytes written to the destination, then read the destination file back
+    /// once it is closed and fail the copy (removing the file) if its content diverges.
+    ///
+    /// Ignored when a [`Sink`] is set, since written files cannot be read back.
+    pub fn with_integrity_check(mut self, integrity_check: bool) -> Self {
+        self.integrity_check = integrity_check;
+        self
+    }
+
+    /// Fail copies that would exceed a [`Quota`] of the destination.
+    ///
+    /// The usage of the destination prefix is probed before every copy,
+    /// then every file written is counted against the quota.
+    pub fn with_quota(mut self, quota: Quota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Live statistics of the transfers performed by this copier.
+    pub fn stats(&self) -> TransferStats {
+        self.stats.clone()
+    }
+
     pub async fn copy(
         &self,
         source: impl Into<String>,
         destination: impl Into<String>,
-    ) -> Result<(), Error> {
-        self.copy_options(source, destination, CopyOptions::default())
-            .await
+    ) -> Result<(), CopyError> {
+        self.copy_options(source, destination, self.options).await
     }
 
+    /// Copy a file, a directory or the files matching a glob pattern.
+    ///
+    /// With [`CopyOptions::template`], the destination may contain placeholders expanded for every copied file,
+    /// to fan files out into partitioned layouts (e.g. `archive/{date:%Y/%m/%d}/{name}`):
+    /// - `{name}`, `{stem}` and `{ext}`: file name of the source, without the extension, and its extension
+    /// - `{path}`: path of the source relative to the copied directory or glob prefix
+    /// - `{date:<format>}`: modification time of the source (UTC) formatted with strftime
+    /// - `{hash:<n>}`: first `n` hex characters of the SHA-256 of the source path (`{hash}` for all of them)
+    ///
+    /// A destination expanding to a path ending with `/` is treated as a directory.
     pub async fn copy_options(
         &self,
         source: impl Into<String>,
         destination: impl Into<String>,
         options: CopyOptions,
-    ) -> Result<(), Error> {
+    ) -> Result<(), CopyError> {
+        let start = Instant::now();
+
         let source = source.into();
-        let destination = destination.into();
+
+        let mut result = self.preflight(&source, options).phase(CopyPhase::Stat);
+
+        if result.is_ok()
+            && let Some(quota) = &self.quota
+        {
+            result = quota.probe().await.map(|_| ()).phase(CopyPhase::Stat);
+        }
+
+        if result.is_ok() {
+            result = match (
+                resolve_root(&self.source_root, &source),
+                resolve_root(&self.destination_root, &destination.into()),
+            ) {
+                (Ok(source), Ok(destination)) => self.copy_path(source, destination, options).await,
+                (Err(err), _) | (_, Err(err)) => Err(err),
+            };
+        }
+
+        // Persist progress even if the copy failed, so that it can be resumed
+        if let Some(journal) = &self.journal {
+            let flushed = journal.flush().await.phase(CopyPhase::Write);
+
+            result = result.and(flushed);
+        }
+
+        #[cfg(feature = "serde")]
+        if let Some(status) = &self.status {
+            let written = status
+                .finish(result.as_ref().err().map(CopyError::inner))
+                .await
+                .phase(CopyPhase::Write);
+
+            result = result.and(written);
+        }
+
+        telemetry::record_copy(
+            self.schemes(),
+            start.elapsed(),
+            result.as_ref().err().map(CopyError::inner),
+        );
+
+        result
+    }
+
+    // Copy files, then delete destination files missing from the source directory (if requested).
+    pub(crate) async fn sync(
+        &self,
+        source: &str,
+        destination: &str,
+        options: CopyOptions,
+        delete_extraneous: bool,
+    ) -> Result<(), Error> {
+        self.copy_options(source, destination, options).await?;
+
+        if !delete_extraneous {
+            return Ok(());
+        }
+
+        let source_root = normalize_dir(&self.source_path(source)?);
+        let destination_root = normalize_dir(&self.destination_path(destination)?);
+
+        let list_options = ListOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let mut lister =
+            list::lister(&self.destination, &destination_root, Some(list_options)).await?;
+
+        while let Some(entry) = lister.try_next().await? {
+            if !entry.metadata().is_file() {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .trim_start_matches('/')
+                .strip_prefix(destination_root.trim_start_matches('/'))
+                .unwrap_or(entry.path());
+
+            match self
+                .source
+                .stat(&format!("{}{}", source_root, relative))
+                .await
+            {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    self.destination.delete(entry.path()).await?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Resolve a path relative to the source root into a path of the source operator.
+    pub(crate) fn source_path(&self, path: &str) -> Result<String, Error> {
+        resolve_root(&self.source_root, path).map_err(Error::from)
+    }
+
+    // Resolve a path relative to the destination root into a path of the destination operator.
+    pub(crate) fn destination_path(&self, path: &str) -> Result<String, Error> {
+        resolve_root(&self.destination_root, path).map_err(Error::from)
+    }
+
+    /// Check that the source and the destination support the capabilities a copy with the given options requires.
+    ///
+    /// Called by [`Copier::copy_options`] before copying anything, so copies fail early with an
+    /// [`Unsupported`](ErrorKind::Unsupported) error naming the missing capability
+    /// instead of after transferring some of the files.
+    ///
+    /// Globs and directories known by their trailing slash require listing (recursively for globs and recursive copies).
+    /// Other sources only turn out to be directories once stat'ed, so listing is checked again before copying them.
+    pub fn preflight(&self, source: &str, options: CopyOptions) -> Result<(), Error> {
+        let capability = self.source.info().full_capability();
+
+        require_capability(&self.source, "source", "stat", capability.stat)?;
+        require_capability(&self.source, "source", "read", capability.read)?;
+
+        let glob = !options.disable_glob && glob::has_glob_chars(source);
+
+        if glob || source.is_empty() || source.ends_with('/') {
+            self.require_list(glob || options.recursive)?;
+        }
+
+        // Sinks don't write to the destination operator
+        if self.sink.is_none() {
+            let destination = self.destination.info().full_capability();
+
+            require_capability(&self.destination, "destination", "stat", destination.stat)?;
+            require_capability(&self.destination, "destination", "write", destination.write)?;
+
+            if self.dedup || self.integrity_check {
+                require_capability(&self.destination, "destination", "read", destination.read)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Services without native recursive listing simulate it with flat listings (and report it as supported).
+    fn require_list(&self, recursive: bool) -> Result<(), Error> {
+        let capability = self.source.info().full_capability();
+
+        require_capability(&self.source, "source", "list", capability.list)?;
+
+        if recursive {
+            require_capability(
+                &self.source,
+                "source",
+                "recursive list",
+                capability.list_with_recursive,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy a single file entry returned by a listing of the source operator.
+    ///
+    /// Useful when iterating over (and filtering) a listing yourself,
+    /// while still writing files the same way as [`Copier::copy_options`] (with metadata, retries, etc).
+    /// The destination is relative to the destination root, and is treated as a directory when it ends with `/`.
+    pub async fn copy_entry(&self, entry: &Entry, destination: &str) -> Result<(), CopyError> {
+        if !entry.metadata().is_file() {
+            return Err(CopyError::new(
+                CopyPhase::Stat,
+                Error::new(ErrorKind::IsADirectory, "Entry is not a file"),
+            )
+            .with_source_path(entry.path()));
+        }
+
+        let source = Source::new(
+            Utf8UnixPathBuf::from(entry.path()),
+            entry.metadata().clone(),
+        );
+
+        let mut result = match resolve_root(&self.destination_root, destination) {
+            Ok(destination) => {
+                self.copy_file(source, normalize_path(&destination), None)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+
+        if let Some(journal) = &self.journal {
+            let flushed = journal.flush().await.phase(CopyPhase::Write);
+
+            result = result.and(flushed);
+        }
+
+        result
+    }
+
+    /// Copy, skipping the files recorded in a checkpoint of a previous (interrupted) copy.
+    ///
+    /// Returns the result of the copy along with a new checkpoint (including the files copied before an error),
+    /// to be persisted and passed to the next invocation (e.g. by a workflow engine).
+    ///
+    /// The checkpoint replaces the journal of the copier (if any).
+    pub async fn copy_resumable(
+        &self,
+        checkpoint: CopyCheckpoint,
+        source: impl Into<String>,
+        destination: impl Into<String>,
+        options: CopyOptions,
+    ) -> (Result<(), CopyError>, CopyCheckpoint) {
+        let journal = CopyJournal::in_memory(checkpoint);
+
+        let copier = Copier {
+            journal: Some(journal.clone()),
+            ..self.clone()
+        };
+
+        let result = copier.copy_options(source, destination, options).await;
+
+        (result, journal.checkpoint().await)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "copy",
+            skip_all,
+            fields(
+                source = %source,
+                destination = %destination,
+                source_scheme = self.source.info().scheme(),
+                destination_scheme = self.destination.info().scheme(),
+                recursive = options.recursive,
+            ),
+            err(Display)
+        )
+    )]
+    async fn copy_path(
+        &self,
+        source: String,
+        destination: String,
+        options: CopyOptions,
+    ) -> Result<(), CopyError> {
+        let template = if options.template && !options.disable_glob {
+            DestinationTemplate::parse(&destination)
+                .phase(CopyPhase::Stat)
+                .map_err(|err| err.with_destination_path(&destination))?
+        } else {
+            None
+        };
 
         let source = normalize_path(&source);
         let destination = normalize_path(&destination);
 
         // Check if source contains glob patterns
         if !options.disable_glob && glob::has_glob_chars(source.as_str()) {
-            return self.copy_glob(source, destination).await;
+            return self
+                .copy_glob(source, destination, template.as_ref(), options.links)
+                .await;
         }
 
-        let stat = self.source.stat(source.as_str()).await?;
+        let stat = self
+            .retry(|| async {
+                self.source
+                    .stat(source.as_str())
+                    .await
+                    .phase(CopyPhase::Stat)
+            })
+            .await
+            .map_err(|err| err.with_source_path(source.as_str()))?;
         let source = Source::new(source, stat);
 
         match source.meta.mode() {
-            EntryMode::DIR => self.copy_dir(source, destination, options.recursive).await,
-            EntryMode::FILE => self.copy_file(source, destination).await,
-            _ => Err(Error::new(ErrorKind::Unsupported, "Unknown entry mode")),
+            EntryMode::DIR => {
+                let destination = match source.path.file_name() {
+                    Some(name) if options.copy_dir_itself && template.is_none() => {
+                        destination.join(name)
+                    }
+                    _ => destination,
+                };
+
+                self.copy_dir(source, destination, template.as_ref(), options)
+                    .await
+            }
+            EntryMode::FILE => self.copy_file(source, destination, template.as_ref()).await,
+            _ => Err(CopyError::new(
+                CopyPhase::Stat,
+                Error::new(ErrorKind::Unsupported, "Unknown entry mode"),
+            )
+            .with_source_path(source.path.as_str())),
         }
     }
 
@@ -87,23 +867,40 @@ impl Copier {
         &self,
         source: Utf8UnixPathBuf,
         destination: Utf8UnixPathBuf,
-    ) -> Result<(), Error> {
+        template: Option<&DestinationTemplate>,
+        links: LinkPolicy,
+    ) -> Result<(), CopyError> {
         // Get the literal prefix to determine the base path for relative path computation
         let prefix = glob::extract_glob_prefix(source.as_str()).unwrap_or_default();
         let prefix = Utf8UnixPathBuf::from(prefix);
 
-        let lister = crate::list::lister(&self.source, source.as_str(), None).await?;
+        let lister = self
+            .retry(|| async {
+                list::lister(&self.source, source.as_str(), None)
+                    .await
+                    .phase(CopyPhase::List)
+            })
+            .await
+            .map_err(|err| err.with_source_path(source.as_str()))?;
 
-        self.copy_entries(lister, prefix, destination).await
+        self.copy_entries(lister, prefix, destination, template, links)
+            .await
     }
 
     async fn copy_dir(
         &self,
         source: Source,
         destination: Utf8UnixPathBuf,
-        recursive: bool,
-    ) -> Result<(), Error> {
-        let options = if recursive {
+        template: Option<&DestinationTemplate>,
+        options: CopyOptions,
+    ) -> Result<(), CopyError> {
+        let links = options.links;
+
+        self.require_list(options.recursive)
+            .phase(CopyPhase::List)
+            .map_err(|err| err.with_source_path(source.path.as_str()))?;
+
+        let options = if options.recursive {
             Some(ListOptions {
                 recursive: true,
                 ..Default::default()
@@ -112,45 +909,129 @@ impl Copier {
             None
         };
 
-        let lister = list::lister(&self.source, source.path.as_str(), options).await?;
-
-        self.copy_entries(lister, source.path, destination).await
+        let lister = self
+            .retry(|| async {
+                list::lister(&self.source, source.path.as_str(), options.clone())
+                    .await
+                    .phase(CopyPhase::List)
+            })
+            .await
+            .map_err(|err| err.with_source_path(source.path.as_str()))?;
+
+        self.copy_entries(lister, source.path, destination, template, links)
+            .await
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "copy_entries",
+            skip_all,
+            fields(
+                source_prefix = %source_prefix,
+                destination = %destination,
+                files = tracing::field::Empty,
+            ),
+            err(Display)
+        )
+    )]
     async fn copy_entries(
+        &self,
+        lister: futures::stream::BoxStream<'static, Result<opendal::Entry, Error>>,
+        source_prefix: Utf8UnixPathBuf,
+        destination: Utf8UnixPathBuf,
+        template: Option<&DestinationTemplate>,
+        links: LinkPolicy,
+    ) -> Result<(), CopyError> {
+        let mut completed = Vec::new();
+
+        self.copy_entries_into(
+            lister,
+            source_prefix,
+            destination,
+            template,
+            links,
+            &mut completed,
+        )
+        .await
+        .map_err(|err| err.with_completed(completed))
+    }
+
+    async fn copy_entries_into(
         &self,
         mut lister: futures::stream::BoxStream<'static, Result<opendal::Entry, Error>>,
         source_prefix: Utf8UnixPathBuf,
         destination: Utf8UnixPathBuf,
-    ) -> Result<(), Error> {
-        match self.destination.stat(destination.as_str()).await {
-            Ok(stat) if stat.is_file() => {
-                return Err(Error::new(
-                    ErrorKind::NotADirectory,
-                    "Cannot copy directory to a file",
-                ));
-            }
-            Ok(_) => (), // Destination exists and is a directory, continue
-            Err(e) if e.kind() == ErrorKind::NotFound => {
-                // Destination doesn't exist, create it
-                self.destination
-                    .create_dir(&format!("{}/", destination))
-                    .await?;
+        template: Option<&DestinationTemplate>,
+        links: LinkPolicy,
+        completed: &mut Vec<CopiedEntry>,
+    ) -> Result<(), CopyError> {
+        // Templated destinations are expanded for every file, so there is no destination directory
+        if template.is_none() && self.sink.is_none() {
+            match self.destination.stat(destination.as_str()).await {
+                Ok(stat) if stat.is_file() => {
+                    return Err(CopyError::new(
+                        CopyPhase::Stat,
+                        Error::new(ErrorKind::NotADirectory, "Cannot copy directory to a file"),
+                    )
+                    .with_destination_path(destination.as_str()));
+                }
+                Ok(_) => (), // Destination exists and is a directory, continue
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    // Destination doesn't exist, create it
+                    self.destination
+                        .create_dir(&format!("{}/", destination))
+                        .await
+                        .phase(CopyPhase::Write)
+                        .map_err(|err| err.with_destination_path(destination.as_str()))?;
+                }
+                Err(e) => {
+                    return Err(CopyError::new(CopyPhase::Stat, e)
+                        .with_destination_path(destination.as_str()));
+                }
             }
-            Err(e) => return Err(e),
         }
 
-        // Track which directories we've already created to avoid duplicate create_dir calls
-        let mut created_dirs = HashSet::new();
+        // Track which directories exist to avoid creating them again
+        let mut dirs = DirCreator::new(self.destination.clone());
+
+        if template.is_none() {
+            dirs.mark_existing(destination.as_str());
+        }
 
-        // Mark the destination directory as already created
-        created_dirs.insert(destination.clone());
+        #[cfg(feature = "tracing")]
+        let mut files = 0u64;
 
-        while let Some(entry) = lister.try_next().await? {
+        while let Some(entry) = lister
+            .try_next()
+            .await
+            .phase(CopyPhase::List)
+            .map_err(|err| err.with_source_path(source_prefix.as_str()))?
+        {
             if entry.metadata().is_dir() {
                 continue;
             }
 
+            let mut meta = entry.metadata().clone();
+
+            if meta.mode() == EntryMode::Unknown {
+                if !links.follow(entry.path())? {
+                    continue;
+                }
+
+                // Stat follows links
+                meta = self
+                    .source
+                    .stat(entry.path())
+                    .await
+                    .phase(CopyPhase::Stat)
+                    .map_err(|err| err.with_source_path(entry.path()))?;
+
+                if !meta.is_file() {
+                    continue;
+                }
+            }
+
             let entry_path = Utf8UnixPathBuf::from(entry.path());
 
             // Compute relative path from the source prefix
@@ -163,158 +1044,1253 @@ impl Copier {
                     .unwrap_or_else(|_| entry_path.clone())
             };
 
-            let dest_path = destination.join(&relative_path);
+            let source = Source::new(entry_path, meta);
+
+            let dest_path = match template {
+                Some(template) => {
+                    let expanded = self
+                        .expand_template(template, &source, relative_path.as_str())
+                        .await
+                        .map_err(|err| err.with_source_path(source.path.as_str()))?;
+
+                    if expanded.as_str().ends_with('/') {
+                        expanded.join(&relative_path)
+                    } else {
+                        expanded
+                    }
+                }
+                None => destination.join(&relative_path),
+            };
+
+            if let Some(parent) = dest_path.parent().filter(|_| self.sink.is_none()) {
+                dirs.ensure(parent.as_str())
+                    .await
+                    .phase(CopyPhase::Write)
+                    .map_err(|err| err.with_destination_path(parent.as_str()))?;
+            }
+
+            self.retry(|| self.do_copy_file(source.clone(), dest_path.as_str()))
+                .await?;
+
+            completed.push(CopiedEntry {
+                source: source.path.to_string(),
+                destination: dest_path.to_string(),
+            });
+
+            #[cfg(feature = "tracing")]
+            {
+                files += 1;
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("files", files);
+
+        Ok(())
+    }
+
+    async fn copy_file(
+        &self,
+        source: Source,
+        destination: Utf8UnixPathBuf,
+        template: Option<&DestinationTemplate>,
+    ) -> Result<(), CopyError> {
+        let destination = match template {
+            Some(template) => {
+                let name = source.path.file_name().unwrap_or_default().to_string();
+
+                self.expand_template(template, &source, &name)
+                    .await
+                    .map_err(|err| err.with_source_path(source.path.as_str()))?
+            }
+            None => destination,
+        };
+
+        let destination = self
+            .file_destination(&source, &destination)
+            .await
+            .map_err(|err| err.with_paths(source.path.as_str(), destination.as_str()))?;
+
+        self.retry(|| self.do_copy_file(source.clone(), destination.as_str()))
+            .await
+    }
+
+    // Resolve the path of the destination file, creating its directory if necessary.
+    async fn file_destination(
+        &self,
+        source: &Source,
+        destination: &Utf8UnixPathBuf,
+    ) -> Result<Utf8UnixPathBuf, CopyError> {
+        let prefer_content_disposition = matches!(self.source.info().scheme(), "http" | "https");
+
+        let name = || {
+            source
+                .name(prefer_content_disposition)
+                .phase(CopyPhase::Stat)
+        };
+
+        // Sinks have no directories
+        if self.sink.is_some() {
+            if destination.as_str().is_empty() || destination.as_str().ends_with('/') {
+                return Ok(destination.join(name()?));
+            }
+
+            return Ok(destination.clone());
+        }
+
+        match self.destination.stat(destination.as_str()).await {
+            Ok(stat) if stat.is_dir() => {
+                // Destination exists and is a directory
+                Ok(destination.join(name()?))
+            }
+            Ok(_) => Ok(destination.clone()), // Destination exists and is a file (overwrite)
+            Err(e) if e.kind() == ErrorKind::NotFound && destination.as_str().ends_with('/') => {
+                // Destination is a directory that does not exist yet
+                self.destination
+                    .create_dir(destination.as_str())
+                    .await
+                    .phase(CopyPhase::Write)?;
+
+                Ok(destination.join(name()?))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                // Destination does not exist, ensure parent directory exists
+                if let Some(parent) = destination.parent() {
+                    self.destination
+                        .create_dir(&format!("{}/", parent))
+                        .await
+                        .phase(CopyPhase::Write)?;
+                }
+
+                Ok(destination.clone())
+            }
+            Err(e) => Err(CopyError::new(CopyPhase::Stat, e)),
+        }
+    }
+
+    // Expand a destination template for a source file (stat'ing it when the listing lacks the modification time).
+    async fn expand_template(
+        &self,
+        template: &DestinationTemplate,
+        source: &Source,
+        relative: &str,
+    ) -> Result<Utf8UnixPathBuf, CopyError> {
+        let stat;
+
+        let meta = if template.needs_last_modified() && source.meta.last_modified().is_none() {
+            stat = self
+                .source
+                .stat(source.path.as_str())
+                .await
+                .phase(CopyPhase::Stat)?;
+
+            &stat
+        } else {
+            &source.meta
+        };
+
+        let destination = template
+            .expand(source.path.as_str(), relative, meta)
+            .phase(CopyPhase::Stat)?;
+
+        Ok(normalize_path(&destination))
+    }
+
+    async fn retry<T, F, Fut>(&self, mut operation: F) -> Result<T, CopyError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, CopyError>>,
+    {
+        match &self.retry_policy {
+            Some(retry_policy) => retry_policy.retry_by(operation, CopyError::inner).await,
+            None => operation().await,
+        }
+    }
+
+    fn schemes(&self) -> telemetry::Schemes {
+        (
+            self.source.info().scheme(),
+            self.destination.info().scheme(),
+        )
+    }
+
+    fn emit(&self, event: CopyEvent) {
+        self.stats.on_event(&event);
+
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event);
+        }
+
+        #[cfg(feature = "serde")]
+        if let Some(status) = &self.status {
+            status.on_event(&event);
+        }
+    }
+
+    // Copy a file from one storage to another, reporting progress to the observer.
+    // This function expects that the input parameters have been validated
+    // (that is, each path points to a file).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "copy_file",
+            skip_all,
+            fields(
+                source = %source.path,
+                destination = destination,
+                size = source.meta.content_length(),
+                bytes = tracing::field::Empty,
+                skipped = tracing::field::Empty,
+            ),
+            err(Display)
+        )
+    )]
+    async fn do_copy_file(&self, source: Source, destination: &str) -> Result<(), CopyError> {
+        if let Some(journal) = &self.journal
+            && journal
+                .is_completed(source.path.as_str(), destination)
+                .await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("skipped", true);
+
+            return Ok(());
+        }
+
+        let duplicate = self.dedup
+            && self.pipeline.is_none()
+            && self.sink.is_none()
+            && self
+                .is_duplicate(&source, destination)
+                .await
+                .map_err(|err| err.with_paths(source.path.as_str(), destination))?;
+
+        if duplicate {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("skipped", true);
+
+            self.emit(CopyEvent::FileSkipped {
+                source: source.path.to_string(),
+                destination: destination.to_string(),
+            });
+        } else {
+            self.emit(CopyEvent::FileStarted {
+                source: source.path.to_string(),
+                destination: destination.to_string(),
+                size: source.meta.content_length(),
+            });
+
+            let result = self
+                .transfer_file(&source, destination)
+                .await
+                .map_err(|err| err.with_paths(source.path.as_str(), destination));
+
+            match &result {
+                Ok(bytes) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("bytes", bytes);
+
+                    self.emit(CopyEvent::FileCompleted {
+                        source: source.path.to_string(),
+                        destination: destination.to_string(),
+                        bytes: *bytes,
+                    })
+                }
+                Err(err) => {
+                    telemetry::record_file_failed(self.schemes(), err.inner());
+
+                    self.emit(CopyEvent::FileFailed {
+                        source: source.path.to_string(),
+                        destination: destination.to_string(),
+                        error: err.inner().to_string(),
+                    })
+                }
+            }
+
+            result?;
+        }
+
+        if let Some(journal) = &self.journal {
+            journal
+                .record(source.path.as_str(), destination)
+                .await
+                .phase(CopyPhase::Write)
+                .map_err(|err| err.with_paths(source.path.as_str(), destination))?;
+        }
+
+        Ok(())
+    }
+
+    // Whether the destination file already has the same content as the source.
+    async fn is_duplicate(&self, source: &Source, destination: &str) -> Result<bool, CopyError> {
+        let target = match self.destination.stat(destination).await {
+            Ok(meta) if meta.is_file() => meta,
+            Ok(_) => return Ok(false),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(CopyError::new(CopyPhase::Stat, err)),
+        };
+
+        // Listing does not necessarily return every metadata field
+        let meta = self
+            .source
+            .stat(source.path.as_str())
+            .await
+            .phase(CopyPhase::Stat)?;
+
+        if meta.content_length() != target.content_length() {
+            return Ok(false);
+        }
+
+        if let (Some(source_md5), Some(target_md5)) = (meta.content_md5(), target.content_md5()) {
+            return Ok(source_md5 == target_md5);
+        }
+
+        let (source_digest, target_digest) = futures::try_join!(
+            checksum(&self.source, source.path.as_str(), HashAlgorithm::Sha256),
+            checksum(&self.destination, destination, HashAlgorithm::Sha256),
+        )
+        .phase(CopyPhase::Read)?;
+
+        Ok(source_digest == target_digest)
+    }
+
+    // Returns the number of bytes read from the source.
+    async fn transfer_file(&self, source: &Source, destination: &str) -> Result<u64, CopyError> {
+        let _permit = match &self.budget {
+            Some(budget) => Some(budget.acquire_transfer().await),
+            None => None,
+        };
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            // One request for reading and one for writing
+            rate_limiter.acquire_request().await;
+            rate_limiter.acquire_request().await;
+        }
+
+        let mut reservation = match &self.quota {
+            Some(quota) => {
+                // Fail early when the size of the file is known (and not changed by a transform)
+                if self.pipeline.is_none() {
+                    quota
+                        .check_write(1, source.meta.content_length())
+                        .phase(CopyPhase::Write)?;
+                }
+
+                let mut reservation = quota.reservation();
+                reservation.charge(1, 0).phase(CopyPhase::Write)?;
+
+                Some(reservation)
+            }
+            None => None,
+        };
+
+        let start = tokio::time::Instant::now();
+
+        // Write to a temporary sibling and rename it once complete
+        let atomic = self.atomic_writes
+            && self.sink.is_none()
+            && self.destination.info().full_capability().rename;
+
+        let bytes = if atomic {
+            let temp_path = write::temp_path(&Utf8UnixPathBuf::from(destination));
+
+            let result = match self
+                .write_file(source, &temp_path, start, reservation.as_mut())
+                .await
+            {
+                Ok(bytes) => self
+                    .destination
+                    .rename(&temp_path, destination)
+                    .await
+                    .map(|_| bytes)
+                    .phase(CopyPhase::Close),
+                Err(err) => Err(err),
+            };
+
+            if result.is_err() {
+                let _ = self.destination.delete(&temp_path).await;
+            }
+
+            result?
+        } else {
+            self.write_file(source, destination, start, reservation.as_mut())
+                .await?
+        };
+
+        if let Some(reservation) = reservation {
+            reservation.commit();
+        }
+
+        telemetry::record_file_copied(
+            self.schemes(),
+            source.path.as_str(),
+            destination,
+            bytes,
+            start.elapsed(),
+        );
+
+        Ok(bytes)
+    }
+
+    // Returns the number of bytes read from the source.
+    async fn write_file(
+        &self,
+        source: &Source,
+        destination: &str,
+        start: tokio::time::Instant,
+        mut reservation: Option<&mut Reservation>,
+    ) -> Result<u64, CopyError> {
+        let mut first_chunk = true;
+
+        let reader = self
+            .source
+            .reader(source.path.as_str())
+            .await
+            .phase(CopyPhase::Read)?;
+        let mut stream = reader.into_bytes_stream(..).await.phase(CopyPhase::Read)?;
+
+        let mut meta = Cow::Borrowed(&source.meta);
+
+        // The first chunk is read before opening the writer when sniffing the content type
+        let mut first = None;
+
+        if self.detect_content_type && source.meta.content_type().is_none() {
+            // Listing does not necessarily return the content type
+            let stat = self
+                .source
+                .stat(source.path.as_str())
+                .await
+                .phase(CopyPhase::Stat)?;
+
+            let mut content_type = stat
+                .content_type()
+                .map(String::from)
+                .or_else(|| content_type::from_extension(source.path.as_str()).map(String::from));
+
+            if content_type.is_none() {
+                first = stream
+                    .try_next()
+                    .map_err(IoErrorExt::into_opendal_error)
+                    .await
+                    .phase(CopyPhase::Read)?;
+
+                content_type = first
+                    .as_deref()
+                    .and_then(content_type::from_magic)
+                    .map(String::from);
+            }
+
+            if let Some(content_type) = content_type {
+                meta = Cow::Owned(source.meta.clone().with_content_type(content_type));
+            }
+        }
+
+        let mut writer: Box<dyn SinkWriter> = match &self.sink {
+            Some(sink) => sink.writer(destination, &meta).await,
+            None => open_writer(&self.destination, destination, &meta)
+                .await
+                .map(|writer| Box::new(writer) as Box<dyn SinkWriter>),
+        }
+        .phase(CopyPhase::Write)?;
+
+        let mut transform = self.pipeline.as_ref().map(Pipeline::build);
+
+        let mut bytes = 0;
+
+        let integrity_check = self.integrity_check && self.sink.is_none();
+        let mut crc = Crc32c::default();
+
+        let mut stream = futures::stream::iter(first.map(Ok)).chain(stream);
+        while let Some(chunk) = stream
+            .try_next()
+            .map_err(IoErrorExt::into_opendal_error)
+            .await
+            .phase(CopyPhase::Read)?
+        {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire_bytes(chunk.len() as u64).await;
+            }
+
+            if first_chunk {
+                self.stats.record_latency(Backend::Source, start.elapsed());
+                first_chunk = false;
+            }
+
+            bytes += chunk.len() as u64;
+
+            self.emit(CopyEvent::FileProgress {
+                source: source.path.to_string(),
+                bytes: chunk.len() as u64,
+            });
+
+            let chunk = match &mut transform {
+                Some(transform) => transform.transform(chunk).phase(CopyPhase::Write)?,
+                None => chunk,
+            };
+
+            if let Some(reservation) = &mut reservation {
+                reservation
+                    .charge(0, chunk.len() as u64)
+                    .phase(CopyPhase::Write)?;
+            }
+
+            if integrity_check {
+                crc.update(&chunk);
+            }
+
+            if !chunk.is_empty() {
+                writer.write(chunk.into()).await.phase(CopyPhase::Write)?;
+            }
+        }
+
+        if let Some(transform) = &mut transform {
+            let chunk = transform.finish().phase(CopyPhase::Write)?;
+
+            if let Some(reservation) = &mut reservation {
+                reservation
+                    .charge(0, chunk.len() as u64)
+                    .phase(CopyPhase::Write)?;
+            }
+
+            if integrity_check {
+                crc.update(&chunk);
+            }
+
+            if !chunk.is_empty() {
+                writer.write(chunk.into()).await.phase(CopyPhase::Write)?;
+            }
+        }
+
+        let close_start = tokio::time::Instant::now();
+
+        writer.close().await.phase(CopyPhase::Close)?;
+
+        self.stats
+            .record_latency(Backend::Destination, close_start.elapsed());
+
+        if integrity_check && let Err(err) = self.verify_written(destination, crc).await {
+            let _ = self.destination.delete(destination).await;
+
+            return Err(CopyError::new(CopyPhase::Close, err));
+        }
+
+        Ok(bytes)
+    }
+
+    // Read a written file back and compare it with the checksum of the bytes written.
+    async fn verify_written(&self, path: &str, expected: Crc32c) -> Result<(), Error> {
+        let mut stream = self.destination.reader(path).await?.into_stream(..).await?;
+        let mut actual = Crc32c::default();
+
+        while let Some(buffer) = stream.try_next().await? {
+            actual.update(&buffer.to_bytes());
+        }
+
+        if actual == expected {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            ErrorKind::Unexpected,
+            "Written file does not match bytes written",
+        )
+        .with_context("path", path)
+        .with_context("expected_bytes", expected.len())
+        .with_context("expected_crc32c", format!("{:08x}", expected.value()))
+        .with_context("actual_bytes", actual.len())
+        .with_context("actual_crc32c", format!("{:08x}", actual.value())))
+    }
+}
+
+/// Builder for a [`Copier`] (see [`Copier::builder`]).
+///
+/// Useful to construct one configured copier per tenant instead of passing the same options at every call site.
+/// Other settings are available on the built copier (e.g. [`Copier::with_retry_policy`]).
+pub struct CopierBuilder {
+    source: Operator,
+    destination: Operator,
+    source_root: String,
+    destination_root: String,
+    options: CopyOptions,
+    observers: Vec<Arc<dyn CopyObserver>>,
+}
+
+impl CopierBuilder {
+    /// Resolve source paths relative to a root.
+    ///
+    /// Paths escaping the root (e.g. with `..`) are rejected.
+    pub fn source_root(mut self, root: impl Into<String>) -> Self {
+        self.source_root = root.into();
+        self
+    }
+
+    /// Resolve destination paths relative to a root.
+    ///
+    /// Paths escaping the root (e.g. with `..`) are rejected.
+    pub fn destination_root(mut self, root: impl Into<String>) -> Self {
+        self.destination_root = root.into();
+        self
+    }
+
+    /// Apply the layers of a [`LayerConfig`] to both operators.
+    pub fn layers(mut self, config: &LayerConfig) -> Self {
+        self.source = config.apply(self.source);
+        self.destination = config.apply(self.destination);
+        self
+    }
+
+    /// Apply a layer to both operators.
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Accessor> + Clone,
+    {
+        self.source = self.source.layer(layer.clone());
+        self.destination = self.destination.layer(layer);
+        self
+    }
+
+    /// Options used by [`Copier::copy`].
+    pub fn options(mut self, options: CopyOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Report the progress of copies to a [`CopyObserver`] (in addition to the observers registered before).
+    pub fn observer(mut self, observer: impl CopyObserver + 'static) -> Self {
+        self.observers.push(Arc::new(observer));
+        self
+    }
+
+    pub fn build(self) -> Copier {
+        let mut copier = Copier::new(self.source, self.destination);
+
+        copier.source_root = self.source_root;
+        copier.destination_root = self.destination_root;
+        copier.options = self.options;
+
+        copier.observer = match self.observers.len() {
+            0 => None,
+            1 => self.observers.into_iter().next(),
+            _ => Some(Arc::new(self.observers)),
+        };
+
+        copier
+    }
+}
+
+impl CopyObserver for Vec<Arc<dyn CopyObserver>> {
+    fn on_event(&self, event: &CopyEvent) {
+        for observer in self {
+            observer.on_event(event);
+        }
+    }
+}
+
+// Resolve a path relative to a root, making sure it does not escape the root.
+pub(crate) fn resolve_root(root: &str, path: &str) -> Result<String, CopyError> {
+    let root = normalize_path(root);
+    let root = root.as_str().trim_end_matches('/');
+
+    if root.is_empty() {
+        return Ok(path.to_string());
+    }
+
+    let resolved = normalize_path(&format!("{}/{}", root, path.trim_start_matches('/')));
+
+    let inside = resolved
+        .as_str()
+        .strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+    if !inside {
+        return Err(CopyError::new(
+            CopyPhase::Stat,
+            Error::new(ErrorKind::PermissionDenied, "Path escapes the root")
+                .with_context("root", root)
+                .with_context("path", path),
+        ));
+    }
+
+    Ok(resolved.to_string())
+}
+
+#[derive(Debug, Clone)]
+struct Source {
+    path: Utf8UnixPathBuf,
+    meta: Metadata,
+}
+
+impl Source {
+    fn new(path: Utf8UnixPathBuf, meta: Metadata) -> Self {
+        Self { path, meta }
+    }
+
+    // The file name of the source, taken from its path or its content disposition.
+    //
+    // HTTP servers often serve files from paths unrelated to their names (e.g. `/download/123`),
+    // so the content disposition can be preferred over the path.
+    fn name(&self, prefer_content_disposition: bool) -> Result<String, Error> {
+        let from_path = || self.path.file_name().map(String::from);
+        let from_content_disposition = || {
+            self.meta
+                .content_disposition()
+                .and_then(|cd| parse_content_disposition(cd).filename_full())
+        };
+
+        if prefer_content_disposition {
+            from_content_disposition().or_else(from_path)
+        } else {
+            from_path().or_else(from_content_disposition)
+        }
+        .ok_or_else(|| Error::new(ErrorKind::Unexpected, "Source has no filename"))
+    }
+}
+
+// Fail with an error naming a capability required by a copy if the operator does not support it.
+fn require_capability(
+    operator: &Operator,
+    side: &str,
+    capability: &str,
+    supported: bool,
+) -> Result<(), Error> {
+    if supported {
+        return Ok(());
+    }
+
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!("The {} does not support {}", side, capability),
+    )
+    .with_context("capability", capability)
+    .with_context("scheme", operator.info().scheme()))
+}
+
+/// Opens a writer for `path`, carrying over the metadata of the source object.
+pub(crate) async fn open_writer(
+    operator: &Operator,
+    path: &str,
+    meta: &Metadata,
+) -> Result<Writer, Error> {
+    let mut writer_builder = operator.writer_with(path);
+
+    if let Some(content_type) = meta.content_type() {
+        writer_builder = writer_builder.content_type(content_type);
+    }
+    // TODO: add other metadata?
+
+    writer_builder.await
+}
+
+/// Normalizes a path by:
+/// - Removing leading slashes (root)
+/// - Preserving trailing slashes for directories
+/// - Resolving `.` and `..` components
+pub(crate) fn normalize_path(path: &str) -> Utf8UnixPathBuf {
+    let is_dir = path.ends_with('/');
+
+    let mut path = Utf8UnixPathBuf::from(path)
+        .normalize()
+        .to_string()
+        .trim_start_matches("/")
+        .to_string();
+
+    if is_dir {
+        path.push('/');
+    }
+
+    Utf8UnixPathBuf::from(path)
+}
+
+/// Normalizes a directory path (see [`normalize_path`]):
+/// empty for the root, with a trailing slash otherwise.
+pub(crate) fn normalize_dir(path: &str) -> String {
+    match normalize_path(path).as_str().trim_end_matches('/') {
+        "" => String::new(),
+        path => format!("{}/", path),
+    }
+}
+
+pub(crate) trait IoErrorExt {
+    fn into_opendal_error(self) -> Error;
+}
+
+impl IoErrorExt for io::Error {
+    fn into_opendal_error(self) -> Error {
+        let kind = match self.kind() {
+            io::ErrorKind::NotFound => ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+            io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+            io::ErrorKind::IsADirectory => ErrorKind::IsADirectory,
+            io::ErrorKind::NotADirectory => ErrorKind::NotADirectory,
+            _ => ErrorKind::Unexpected,
+        };
+
+        Error::new(kind, self.to_string()).set_source(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::quota::{QuotaExceeded, QuotaOptions};
+
+    #[tokio::test]
+    async fn test_copy_file() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("path/to/file.txt", "foo").await?;
+
+        let copier = Copier::new(source, destination.clone());
+        copier.copy("path/to/file.txt", "").await?;
+
+        let buffer = destination.read("file.txt").await.unwrap();
+        assert_eq!(buffer.to_vec(), "foo".as_bytes());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_destination_template() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("incoming/a.csv", "a").await?;
+        source.write("incoming/nested/b.csv", "b").await?;
+        source.write("incoming/c.txt", "c").await?;
+
+        let options = CopyOptions {
+            template: true,
+            ..Default::default()
+        };
+
+        let copier = Copier::builder(source.clone(), destination.clone())
+            .options(options)
+            .build();
+
+        copier
+            .copy_options("incoming/**/*.csv", "by-ext/{ext}/{stem}.{ext}", options)
+            .await?;
+
+        assert_eq!(destination.read("by-ext/csv/a.csv").await?.to_vec(), b"a");
+        assert_eq!(destination.read("by-ext/csv/b.csv").await?.to_vec(), b"b");
+        assert!(!destination.exists("by-ext/txt/c.txt").await?);
+
+        let options = CopyOptions {
+            recursive: true,
+            template: true,
+            ..Default::default()
+        };
+
+        copier
+            .copy_options("incoming/", "by-hash/{hash:2}/{path}", options)
+            .await?;
+
+        let hash = &hex::encode(Sha256::digest("incoming/nested/b.csv"))[..2];
+        assert!(
+            destination
+                .exists(&format!("by-hash/{}/nested/b.csv", hash))
+                .await?
+        );
+
+        copier.copy("incoming/c.txt", "single/{name}/").await?;
+        assert!(destination.exists("single/c.txt/c.txt").await?);
+
+        // The memory service has no modification times
+        let err = copier
+            .copy("incoming/c.txt", "{date:%Y}/{name}")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        let err = copier
+            .copy("incoming/c.txt", "{unknown}")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        // Destinations are literal without opting in (or with globs disabled)
+        Copier::new(source.clone(), destination.clone())
+            .copy("incoming/c.txt", "data/{raw}/c.txt")
+            .await?;
+        assert!(destination.exists("data/{raw}/c.txt").await?);
+
+        let options = CopyOptions {
+            template: true,
+            disable_glob: true,
+            ..Default::default()
+        };
+
+        copier
+            .copy_options("incoming/c.txt", "literal/{name}", options)
+            .await?;
+        assert!(destination.exists("literal/{name}").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_content_type_detection() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("site/index.html", "<html></html>").await?;
+        source
+            .write("site/logo", b"\x89PNG\r\n\x1a\n....".to_vec())
+            .await?;
+        source
+            .write_with("site/data", "{}")
+            .content_type("application/json")
+            .await?;
+        source.write("site/unknown", "foo").await?;
+
+        let options = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        Copier::new(source, destination.clone())
+            .with_content_type_detection(true)
+            .copy_options("site/", "out/", options)
+            .await?;
+
+        let content_type = async |path: &str| -> Result<Option<String>, Error> {
+            Ok(destination
+                .stat(path)
+                .await?
+                .content_type()
+                .map(String::from))
+        };
+
+        assert_eq!(
+            content_type("out/index.html").await?.as_deref(),
+            Some("text/html")
+        );
+        assert_eq!(
+            content_type("out/logo").await?.as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(
+            content_type("out/data").await?.as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(content_type("out/unknown").await?, None);
+        assert_eq!(destination.read("out/logo").await?.len(), 12);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_dedup() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "aaa").await?;
+        source.write("data/b.txt", "bbb").await?;
+        source.write("data/c.txt", "ccc").await?;
+        destination.write("out/a.txt", "aaa").await?;
+        destination.write("out/b.txt", "xxx").await?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let options = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        Copier::new(source, destination.clone())
+            .with_dedup(true)
+            .with_observer(tx)
+            .copy_options("data/", "out/", options)
+            .await?;
+
+        let mut skipped = Vec::new();
+        let mut completed = Vec::new();
+
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                CopyEvent::FileSkipped { source, .. } => skipped.push(source),
+                CopyEvent::FileCompleted { source, .. } => completed.push(source),
+                _ => (),
+            }
+        }
+
+        assert_eq!(skipped, vec!["data/a.txt"]);
+        assert_eq!(completed, vec!["data/b.txt", "data/c.txt"]);
+        assert_eq!(destination.read("out/b.txt").await?.to_vec(), b"bbb");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_preflight() -> Result<(), Error> {
+        use opendal::raw::Access;
+
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("a.txt", "a").await?;
+
+        destination
+            .inner()
+            .info()
+            .update_full_capability(|mut capability| {
+                capability.write = false;
+                capability
+            });
+
+        let copier = Copier::new(source, destination.clone());
+
+        let err = copier.copy("a.txt", "b.txt").await.unwrap_err();
+        assert_eq!(err.inner().kind(), ErrorKind::Unsupported);
+        assert_eq!(
+            err.inner().message(),
+            "The destination does not support write"
+        );
+
+        let sink = (Operator::new(Memory::default())?.finish(), String::new());
+        assert!(
+            copier
+                .with_sink(sink)
+                .preflight("a.txt", CopyOptions::default())
+                .is_ok()
+        );
+
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "a").await?;
+
+        source
+            .inner()
+            .info()
+            .update_full_capability(|mut capability| {
+                capability.list = false;
+                capability
+            });
+
+        let copier = Copier::new(source, destination.clone());
+
+        for path in ["dir/*.txt", "dir/"] {
+            let err = copier.copy(path, "out/").await.unwrap_err();
+            assert_eq!(err.inner().kind(), ErrorKind::Unsupported);
+            assert_eq!(err.inner().message(), "The source does not support list");
+        }
+
+        copier.copy("dir/a.txt", "out/a.txt").await?;
+        assert_eq!(destination.read("out/a.txt").await?.to_vec(), b"a");
+
+        // Globs are listed recursively
+        let source = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "a").await?;
+
+        source
+            .inner()
+            .info()
+            .update_full_capability(|mut capability| {
+                capability.list_with_recursive = false;
+                capability
+            });
+
+        let err = Copier::new(source, destination)
+            .copy("dir/**/*.txt", "out/")
+            .await
+            .unwrap_err();
+        assert_eq!(err.inner().kind(), ErrorKind::Unsupported);
+        assert_eq!(
+            err.inner().message(),
+            "The source does not support recursive list"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_integrity_check() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("a.txt", vec![7u8; 100_000]).await?;
 
-            if let Some(parent) = dest_path.parent()
-                && !created_dirs.contains(&(parent.to_owned()))
-            {
-                self.destination.create_dir(&format!("{}/", parent)).await?;
+        Copier::new(source.clone(), destination.clone())
+            .with_integrity_check(true)
+            .copy("a.txt", "b.txt")
+            .await?;
 
-                created_dirs.insert(parent.to_owned());
-            }
+        assert_eq!(
+            destination.read("b.txt").await?.to_vec(),
+            vec![7u8; 100_000]
+        );
 
-            let source = Source::new(entry_path, entry.metadata().clone());
+        let corrupted = Operator::new(Memory::default())?
+            .layer(CorruptLayer)
+            .finish();
 
-            self.do_copy_file(source, dest_path.as_str()).await?;
-        }
+        let err = Copier::new(source, corrupted.clone())
+            .with_integrity_check(true)
+            .copy("a.txt", "b.txt")
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.phase(), CopyPhase::Close);
+        assert_eq!(
+            err.inner().message(),
+            "Written file does not match bytes written"
+        );
+        assert!(!corrupted.exists("b.txt").await?);
 
         Ok(())
     }
 
-    async fn copy_file(&self, source: Source, destination: Utf8UnixPathBuf) -> Result<(), Error> {
-        let destination = match self.destination.stat(destination.as_str()).await {
-            Ok(stat) if stat.is_dir() => destination.join(source.name()?), // Destination exists and is a directory
-            Ok(_) => destination, // Destination exists and is a file (overwrite)
-            Err(e) if e.kind() == ErrorKind::NotFound => {
-                // Destination does not exist, ensure parent directory exists
-                if let Some(parent) = destination.parent() {
-                    self.destination.create_dir(&format!("{}/", parent)).await?;
-                }
+    // Flips the first byte of every chunk written.
+    #[derive(Debug, Clone, Copy)]
+    struct CorruptLayer;
 
-                destination
-            }
-            Err(e) => {
-                return Err(e);
-            }
-        };
+    #[derive(Debug)]
+    struct CorruptAccessor<A> {
+        inner: A,
+    }
 
-        self.do_copy_file(source, destination.as_str()).await
+    struct CorruptWriter<W> {
+        inner: W,
     }
 
-    // Copy a file from one storage to another.
-    // This function expects that the input parameters have been validated
-    // (that is, each path points to a file).
-    async fn do_copy_file(&self, source: Source, destination: &str) -> Result<(), Error> {
-        let reader = self.source.reader(source.path.as_str()).await?;
-        let mut writer_builder = self.destination.writer_with(destination);
+    impl<A: opendal::raw::Access> Layer<A> for CorruptLayer {
+        type LayeredAccess = CorruptAccessor<A>;
 
-        if let Some(content_type) = source.meta.content_type() {
-            writer_builder = writer_builder.content_type(content_type);
+        fn layer(&self, inner: A) -> Self::LayeredAccess {
+            CorruptAccessor { inner }
         }
-        // TODO: add other metadata?
+    }
 
-        let mut writer = writer_builder.await?;
+    impl<A: opendal::raw::Access> opendal::raw::LayeredAccess for CorruptAccessor<A> {
+        type Inner = A;
+        type Reader = A::Reader;
+        type Writer = CorruptWriter<A::Writer>;
+        type Lister = A::Lister;
+        type Deleter = A::Deleter;
+        type Copier = A::Copier;
 
-        let mut stream = reader.into_bytes_stream(..).await?;
-        while let Some(chunk) = stream
-            .try_next()
-            .map_err(IoErrorExt::into_opendal_error)
-            .await?
-        {
-            writer.write(chunk).await?;
+        fn inner(&self) -> &Self::Inner {
+            &self.inner
         }
 
-        writer.close().await?;
+        async fn read(
+            &self,
+            path: &str,
+            args: opendal::raw::OpRead,
+        ) -> opendal::Result<(opendal::raw::RpRead, Self::Reader)> {
+            self.inner.read(path, args).await
+        }
 
-        Ok(())
-    }
-}
+        async fn write(
+            &self,
+            path: &str,
+            args: opendal::raw::OpWrite,
+        ) -> opendal::Result<(opendal::raw::RpWrite, Self::Writer)> {
+            let (rp, inner) = self.inner.write(path, args).await?;
 
-#[derive(Debug, Clone)]
-struct Source {
-    path: Utf8UnixPathBuf,
-    meta: Metadata,
-}
+            Ok((rp, CorruptWriter { inner }))
+        }
 
-impl Source {
-    fn new(path: Utf8UnixPathBuf, meta: Metadata) -> Self {
-        Self { path, meta }
-    }
+        async fn delete(&self) -> opendal::Result<(opendal::raw::RpDelete, Self::Deleter)> {
+            self.inner.delete().await
+        }
 
-    fn name(&self) -> Result<String, Error> {
-        self.path
-            .file_name()
-            .map(String::from)
-            .or_else(|| {
-                self.meta
-                    .content_disposition()
-                    .and_then(|cd| parse_content_disposition(cd).filename_full())
-            })
-            .ok_or_else(|| Error::new(ErrorKind::Unexpected, "Source has no filename"))
+        async fn list(
+            &self,
+            path: &str,
+            args: opendal::raw::OpList,
+        ) -> opendal::Result<(opendal::raw::RpList, Self::Lister)> {
+            self.inner.list(path, args).await
+        }
     }
-}
 
-/// Normalizes a path by:
-/// - Removing leading slashes (root)
-/// - Preserving trailing slashes for directories
-/// - Resolving `.` and `..` components
-fn normalize_path(path: &str) -> Utf8UnixPathBuf {
-    let is_dir = path.ends_with('/');
+    impl<W: opendal::raw::oio::Write> opendal::raw::oio::Write for CorruptWriter<W> {
+        async fn write(&mut self, buffer: opendal::Buffer) -> opendal::Result<()> {
+            let mut bytes = buffer.to_vec();
 
-    let mut path = Utf8UnixPathBuf::from(path)
-        .normalize()
-        .to_string()
-        .trim_start_matches("/")
-        .to_string();
+            if let Some(byte) = bytes.first_mut() {
+                *byte = !*byte;
+            }
 
-    if is_dir {
-        path.push('/');
+            self.inner.write(opendal::Buffer::from(bytes)).await
+        }
+
+        async fn close(&mut self) -> opendal::Result<Metadata> {
+            self.inner.close().await
+        }
+
+        async fn abort(&mut self) -> opendal::Result<()> {
+            self.inner.abort().await
+        }
     }
 
-    Utf8UnixPathBuf::from(path)
-}
+    #[tokio::test]
+    async fn test_copy_entry() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
 
-trait IoErrorExt {
-    fn into_opendal_error(self) -> Error;
-}
+        source.write("data/keep.txt", "keep").await?;
+        source.write("data/skip.log", "skip").await?;
 
-impl IoErrorExt for io::Error {
-    fn into_opendal_error(self) -> Error {
-        let kind = match self.kind() {
-            io::ErrorKind::NotFound => ErrorKind::NotFound,
-            io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
-            io::ErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
-            io::ErrorKind::IsADirectory => ErrorKind::IsADirectory,
-            io::ErrorKind::NotADirectory => ErrorKind::NotADirectory,
-            _ => ErrorKind::Unexpected,
-        };
+        let copier = Copier::new(source.clone(), destination.clone());
 
-        Error::new(kind, self.to_string()).set_source(self)
-    }
-}
+        for entry in source.list("data/").await? {
+            if entry.path().ends_with(".txt") {
+                copier.copy_entry(&entry, "out/").await?;
+            }
+        }
 
-#[cfg(test)]
-mod tests {
-    use opendal::services::Memory;
+        assert_eq!(destination.read("out/keep.txt").await?.to_vec(), b"keep");
+        assert!(!destination.exists("out/skip.log").await?);
 
-    use super::*;
+        let dir = source.list("").await?.remove(0);
+        let err = copier.copy_entry(&dir, "out/").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IsADirectory);
+
+        Ok(())
+    }
 
     #[tokio::test]
-    async fn test_copy_file() -> Result<(), Error> {
+    async fn test_copy_dir_itself() -> Result<(), Error> {
         let source = Operator::new(Memory::default())?.finish();
         let destination = Operator::new(Memory::default())?.finish();
 
-        source.write("path/to/file.txt", "foo").await?;
+        source.write("data/a.txt", "a").await?;
+        source.write("data/sub/b.txt", "b").await?;
 
-        let copier = Copier::new(source, destination.clone());
-        copier.copy("path/to/file.txt", "").await?;
+        let options = CopyOptions {
+            recursive: true,
+            copy_dir_itself: true,
+            ..Default::default()
+        };
 
-        let buffer = destination.read("file.txt").await.unwrap();
-        assert_eq!(buffer.to_vec(), "foo".as_bytes());
+        Copier::new(source, destination.clone())
+            .copy_options("data/", "backup/", options)
+            .await?;
+
+        assert!(destination.exists("backup/data/a.txt").await?);
+        assert!(destination.exists("backup/data/sub/b.txt").await?);
+        assert!(!destination.exists("backup/a.txt").await?);
 
         Ok(())
     }
 
+    #[test]
+    fn test_link_policy() {
+        assert!(LinkPolicy::Follow.follow("link").unwrap());
+        assert!(!LinkPolicy::Skip.follow("link").unwrap());
+
+        let err = LinkPolicy::Error.follow("link").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(err.source_path(), Some("link"));
+    }
+
     #[tokio::test]
     async fn test_copy_file_overwrite() -> Result<(), Error> {
         let source = Operator::new(Memory::default())?.finish();
@@ -546,6 +2522,26 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_copy_with_pipeline() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "foo").await?;
+        source.write("dir/b.txt", "bar").await?;
+
+        let pipeline = Pipeline::new()
+            .with(|| |chunk: bytes::Bytes| Ok(bytes::Bytes::from(chunk.to_ascii_uppercase())));
+
+        let copier = Copier::new(source, destination.clone()).with_pipeline(pipeline);
+        copier.copy("dir/", "out/").await?;
+
+        assert_eq!(destination.read("out/a.txt").await?.to_vec(), b"FOO");
+        assert_eq!(destination.read("out/b.txt").await?.to_vec(), b"BAR");
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_copy_directory_overwrite_existing_files() -> Result<(), Error> {
         let source = Operator::new(Memory::default())?.finish();
@@ -574,6 +2570,321 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_copy_with_rate_limiter() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file1.txt", "0123456789").await?;
+        source.write("file2.txt", "0123456789").await?;
+
+        let limiter = RateLimiter::new(crate::RateLimit {
+            bytes_per_second: Some(10),
+            ..Default::default()
+        })?;
+
+        let start = tokio::time::Instant::now();
+
+        // Copiers sharing a limiter draw from the same budget
+        let copier1 =
+            Copier::new(source.clone(), destination.clone()).with_rate_limiter(limiter.clone());
+        let copier2 = Copier::new(source, destination.clone()).with_rate_limiter(limiter);
+
+        copier1.copy("file1.txt", "file1.txt").await?;
+        copier2.copy("file2.txt", "file2.txt").await?;
+
+        assert!(start.elapsed() >= std::time::Duration::from_secs(1));
+
+        let buffer = destination.read("file2.txt").await?;
+        assert_eq!(buffer.to_vec(), b"0123456789");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_observer() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file.txt", "foo").await?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let copier = Copier::new(source, destination).with_observer(tx);
+        copier.copy("file.txt", "copy.txt").await?;
+        assert!(copier.copy("missing.txt", "copy.txt").await.is_err());
+
+        drop(copier);
+
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                CopyEvent::FileStarted {
+                    source: "file.txt".to_string(),
+                    destination: "copy.txt".to_string(),
+                    size: 3,
+                },
+                CopyEvent::FileProgress {
+                    source: "file.txt".to_string(),
+                    bytes: 3,
+                },
+                CopyEvent::FileCompleted {
+                    source: "file.txt".to_string(),
+                    destination: "copy.txt".to_string(),
+                    bytes: 3,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_copy_with_retry_policy() -> Result<(), Error> {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file.txt", "foo").await?;
+
+        // Treat "not found" as transient: the file shows up after the first attempt
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let policy = RetryPolicy::default().with_retryable(move |err| {
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            err.kind() == ErrorKind::NotFound
+        });
+
+        let copier = Copier::new(source.clone(), destination.clone()).with_retry_policy(policy);
+
+        let missing = copier.copy("missing.txt", "missing.txt").await;
+        assert_eq!(missing.unwrap_err().kind(), ErrorKind::NotFound);
+        // The predicate is consulted before each of the two retries
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        copier.copy("file.txt", "file.txt").await?;
+
+        let buffer = destination.read("file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_atomic_writes() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/file.txt", "foo").await?;
+
+        // The memory service cannot rename, so files are written directly
+        let copier = Copier::new(source, destination.clone()).with_atomic_writes(true);
+        copier.copy("dir/", "out/").await?;
+
+        let entries = destination.list("out/").await?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(destination.read("out/file.txt").await?.to_vec(), b"foo");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_quota() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "aaaa").await?;
+        source.write("dir/b.txt", "bbbb").await?;
+        destination.write("tenant/existing.txt", "xx").await?;
+
+        let quota = Quota::new(
+            destination.clone(),
+            "tenant/",
+            QuotaOptions {
+                max_bytes: Some(8),
+                max_objects: None,
+            },
+        );
+
+        let copier = Copier::new(source, destination.clone()).with_quota(quota.clone());
+
+        copier.copy("dir/a.txt", "tenant/a.txt").await?;
+
+        let err = copier.copy("dir/b.txt", "tenant/b.txt").await.unwrap_err();
+        assert!(QuotaExceeded::from_error(err.inner()).is_some());
+        assert!(!destination.exists("tenant/b.txt").await?);
+        assert_eq!(quota.usage().bytes, 6);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_error() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "a").await?;
+        source.write("dir/b.txt", "b").await?;
+
+        let copier = Copier::new(source, destination.clone());
+
+        let err = copier.copy("missing.txt", "out.txt").await.unwrap_err();
+        assert_eq!(err.phase(), CopyPhase::Stat);
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.source_path(), Some("missing.txt"));
+        assert_eq!(err.destination_path(), None);
+
+        // Fail the second file
+        let quota = Quota::new(
+            destination.clone(),
+            "out/",
+            QuotaOptions {
+                max_bytes: None,
+                max_objects: Some(1),
+            },
+        );
+
+        let err = copier
+            .with_quota(quota)
+            .copy("dir/", "out/")
+            .await
+            .unwrap_err();
+        assert_eq!(err.phase(), CopyPhase::Write);
+        assert_eq!(err.source_path(), Some("dir/b.txt"));
+        assert_eq!(err.destination_path(), Some("out/b.txt"));
+        assert_eq!(
+            err.completed(),
+            &[CopiedEntry {
+                source: "dir/a.txt".to_string(),
+                destination: "out/a.txt".to_string(),
+            }]
+        );
+
+        let err = Error::from(err);
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.to_string().contains("out/b.txt"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copier_builder() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("tenant-a/data/file.txt", "foo").await?;
+        source.write("tenant-b/secret.txt", "bar").await?;
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+
+        let copier = Copier::builder(source, destination.clone())
+            .source_root("tenant-a/")
+            .destination_root("/backups/tenant-a")
+            .layers(&LayerConfig::default())
+            .options(CopyOptions {
+                recursive: true,
+                ..Default::default()
+            })
+            .observer(move |event: &CopyEvent| recorded.lock().unwrap().push(event.clone()))
+            .observer(|_: &CopyEvent| ())
+            .build();
+
+        copier.copy("/data/", "data/").await?;
+
+        assert_eq!(
+            destination
+                .read("backups/tenant-a/data/file.txt")
+                .await?
+                .to_vec(),
+            b"foo"
+        );
+        assert_eq!(events.lock().unwrap().len(), 3);
+
+        let err = copier
+            .copy("../tenant-b/secret.txt", "secret.txt")
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_root() {
+        assert_eq!(resolve_root("", "a/b").unwrap(), "a/b");
+        assert_eq!(resolve_root("root/", "/a/b/").unwrap(), "root/a/b/");
+        assert_eq!(resolve_root("/root", "a/../b").unwrap(), "root/b");
+        assert!(resolve_root("root/", "../other").is_err());
+        assert!(resolve_root("root/", "../rootkit/a").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_copy_budget() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file.txt", "foo").await?;
+
+        let budget = TransferBudget::new(crate::RateLimit::default())?.with_max_transfers(1);
+
+        let copier = Copier::new(source, destination.clone()).with_budget(budget.clone());
+
+        // Another copier holds the only transfer
+        let permit = budget.acquire_transfer().await;
+
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(10),
+            copier.copy("file.txt", "a.txt"),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        drop(permit);
+
+        copier.copy("file.txt", "a.txt").await?;
+        assert!(destination.exists("a.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_file_to_nonexistent_directory() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/file.txt", "foo").await?;
+
+        let copier = Copier::new(source, destination.clone());
+        copier.copy("dir/file.txt", "ingest/").await?;
+
+        let buffer = destination.read("ingest/file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_name() {
+        let meta = Metadata::new(EntryMode::FILE)
+            .with_content_disposition("attachment; filename=\"report.pdf\"".to_string());
+        let source = Source::new(Utf8UnixPathBuf::from("download/123"), meta);
+
+        assert_eq!(source.name(false).unwrap(), "123");
+        assert_eq!(source.name(true).unwrap(), "report.pdf");
+
+        let source = Source::new(Utf8UnixPathBuf::from(""), Metadata::new(EntryMode::FILE));
+        assert!(source.name(true).is_err());
+    }
+
     #[test]
     fn test_normalize_path() {
         // Simple file paths
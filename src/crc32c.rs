@@ -0,0 +1,69 @@
+// CRC-32C (Castagnoli) lookup table for the reflected polynomial.
+const TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+/// Rolling CRC-32C checksum of a stream of bytes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Crc32c {
+    crc: u32,
+    len: u64,
+}
+
+impl Crc32c {
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        let mut crc = !self.crc;
+
+        for byte in bytes {
+            crc = TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8);
+        }
+
+        self.crc = !crc;
+        self.len += bytes.len() as u64;
+    }
+
+    pub(crate) fn value(&self) -> u32 {
+        self.crc
+    }
+
+    /// Number of bytes checksummed.
+    pub(crate) fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32c() {
+        let mut crc = Crc32c::default();
+        crc.update(b"1234");
+        crc.update(b"56789");
+
+        assert_eq!(crc.value(), 0xe306_9283);
+        assert_eq!(crc.len(), 9);
+        assert_eq!(Crc32c::default().value(), 0);
+    }
+}
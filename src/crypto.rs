@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use opendal::{Error, ErrorKind};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::ByteTransform;
+
+// Header of encrypted objects: magic, format version, key ID length, key ID, nonce prefix.
+const MAGIC: &[u8; 4] = b"ODUE";
+const VERSION: u8 = 1;
+
+// Plaintext is encrypted in segments, so objects can be streamed without buffering them.
+const SEGMENT_SIZE: usize = 64 * 1024;
+const TAG_LEN: usize = 16;
+
+// Nonces are made of a random prefix (per object), a segment counter and a flag marking the last segment
+// (the STREAM construction), so segments can't be reordered, dropped or truncated undetected.
+const NONCE_PREFIX_LEN: usize = NONCE_LEN - 5;
+
+/// A 256-bit AES-GCM key.
+pub type Key = [u8; 32];
+
+/// Provides the keys used to encrypt and decrypt objects.
+///
+/// The ID of the key is stored in the header of encrypted objects, so keys can be rotated:
+/// new objects are encrypted with the current key, while older ones remain readable.
+pub trait KeyProvider: Send + Sync {
+    /// The key used to encrypt new objects, along with its ID (at most 255 bytes).
+    fn encryption_key(&self) -> Result<(String, Key), Error>;
+
+    /// The key with a given ID, used to decrypt objects.
+    fn decryption_key(&self, id: &str) -> Result<Key, Error>;
+}
+
+/// A [`KeyProvider`] holding its keys in memory.
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    current: String,
+    keys: HashMap<String, Key>,
+}
+
+impl StaticKeyProvider {
+    /// Encrypt and decrypt objects with a single key.
+    pub fn new(id: impl Into<String>, key: Key) -> Self {
+        let id = id.into();
+
+        Self {
+            keys: HashMap::from([(id.clone(), key)]),
+            current: id,
+        }
+    }
+
+    /// Add a key only used to decrypt objects (eg. a key rotated out).
+    pub fn with_key(mut self, id: impl Into<String>, key: Key) -> Self {
+        self.keys.entry(id.into()).or_insert(key);
+        self
+    }
+}
+
+impl fmt::Debug for StaticKeyProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the keys
+        f.debug_struct("StaticKeyProvider")
+            .field("current", &self.current)
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn encryption_key(&self) -> Result<(String, Key), Error> {
+        let key = self.decryption_key(&self.current)?;
+
+        Ok((self.current.clone(), key))
+    }
+
+    fn decryption_key(&self, id: &str) -> Result<Key, Error> {
+        self.keys.get(id).copied().ok_or_else(|| {
+            Error::new(ErrorKind::PermissionDenied, "Unknown encryption key")
+                .with_context("key_id", id)
+        })
+    }
+}
+
+/// Encrypts a stream with AES-256-GCM (see [`Decryptor`] for the reverse).
+///
+/// Every object gets a random nonce prefix, so the same key can safely encrypt any number of objects.
+/// Add it to a [`Pipeline`](crate::Pipeline) to encrypt objects on their way to the destination of a copy:
+///
+/// ```no_run
+/// # fn example(source: opendal::Operator, destination: opendal::Operator) {
+/// use std::sync::Arc;
+///
+/// use opendal_util::crypto::{Encryptor, KeyProvider, StaticKeyProvider};
+/// use opendal_util::{Copier, Pipeline};
+///
+/// let keys: Arc<dyn KeyProvider> = Arc::new(StaticKeyProvider::new("2024-06", [0; 32]));
+///
+/// let pipeline = Pipeline::new().with(move || Encryptor::new(keys.clone()));
+///
+/// let copier = Copier::new(source, destination).with_pipeline(pipeline);
+/// # }
+/// ```
+pub struct Encryptor {
+    provider: Arc<dyn KeyProvider>,
+    segments: Option<Segments>,
+    buffer: BytesMut,
+}
+
+impl Encryptor {
+    pub fn new(provider: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            provider,
+            segments: None,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    // Initialize the encryption, returning the header.
+    fn start(&mut self) -> Result<Vec<u8>, Error> {
+        let (id, key) = self.provider.encryption_key()?;
+
+        let id_len = u8::try_from(id.len()).map_err(|_| {
+            Error::new(ErrorKind::ConfigInvalid, "Encryption key ID is too long")
+                .with_context("key_id", &id)
+        })?;
+
+        let mut prefix = [0; NONCE_PREFIX_LEN];
+
+        SystemRandom::new()
+            .fill(&mut prefix)
+            .map_err(|_| Error::new(ErrorKind::Unexpected, "Failed to generate nonce"))?;
+
+        let mut header = Vec::with_capacity(MAGIC.len() + 2 + id.len() + NONCE_PREFIX_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(id_len);
+        header.extend_from_slice(id.as_bytes());
+        header.extend_from_slice(&prefix);
+
+        self.segments = Some(Segments::new(&id, &key, prefix)?);
+
+        Ok(header)
+    }
+}
+
+impl ByteTransform for Encryptor {
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error> {
+        let mut output = BytesMut::new();
+
+        if self.segments.is_none() {
+            output.extend_from_slice(&self.start()?);
+        }
+
+        self.buffer.extend_from_slice(&chunk);
+
+        let segments = self.segments.as_mut().expect("encryption started");
+
+        // Keep at least one byte back: the last segment is only known when the stream ends
+        while self.buffer.len() > SEGMENT_SIZE {
+            let segment = self.buffer.split_to(SEGMENT_SIZE);
+
+            output.extend_from_slice(&segments.seal(&segment, false)?);
+        }
+
+        Ok(output.freeze())
+    }
+
+    fn finish(&mut self) -> Result<Bytes, Error> {
+        let mut output = BytesMut::new();
+
+        if self.segments.is_none() {
+            output.extend_from_slice(&self.start()?);
+        }
+
+        let segments = self.segments.as_mut().expect("encryption started");
+        let segment = self.buffer.split();
+
+        output.extend_from_slice(&segments.seal(&segment, true)?);
+
+        Ok(output.freeze())
+    }
+}
+
+/// Decrypts a stream encrypted by an [`Encryptor`], failing if it was tampered with or truncated.
+///
+/// The key is looked up by the ID stored in the header of the object.
+pub struct Decryptor {
+    provider: Arc<dyn KeyProvider>,
+    segments: Option<Segments>,
+    buffer: BytesMut,
+}
+
+impl Decryptor {
+    pub fn new(provider: Arc<dyn KeyProvider>) -> Self {
+        Self {
+            provider,
+            segments: None,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    // Parse the header once it is completely buffered.
+    fn start(&mut self) -> Result<(), Error> {
+        let fixed = MAGIC.len() + 2;
+
+        if self.buffer.len() < fixed {
+            return Ok(());
+        }
+
+        if &self.buffer[..MAGIC.len()] != MAGIC {
+            return Err(Error::new(ErrorKind::Unexpected, "Object is not encrypted"));
+        }
+
+        let version = self.buffer[MAGIC.len()];
+
+        if version != VERSION {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Unsupported encryption format version",
+            )
+            .with_context("version", version));
+        }
+
+        let id_len = self.buffer[MAGIC.len() + 1] as usize;
+
+        if self.buffer.len() < fixed + id_len + NONCE_PREFIX_LEN {
+            return Ok(());
+        }
+
+        let header = self.buffer.split_to(fixed + id_len + NONCE_PREFIX_LEN);
+
+        let id = std::str::from_utf8(&header[fixed..fixed + id_len])
+            .map_err(|_| Error::new(ErrorKind::Unexpected, "Invalid encryption key ID"))?;
+
+        let mut prefix = [0; NONCE_PREFIX_LEN];
+        prefix.copy_from_slice(&header[fixed + id_len..]);
+
+        let key = self.provider.decryption_key(id)?;
+
+        self.segments = Some(Segments::new(id, &key, prefix)?);
+
+        Ok(())
+    }
+}
+
+impl ByteTransform for Decryptor {
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error> {
+        self.buffer.extend_from_slice(&chunk);
+
+        if self.segments.is_none() {
+            self.start()?;
+        }
+
+        let Some(segments) = self.segments.as_mut() else {
+            return Ok(Bytes::new());
+        };
+
+        let mut output = BytesMut::new();
+
+        while self.buffer.len() > SEGMENT_SIZE + TAG_LEN {
+            let segment = self.buffer.split_to(SEGMENT_SIZE + TAG_LEN);
+
+            output.extend_from_slice(&segments.open(&segment, false)?);
+        }
+
+        Ok(output.freeze())
+    }
+
+    fn finish(&mut self) -> Result<Bytes, Error> {
+        let segments = self
+            .segments
+            .as_mut()
+            .ok_or_else(|| Error::new(ErrorKind::Unexpected, "Encrypted object is truncated"))?;
+
+        let segment = self.buffer.split();
+
+        Ok(Bytes::from(segments.open(&segment, true)?))
+    }
+}
+
+struct Segments {
+    key: LessSafeKey,
+    key_id: String,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+}
+
+impl Segments {
+    fn new(key_id: &str, key: &Key, prefix: [u8; NONCE_PREFIX_LEN]) -> Result<Self, Error> {
+        let key = UnboundKey::new(&AES_256_GCM, key)
+            .map_err(|_| Error::new(ErrorKind::ConfigInvalid, "Invalid encryption key"))?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            key_id: key_id.to_string(),
+            prefix,
+            counter: 0,
+        })
+    }
+
+    fn nonce(&mut self, last: bool) -> Result<Nonce, Error> {
+        let mut nonce = [0; NONCE_LEN];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.prefix);
+        nonce[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&self.counter.to_be_bytes());
+        nonce[NONCE_LEN - 1] = last as u8;
+
+        self.counter = self
+            .counter
+            .checked_add(1)
+            .ok_or_else(|| Error::new(ErrorKind::Unsupported, "Object is too large to encrypt"))?;
+
+        Ok(Nonce::assume_unique_for_key(nonce))
+    }
+
+    fn seal(&mut self, plaintext: &[u8], last: bool) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce(last)?;
+
+        let mut segment = plaintext.to_vec();
+
+        self.key
+            .seal_in_place_append_tag(nonce, Aad::empty(), &mut segment)
+            .map_err(|_| Error::new(ErrorKind::Unexpected, "Failed to encrypt segment"))?;
+
+        Ok(segment)
+    }
+
+    fn open(&mut self, ciphertext: &[u8], last: bool) -> Result<Vec<u8>, Error> {
+        let nonce = self.nonce(last)?;
+
+        let mut segment = ciphertext.to_vec();
+
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut segment)
+            .map_err(|_| {
+                Error::new(ErrorKind::Unexpected, "Failed to decrypt segment")
+                    .with_context("key_id", &self.key_id)
+                    .with_context("segment", self.counter - 1)
+            })?;
+
+        let len = plaintext.len();
+        segment.truncate(len);
+
+        Ok(segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use opendal::Operator;
+    use opendal::services::Memory;
+
+    use super::*;
+    use crate::{Copier, Pipeline, read_transformed};
+
+    fn apply(
+        transform: &mut dyn ByteTransform,
+        input: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let mut output = Vec::new();
+
+        for chunk in input.chunks(chunk_size) {
+            output.extend_from_slice(&transform.transform(Bytes::copy_from_slice(chunk))?);
+        }
+
+        output.extend_from_slice(&transform.finish()?);
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<(), Error> {
+        let old = StaticKeyProvider::new("old", [1; 32]);
+        let keys: Arc<dyn KeyProvider> =
+            Arc::new(StaticKeyProvider::new("new", [2; 32]).with_key("old", [1; 32]));
+
+        let plaintext: Vec<u8> = (0..SEGMENT_SIZE * 2 + 5).map(|i| i as u8).collect();
+
+        let encrypted = apply(&mut Encryptor::new(Arc::new(old)), &plaintext, 1000)?;
+        assert_ne!(
+            &encrypted[encrypted.len() - 100..],
+            &plaintext[plaintext.len() - 100..]
+        );
+
+        // Objects encrypted with a rotated key remain readable
+        let decrypted = apply(&mut Decryptor::new(keys.clone()), &encrypted, 777)?;
+        assert_eq!(decrypted, plaintext);
+
+        let empty = apply(&mut Encryptor::new(keys.clone()), b"", 1)?;
+        assert!(apply(&mut Decryptor::new(keys.clone()), &empty, 1)?.is_empty());
+
+        // Tampering and truncation are detected
+        let mut tampered = encrypted.clone();
+        tampered[100] ^= 1;
+        assert!(apply(&mut Decryptor::new(keys.clone()), &tampered, 4096).is_err());
+
+        let truncated = &encrypted[..encrypted.len() - 10];
+        assert!(apply(&mut Decryptor::new(keys.clone()), truncated, 4096).is_err());
+
+        let truncated =
+            &encrypted[..MAGIC.len() + 2 + 3 + NONCE_PREFIX_LEN + SEGMENT_SIZE + TAG_LEN];
+        assert!(apply(&mut Decryptor::new(keys.clone()), truncated, 4096).is_err());
+
+        let err = apply(&mut Decryptor::new(keys), b"plain text", 4096).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_encrypted() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("secret.txt", "top secret").await?;
+
+        let keys: Arc<dyn KeyProvider> = Arc::new(StaticKeyProvider::new("key", [7; 32]));
+
+        let encrypt = {
+            let keys = keys.clone();
+            Pipeline::new().with(move || Encryptor::new(keys.clone()))
+        };
+        let decrypt = Pipeline::new().with(move || Decryptor::new(keys.clone()));
+
+        Copier::new(source, destination.clone())
+            .with_pipeline(encrypt)
+            .copy("secret.txt", "secret.txt.enc")
+            .await?;
+
+        let stored = destination.read("secret.txt.enc").await?.to_vec();
+        assert!(!stored.windows(6).any(|window| window == b"secret"));
+
+        let output: Vec<Bytes> = read_transformed(&destination, "secret.txt.enc", &decrypt)
+            .await?
+            .try_collect()
+            .await?;
+
+        assert_eq!(output.concat(), b"top secret");
+
+        Ok(())
+    }
+}
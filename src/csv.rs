@@ -0,0 +1,197 @@
+use csv_async::{AsyncReaderBuilder, AsyncSerializer, AsyncWriterBuilder};
+use futures::io::AsyncWriteExt;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use opendal::{Error, ErrorKind, FuturesAsyncWriter, Operator};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+use crate::copy::IoErrorExt;
+
+/// Options for reading and writing CSV objects.
+#[derive(Debug, Copy, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CsvOptions {
+    /// Field delimiter (defaults to `,`).
+    pub delimiter: u8,
+
+    /// Whether the first record is a header row (defaults to `true`).
+    ///
+    /// When reading, headers are used to map fields to struct fields by name.
+    /// When writing, headers are derived from the field names of the first record.
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+}
+
+/// Stream the records of a CSV object, deserializing every record as `T`.
+pub async fn read_csv<T>(
+    operator: &Operator,
+    path: &str,
+    options: CsvOptions,
+) -> Result<BoxStream<'static, Result<T, Error>>, Error>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let reader = operator
+        .reader(path)
+        .await?
+        .into_futures_async_read(..)
+        .await?;
+
+    let path = path.to_string();
+
+    Ok(AsyncReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .create_deserializer(reader)
+        .into_deserialize::<T>()
+        .map_err(move |err| csv_error(err).with_context("path", &path))
+        .boxed())
+}
+
+/// Writes serializable records to a CSV object.
+///
+/// The object is only complete once [`CsvWriter::close`] returns.
+pub struct CsvWriter {
+    serializer: AsyncSerializer<FuturesAsyncWriter>,
+}
+
+impl CsvWriter {
+    pub async fn new(operator: &Operator, path: &str, options: CsvOptions) -> Result<Self, Error> {
+        let writer = operator
+            .writer_with(path)
+            .content_type("text/csv")
+            .await?
+            .into_futures_async_write();
+
+        let serializer = AsyncWriterBuilder::new()
+            .delimiter(options.delimiter)
+            .has_headers(options.has_headers)
+            .create_serializer(writer);
+
+        Ok(Self { serializer })
+    }
+
+    /// Serialize a value and append it as a record.
+    pub async fn write<T: Serialize>(&mut self, record: T) -> Result<(), Error> {
+        self.serializer.serialize(record).await.map_err(csv_error)
+    }
+
+    /// Flush buffered records to the underlying writer.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.serializer
+            .flush()
+            .await
+            .map_err(IoErrorExt::into_opendal_error)
+    }
+
+    /// Flush the remaining records and complete the object.
+    pub async fn close(self) -> Result<(), Error> {
+        let mut writer = self
+            .serializer
+            .into_inner()
+            .await
+            .map_err(|err| err.into_error().into_opendal_error())?;
+
+        writer.close().await.map_err(IoErrorExt::into_opendal_error)
+    }
+}
+
+fn csv_error(err: csv_async::Error) -> Error {
+    Error::new(ErrorKind::Unexpected, "Invalid CSV record").set_source(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Row {
+        name: String,
+        count: u32,
+    }
+
+    #[tokio::test]
+    async fn test_csv_roundtrip() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let options = CsvOptions {
+            delimiter: b';',
+            ..Default::default()
+        };
+
+        let mut writer = CsvWriter::new(&operator, "export.csv", options).await?;
+
+        writer
+            .write(Row {
+                name: "foo".to_string(),
+                count: 1,
+            })
+            .await?;
+        writer
+            .write(Row {
+                name: "bar".to_string(),
+                count: 2,
+            })
+            .await?;
+
+        writer.close().await?;
+
+        let content = operator.read("export.csv").await?;
+        assert_eq!(content.to_vec(), b"name;count\nfoo;1\nbar;2\n");
+
+        let rows: Vec<Row> = read_csv(&operator, "export.csv", options)
+            .await?
+            .try_collect()
+            .await?;
+
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    name: "foo".to_string(),
+                    count: 1
+                },
+                Row {
+                    name: "bar".to_string(),
+                    count: 2
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_csv_without_headers() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data.csv", "foo,1\nbar,x\n").await?;
+
+        let options = CsvOptions {
+            has_headers: false,
+            ..Default::default()
+        };
+
+        let mut stream = read_csv::<(String, u32)>(&operator, "data.csv", options).await?;
+
+        assert_eq!(stream.try_next().await?, Some(("foo".to_string(), 1)));
+        assert!(stream.try_next().await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use opendal::{Error, ErrorKind, Operator};
+use typed_path::{Utf8UnixPath, Utf8UnixPathBuf};
+
+use crate::copy::normalize_path;
+
+/// Create a directory and all of its missing parents (like `mkdir -p`).
+///
+/// Does nothing when the directory already exists.
+pub async fn ensure_dir(operator: &Operator, path: &str) -> Result<(), Error> {
+    DirCreator::new(operator.clone()).ensure(path).await
+}
+
+/// Creates directories, remembering the ones known to exist to avoid checking them again.
+///
+/// Useful when creating the parents of many files (e.g. when copying a directory tree).
+#[derive(Debug, Clone)]
+pub struct DirCreator {
+    operator: Operator,
+    existing: HashSet<Utf8UnixPathBuf>,
+}
+
+impl DirCreator {
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            existing: HashSet::new(),
+        }
+    }
+
+    /// Create a directory and all of its missing parents.
+    pub async fn ensure(&mut self, path: &str) -> Result<(), Error> {
+        let path = normalize_path(path);
+        let path = Utf8UnixPath::new(path.as_str().trim_end_matches('/'));
+
+        // Find the deepest existing ancestor, then create the missing ones from the top
+        let mut missing = Vec::new();
+
+        for dir in path.ancestors() {
+            if dir.as_str().is_empty() || self.existing.contains(dir) {
+                break;
+            }
+
+            match self.operator.stat(&format!("{}/", dir)).await {
+                Ok(meta) if meta.is_dir() => {
+                    self.existing.insert(dir.to_owned());
+                    break;
+                }
+                Ok(_) => {
+                    return Err(Error::new(ErrorKind::NotADirectory, "Path is a file")
+                        .with_context("path", dir));
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => missing.push(dir.to_owned()),
+                Err(err) => return Err(err),
+            }
+        }
+
+        for dir in missing.into_iter().rev() {
+            self.operator.create_dir(&format!("{}/", dir)).await?;
+
+            self.existing.insert(dir);
+        }
+
+        Ok(())
+    }
+
+    /// Remember that a directory exists without checking it.
+    pub fn mark_existing(&mut self, path: &str) {
+        let path = normalize_path(path);
+
+        self.existing
+            .insert(Utf8UnixPathBuf::from(path.as_str().trim_end_matches('/')));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ensure_dir() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        ensure_dir(&operator, "/a/b/c").await?;
+
+        assert!(operator.exists("a/").await?);
+        assert!(operator.exists("a/b/").await?);
+        assert!(operator.exists("a/b/c/").await?);
+
+        // Existing directories are left alone
+        operator.write("a/b/file.txt", "foo").await?;
+        ensure_dir(&operator, "a/b/").await?;
+        assert!(operator.exists("a/b/file.txt").await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use futures::stream;
+use opendal::{Buffer, Error, ErrorKind, Operator};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+use crate::write::write_atomic_with;
+
+/// Options for controlling how documents are written.
+#[derive(Debug, Copy, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct DocumentOptions {
+    /// Whether to write the document atomically (see [`write_atomic`](crate::write_atomic)).
+    pub atomic: bool,
+}
+
+/// Read a JSON document and deserialize it.
+pub async fn read_json<T: DeserializeOwned>(operator: &Operator, path: &str) -> Result<T, Error> {
+    let buffer = operator.read(path).await?;
+
+    serde_json::from_slice(&buffer.to_vec()).map_err(|err| invalid_document("JSON", path, err))
+}
+
+/// Serialize a value and write it as a JSON document.
+pub async fn write_json<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+) -> Result<(), Error> {
+    write_json_options(operator, path, value, DocumentOptions::default()).await
+}
+
+pub async fn write_json_options<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+    options: DocumentOptions,
+) -> Result<(), Error> {
+    let content = serde_json::to_vec_pretty(value).map_err(|err| serialize_error("JSON", err))?;
+
+    write_document(operator, path, content, "application/json", options).await
+}
+
+/// Read a TOML document and deserialize it.
+#[cfg(feature = "toml")]
+pub async fn read_toml<T: DeserializeOwned>(operator: &Operator, path: &str) -> Result<T, Error> {
+    let buffer = operator.read(path).await?;
+
+    let content =
+        String::from_utf8(buffer.to_vec()).map_err(|err| invalid_document("TOML", path, err))?;
+
+    toml::from_str(&content).map_err(|err| invalid_document("TOML", path, err))
+}
+
+/// Serialize a value and write it as a TOML document.
+#[cfg(feature = "toml")]
+pub async fn write_toml<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+) -> Result<(), Error> {
+    write_toml_options(operator, path, value, DocumentOptions::default()).await
+}
+
+#[cfg(feature = "toml")]
+pub async fn write_toml_options<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+    options: DocumentOptions,
+) -> Result<(), Error> {
+    let content = toml::to_string_pretty(value).map_err(|err| serialize_error("TOML", err))?;
+
+    write_document(operator, path, content, "application/toml", options).await
+}
+
+/// Read a YAML document and deserialize it.
+#[cfg(feature = "yaml")]
+pub async fn read_yaml<T: DeserializeOwned>(operator: &Operator, path: &str) -> Result<T, Error> {
+    let buffer = operator.read(path).await?;
+
+    serde_yaml::from_slice(&buffer.to_vec()).map_err(|err| invalid_document("YAML", path, err))
+}
+
+/// Serialize a value and write it as a YAML document.
+#[cfg(feature = "yaml")]
+pub async fn write_yaml<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+) -> Result<(), Error> {
+    write_yaml_options(operator, path, value, DocumentOptions::default()).await
+}
+
+#[cfg(feature = "yaml")]
+pub async fn write_yaml_options<T: Serialize + ?Sized>(
+    operator: &Operator,
+    path: &str,
+    value: &T,
+    options: DocumentOptions,
+) -> Result<(), Error> {
+    let content = serde_yaml::to_string(value).map_err(|err| serialize_error("YAML", err))?;
+
+    write_document(operator, path, content, "application/yaml", options).await
+}
+
+async fn write_document(
+    operator: &Operator,
+    path: &str,
+    content: impl Into<Buffer>,
+    content_type: &str,
+    options: DocumentOptions,
+) -> Result<(), Error> {
+    if options.atomic {
+        let stream = stream::iter([Ok(content.into())]);
+
+        return write_atomic_with(operator, path, stream, Some(content_type)).await;
+    }
+
+    operator
+        .write_with(path, content)
+        .content_type(content_type)
+        .await?;
+
+    Ok(())
+}
+
+fn invalid_document(
+    format: &str,
+    path: &str,
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> Error {
+    Error::new(
+        ErrorKind::Unexpected,
+        format!("Invalid {} document", format),
+    )
+    .with_context("path", path)
+    .set_source(err)
+}
+
+fn serialize_error(format: &str, err: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::new(
+        ErrorKind::Unexpected,
+        format!("Failed to serialize {} document", format),
+    )
+    .set_source(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Config {
+        name: String,
+        replicas: u32,
+    }
+
+    fn config() -> Config {
+        Config {
+            name: "app".to_string(),
+            replicas: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        write_json(&operator, "config.json", &config()).await?;
+
+        let stat = operator.stat("config.json").await?;
+        assert_eq!(stat.content_type(), Some("application/json"));
+
+        let read: Config = read_json(&operator, "config.json").await?;
+        assert_eq!(read, config());
+
+        write_json_options(
+            &operator,
+            "state.json",
+            &config(),
+            DocumentOptions { atomic: true },
+        )
+        .await?;
+
+        let read: Config = read_json(&operator, "state.json").await?;
+        assert_eq!(read, config());
+
+        let stat = operator.stat("state.json").await?;
+        assert_eq!(stat.content_type(), Some("application/json"));
+
+        operator.write("invalid.json", "{").await?;
+        assert!(
+            read_json::<Config>(&operator, "invalid.json")
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "toml")]
+    #[tokio::test]
+    async fn test_toml() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        write_toml(&operator, "config.toml", &config()).await?;
+
+        let read: Config = read_toml(&operator, "config.toml").await?;
+        assert_eq!(read, config());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "yaml")]
+    #[tokio::test]
+    async fn test_yaml() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        write_yaml(&operator, "config.yaml", &config()).await?;
+
+        let read: Config = read_yaml(&operator, "config.yaml").await?;
+        assert_eq!(read, config());
+
+        Ok(())
+    }
+}
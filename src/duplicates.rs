@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use opendal::{Error, Operator, options::ListOptions};
+
+use crate::{Digest, HashAlgorithm, checksum, list};
+
+/// A set of files with identical content.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    /// Size of each file in bytes.
+    pub size: u64,
+
+    pub digest: Digest,
+
+    /// Paths of the files, sorted.
+    pub paths: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Number of bytes that could be saved by keeping a single copy.
+    pub fn savings(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+/// Result of [`find_duplicates`].
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateReport {
+    /// Groups of duplicate files, largest potential savings first.
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// Total number of bytes that could be saved by deduplicating every group.
+    pub fn savings(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::savings).sum()
+    }
+}
+
+/// Find files with identical content under `prefix`.
+///
+/// Files are first grouped by size and only files sharing a size with another file are hashed,
+/// so most files are never read. Hashing is streamed, one file at a time.
+///
+/// Empty files are ignored: deduplicating them saves nothing.
+pub async fn find_duplicates(operator: &Operator, prefix: &str) -> Result<DuplicateReport, Error> {
+    let options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, prefix, Some(options)).await?;
+
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        // Some services don't return the size when listing
+        let size = match entry.metadata().content_length() {
+            0 => operator.stat(entry.path()).await?.content_length(),
+            size => size,
+        };
+
+        if size > 0 {
+            by_size
+                .entry(size)
+                .or_default()
+                .push(entry.path().to_string());
+        }
+    }
+
+    let mut groups = Vec::new();
+
+    for (size, paths) in by_size {
+        if paths.len() < 2 {
+            continue;
+        }
+
+        let mut by_digest: HashMap<Digest, Vec<String>> = HashMap::new();
+
+        for path in paths {
+            let digest = checksum(operator, &path, HashAlgorithm::Sha256).await?;
+
+            by_digest.entry(digest).or_default().push(path);
+        }
+
+        for (digest, mut paths) in by_digest {
+            if paths.len() < 2 {
+                continue;
+            }
+
+            paths.sort();
+
+            groups.push(DuplicateGroup {
+                size,
+                digest,
+                paths,
+            });
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.savings()
+            .cmp(&a.savings())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+
+    Ok(DuplicateReport { groups })
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_find_duplicates() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data/a.txt", "foo").await?;
+        operator.write("data/nested/b.txt", "foo").await?;
+        operator.write("data/c.txt", "bar").await?; // same size, different content
+        operator.write("data/d.txt", "foobar").await?;
+        operator.write("data/e.txt", "foobar").await?;
+        operator.write("data/f.txt", "foobar").await?;
+        operator.write("data/empty1.txt", "").await?;
+        operator.write("data/empty2.txt", "").await?;
+
+        let report = find_duplicates(&operator, "data/").await?;
+
+        assert_eq!(report.groups.len(), 2);
+
+        assert_eq!(
+            report.groups[0].paths,
+            vec!["data/d.txt", "data/e.txt", "data/f.txt"]
+        );
+        assert_eq!(report.groups[0].savings(), 12);
+
+        assert_eq!(
+            report.groups[1].paths,
+            vec!["data/a.txt", "data/nested/b.txt"]
+        );
+        assert_eq!(report.groups[1].size, 3);
+
+        assert_eq!(report.savings(), 15);
+
+        Ok(())
+    }
+}
@@ -1,10 +1,71 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
 
+use futures::future::BoxFuture;
+use opendal::layers::{ConcurrentLimitLayer, LoggingLayer, RetryLayer, TimeoutLayer};
 use opendal::{Error, ErrorKind, Operator, OperatorRegistry, OperatorUri};
+use percent_encoding::percent_decode_str;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use url::Url;
 
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_path;
+use crate::{ResolvedLocation, RetryPolicy};
+
 pub trait OperatorFactory: Send + Sync {
     fn load(&self, uri: &str) -> Result<Operator, Error>;
+
+    /// Split a URI into an operator rooted at the top of the storage and the path relative to it.
+    ///
+    /// For example, `s3://bucket/prefix/file.txt` resolves to an operator for `s3://bucket`
+    /// and the path `prefix/file.txt`.
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        let (base, path) = split_uri(uri)?;
+
+        Ok(ResolvedLocation {
+            operator: self.load(&base)?,
+            path,
+        })
+    }
+
+    /// Load an operator and check that its backend is reachable (see [`Operator::check`]).
+    fn check<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move { self.load(uri)?.check().await })
+    }
+
+    /// Run the hooks of an [`OperatorFactoryInterceptor`] around every load.
+    fn with_interceptor<I>(self, interceptor: I) -> InterceptedOperatorFactory<Self, I>
+    where
+        Self: Sized,
+        I: OperatorFactoryInterceptor,
+    {
+        InterceptedOperatorFactory::new(self, interceptor)
+    }
+}
+
+// Split a URI into the URI of its storage (without a path) and the decoded path relative to it.
+fn split_uri(uri: &str) -> Result<(String, String), Error> {
+    let mut url = Url::parse(uri).map_err(|err| {
+        Error::new(ErrorKind::ConfigInvalid, "Failed to parse uri")
+            .with_context("uri", uri)
+            .set_source(err)
+    })?;
+
+    let path = percent_decode_str(url.path())
+        .decode_utf8_lossy()
+        .trim_start_matches('/')
+        .to_string();
+
+    url.set_path("/");
+
+    Ok((url.to_string(), path))
 }
 
 pub struct DefaultOperatorFactory;
@@ -27,19 +88,357 @@ impl OperatorFactory for DefaultOperatorFactory {
     }
 }
 
+impl<T: OperatorFactory + ?Sized> OperatorFactory for Arc<T> {
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        (**self).load(uri)
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        (**self).resolve(uri)
+    }
+
+    fn check<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        (**self).check(uri)
+    }
+}
+
 impl OperatorFactory for OperatorRegistry {
     fn load(&self, uri: &str) -> Result<Operator, Error> {
         OperatorRegistry::load(self, uri)
     }
 }
 
+/// Resolves secrets referenced by placeholders in profile values.
+///
+/// A placeholder has the form `${provider:key}`, e.g. `${env:AWS_SECRET_ACCESS_KEY}`.
+pub trait SecretResolver: Send + Sync {
+    fn resolve(&self, provider: &str, key: &str) -> Result<String, Error>;
+}
+
+/// Resolves `${env:NAME}` placeholders from environment variables
+/// and `${file:/path/to/secret}` placeholders from the content of files (without the trailing newline).
+pub struct DefaultSecretResolver;
+
+impl DefaultSecretResolver {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DefaultSecretResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretResolver for DefaultSecretResolver {
+    fn resolve(&self, provider: &str, key: &str) -> Result<String, Error> {
+        match provider {
+            "env" => std::env::var(key).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "Environment variable not found")
+                    .with_context("name", key)
+                    .set_source(err)
+            }),
+            "file" => std::fs::read_to_string(key)
+                .map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+                .map_err(|err| {
+                    Error::new(ErrorKind::ConfigInvalid, "Failed to read secret file")
+                        .with_context("path", key)
+                        .set_source(err)
+                }),
+            _ => Err(
+                Error::new(ErrorKind::ConfigInvalid, "Unknown secret provider")
+                    .with_context("provider", provider),
+            ),
+        }
+    }
+}
+
+/// Replace every `${provider:key}` placeholder in a value with the secret it references.
+pub fn resolve_placeholders(value: &str, resolver: &dyn SecretResolver) -> Result<String, Error> {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+
+        let placeholder = &rest[start + 2..];
+
+        let end = placeholder.find('}').ok_or_else(|| {
+            Error::new(ErrorKind::ConfigInvalid, "Unterminated placeholder")
+                .with_context("value", value)
+        })?;
+
+        let (provider, key) = placeholder[..end].split_once(':').ok_or_else(|| {
+            Error::new(ErrorKind::ConfigInvalid, "Invalid placeholder")
+                .with_context("placeholder", &placeholder[..end])
+        })?;
+
+        result.push_str(&resolver.resolve(provider, key)?);
+
+        rest = &placeholder[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+// Profile option naming the profile to inherit options from.
+const EXTENDS_KEY: &str = "extends";
+
+/// Loads operators from named profiles, using the scheme of the URI as the profile name.
+///
+/// A profile may inherit the options of another profile by naming it in its `extends` option;
+/// options of the child profile override the ones of the parent.
+///
+/// Profiles can be replaced while the factory is in use (e.g. to pick up rotated credentials)
+/// with [`reload`](ProfileOperatorFactory::reload) or by [watching a file](ProfileOperatorFactory::watch_file).
 pub struct ProfileOperatorFactory {
-    profiles: HashMap<String, HashMap<String, String>>,
+    profiles: RwLock<HashMap<String, HashMap<String, String>>>,
+    aliases: HashMap<String, String>,
+    secret_resolver: Option<Box<dyn SecretResolver>>,
 }
 
 impl ProfileOperatorFactory {
     pub fn new(profiles: HashMap<String, HashMap<String, String>>) -> Self {
-        Self { profiles }
+        Self {
+            profiles: RwLock::new(profiles),
+            aliases: HashMap::new(),
+            secret_resolver: None,
+        }
+    }
+
+    /// Load profiles from a file and reload them every time the file changes.
+    ///
+    /// The file is checked for changes every `interval` and parsed with `parse`.
+    /// If the file cannot be read or parsed on reload, the previous profiles stay in effect.
+    pub fn watch_file<F>(
+        path: impl Into<PathBuf>,
+        interval: Duration,
+        parse: F,
+    ) -> Result<(Arc<Self>, ProfileWatcher), Error>
+    where
+        F: Fn(&str) -> Result<HashMap<String, HashMap<String, String>>, Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let path = path.into();
+
+        let (profiles, modified) = read_profiles(&path, &parse)?;
+
+        let factory = Arc::new(Self::new(profiles));
+
+        let (shutdown, mut shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn({
+            let factory = Arc::downgrade(&factory);
+
+            async move {
+                let mut last_modified = modified;
+
+                let mut ticker = tokio::time::interval(interval);
+                ticker.tick().await;
+
+                loop {
+                    tokio::select! {
+                        _ = ticker.tick() => {}
+                        _ = shutdown_rx.changed() => break,
+                    }
+
+                    let Some(factory) = factory.upgrade() else {
+                        break;
+                    };
+
+                    let modified = std::fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok();
+
+                    if modified.is_none() || modified == last_modified {
+                        continue;
+                    }
+
+                    if let Ok((profiles, modified)) = read_profiles(&path, &parse) {
+                        factory.reload(profiles);
+                        last_modified = modified;
+                    }
+                }
+            }
+        });
+
+        let watcher = ProfileWatcher {
+            task: Some((shutdown, task)),
+        };
+
+        Ok((factory, watcher))
+    }
+
+    /// Replace every profile.
+    ///
+    /// Operators loaded before the reload keep their configuration.
+    pub fn reload(&self, profiles: HashMap<String, HashMap<String, String>>) {
+        *self.profiles.write().expect("profiles lock poisoned") = profiles;
+    }
+
+    /// Build the operator of every profile (and optionally check its backend with [`Operator::check`]),
+    /// so that broken profiles can be detected at startup instead of at first use.
+    pub async fn validate(&self, check: bool) -> ValidationReport {
+        let mut names: Vec<String> = self
+            .profiles
+            .read()
+            .expect("profiles lock poisoned")
+            .keys()
+            .chain(self.aliases.keys())
+            .cloned()
+            .collect();
+        names.sort();
+
+        let results = futures::future::join_all(names.into_iter().map(|name| async move {
+            let result = async {
+                let op = self.load(&format!("{}:///", name))?;
+
+                if check {
+                    op.check().await?;
+                }
+
+                Ok::<_, Error>(())
+            }
+            .await;
+
+            (name, result)
+        }))
+        .await;
+
+        let mut report = ValidationReport::default();
+
+        for (name, result) in results {
+            match result {
+                Ok(()) => report.valid.push(name),
+                Err(err) => {
+                    report.invalid.insert(name, err);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Make a profile available under another name (e.g. `prod` for `s3-prod`).
+    pub fn with_alias(mut self, alias: impl Into<String>, profile: impl Into<String>) -> Self {
+        self.aliases.insert(alias.into(), profile.into());
+        self
+    }
+
+    /// The options of a profile, merged with the options of the profiles it extends.
+    pub fn profile(&self, name: &str) -> Result<HashMap<String, String>, Error> {
+        let name = self.aliases.get(name).map(String::as_str).unwrap_or(name);
+
+        let profiles = self.profiles.read().expect("profiles lock poisoned");
+
+        let mut chain = vec![name];
+        let mut current = name;
+
+        let mut profile = loop {
+            let profile = profiles.get(current).ok_or_else(|| {
+                // Operator::from_uri returns this error as well when a scheme is unsupported,
+                // even though the error description says this error is returned when an operation is not supported.
+                Error::new(ErrorKind::Unsupported, "Profile not found")
+                    .with_context("profile_name", current)
+            })?;
+
+            let Some(parent) = profile.get(EXTENDS_KEY) else {
+                break HashMap::new();
+            };
+
+            if chain.contains(&parent.as_str()) {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, "Profile inheritance cycle")
+                        .with_context("profile_name", name)
+                        .with_context("chain", chain.join(" -> ")),
+                );
+            }
+
+            chain.push(parent);
+            current = parent;
+        };
+
+        // Apply profiles from the root ancestor down to the requested one
+        for name in chain.into_iter().rev() {
+            profile.extend(profiles[name].clone());
+        }
+
+        profile.remove(EXTENDS_KEY);
+
+        Ok(profile)
+    }
+
+    /// Resolve `${provider:key}` placeholders in profile values every time an operator is loaded.
+    pub fn with_secret_resolver(mut self, secret_resolver: impl SecretResolver + 'static) -> Self {
+        self.secret_resolver = Some(Box::new(secret_resolver));
+        self
+    }
+}
+
+/// Result of [`ProfileOperatorFactory::validate`].
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// Names of the valid profiles (and aliases), sorted.
+    pub valid: Vec<String>,
+
+    /// Errors of the broken profiles (and aliases), keyed by name.
+    pub invalid: BTreeMap<String, Error>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+type Profiles = HashMap<String, HashMap<String, String>>;
+
+fn read_profiles(
+    path: &Path,
+    parse: &impl Fn(&str) -> Result<Profiles, Error>,
+) -> Result<(Profiles, Option<SystemTime>), Error> {
+    let modified = std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok();
+
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        Error::new(ErrorKind::ConfigInvalid, "Failed to read profiles")
+            .with_context("path", path.display())
+            .set_source(err)
+    })?;
+
+    let profiles = parse(&content).map_err(|err| err.with_context("path", path.display()))?;
+
+    Ok((profiles, modified))
+}
+
+/// Reloads the profiles of a [`ProfileOperatorFactory`] when the watched file changes.
+///
+/// Watching stops when the watcher is stopped or dropped.
+pub struct ProfileWatcher {
+    task: Option<(watch::Sender<bool>, JoinHandle<()>)>,
+}
+
+impl ProfileWatcher {
+    /// Stop watching the file.
+    pub async fn stop(&mut self) {
+        if let Some((shutdown, task)) = self.task.take() {
+            let _ = shutdown.send(true);
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for ProfileWatcher {
+    fn drop(&mut self) {
+        if let Some((shutdown, _)) = self.task.take() {
+            let _ = shutdown.send(true);
+        }
     }
 }
 
@@ -51,16 +450,22 @@ impl OperatorFactory for ProfileOperatorFactory {
 
         let profile_name = url.scheme();
 
-        let profile = self
-            .profiles
-            .get(profile_name)
-            .ok_or_else(|| {
-                // Operator::from_uri returns this error as well when a scheme is unsupported,
-                // even though the error description says this error is returned when an operation is not supported.
-                Error::new(ErrorKind::Unsupported, "Profile not found")
-                    .with_context("profile_name", profile_name)
-            })?
-            .clone();
+        let profile = self.profile(profile_name)?;
+
+        let profile = match &self.secret_resolver {
+            Some(resolver) => profile
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = resolve_placeholders(&value, resolver.as_ref()).map_err(|err| {
+                        err.with_context("profile_name", profile_name)
+                            .with_context("option", &key)
+                    })?;
+
+                    Ok((key, value))
+                })
+                .collect::<Result<HashMap<_, _>, Error>>()?,
+            None => profile,
+        };
 
         let scheme = profile.get("type").cloned().ok_or_else(|| {
             Error::new(ErrorKind::ConfigInvalid, "Missing 'type' in profile")
@@ -96,19 +501,65 @@ impl ChainOperatorFactory {
 }
 
 impl OperatorFactory for ChainOperatorFactory {
+    /// Load an operator from the first factory supporting the URI.
+    ///
+    /// If no factory supports the URI, the returned error has a [`ChainError`] source
+    /// with the error returned by each factory.
     fn load(&self, uri: &str) -> Result<Operator, Error> {
-        for factory in &self.factories {
+        let mut errors = Vec::new();
+
+        for (index, factory) in self.factories.iter().enumerate() {
             match factory.load(uri) {
                 Ok(op) => return Ok(op),
-                Err(e) if e.kind() == ErrorKind::Unsupported => continue,
+                Err(e) if e.kind() == ErrorKind::Unsupported => errors.push((index, e)),
                 Err(e) => return Err(e),
             }
         }
 
-        Err(Error::new(ErrorKind::Unsupported, "Unsupported URI").with_context("uri", uri))
+        let err = Error::new(ErrorKind::Unsupported, "Unsupported URI").with_context("uri", uri);
+
+        if errors.is_empty() {
+            return Err(err);
+        }
+
+        let chain_error = ChainError { errors };
+
+        Err(err
+            .with_context("errors", chain_error.to_string())
+            .set_source(chain_error))
+    }
+}
+
+/// Errors returned by the factories of a [`ChainOperatorFactory`] when none of them supports a URI.
+#[derive(Debug)]
+pub struct ChainError {
+    /// Index of the factory in the chain and the error it returned.
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl ChainError {
+    /// The [`ChainError`] that caused an error returned by a [`ChainOperatorFactory`], if any.
+    pub fn from_error(err: &Error) -> Option<&ChainError> {
+        std::error::Error::source(err)?.downcast_ref()
+    }
+}
+
+impl std::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (index, err)) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+
+            write!(f, "#{}: {} ({})", index, err.message(), err.kind())?;
+        }
+
+        Ok(())
     }
 }
 
+impl std::error::Error for ChainError {}
+
 #[derive(Default)]
 pub struct ChainOperatorFactoryBuilder {
     factories: Vec<Box<dyn OperatorFactory>>,
@@ -157,3 +608,1084 @@ where
         Ok((self.transform)(op))
     }
 }
+
+/// Layers applied by a [`LayeredOperatorFactory`].
+///
+/// Layers are applied in the following order (outermost first):
+/// logging, tracing, retry, timeout and concurrency limit.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct LayerConfig {
+    /// Retry temporary errors.
+    ///
+    /// The retryable predicate of the policy is ignored: OpenDAL retries errors marked as temporary.
+    pub retry: Option<RetryPolicy>,
+
+    /// Timeout of non-IO operations (e.g. stat, delete), in seconds when serialized.
+    #[cfg_attr(feature = "serde", serde(with = "duration_secs"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<f64>"))]
+    pub timeout: Option<Duration>,
+
+    /// Timeout of every IO operation (e.g. reading a chunk), in seconds when serialized.
+    #[cfg_attr(feature = "serde", serde(with = "duration_secs"))]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<f64>"))]
+    pub io_timeout: Option<Duration>,
+
+    /// Log operations using the `log` crate.
+    pub logging: bool,
+
+    /// Trace operations using the `tracing` crate.
+    #[cfg(feature = "tracing")]
+    pub tracing: bool,
+
+    /// Maximum number of concurrent operations per operator.
+    pub concurrency_limit: Option<usize>,
+}
+
+impl LayerConfig {
+    /// Apply the configured layers to an operator.
+    pub fn apply(&self, op: Operator) -> Operator {
+        let mut op = op;
+
+        if let Some(permits) = self.concurrency_limit {
+            op = op.layer(ConcurrentLimitLayer::new(permits));
+        }
+
+        if self.timeout.is_some() || self.io_timeout.is_some() {
+            let mut layer = TimeoutLayer::new();
+
+            if let Some(timeout) = self.timeout {
+                layer = layer.with_timeout(timeout);
+            }
+
+            if let Some(timeout) = self.io_timeout {
+                layer = layer.with_io_timeout(timeout);
+            }
+
+            op = op.layer(layer);
+        }
+
+        if let Some(policy) = &self.retry {
+            op = op.layer(retry_layer(policy));
+        }
+
+        #[cfg(feature = "tracing")]
+        if self.tracing {
+            op = op.layer(opendal::layers::TracingLayer::new());
+        }
+
+        if self.logging {
+            op = op.layer(LoggingLayer::default());
+        }
+
+        op
+    }
+}
+
+// Serializes optional durations as (fractional) seconds, so configuration files can read `timeout: 30`.
+#[cfg(feature = "serde")]
+mod duration_secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        duration
+            .map(|duration| duration.as_secs_f64())
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Applies the layers of a [`LayerConfig`] to every operator produced by an inner factory.
+pub struct LayeredOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    inner: Inner,
+    config: LayerConfig,
+}
+
+impl<Inner> LayeredOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    pub fn new(inner: Inner, config: LayerConfig) -> Self {
+        Self { inner, config }
+    }
+}
+
+impl<Inner> OperatorFactory for LayeredOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        let op = self.inner.load(uri)?;
+
+        Ok(self.config.apply(op))
+    }
+}
+
+// The retry layer matching a retry policy (ignoring its retryable predicate).
+fn retry_layer(policy: &RetryPolicy) -> RetryLayer {
+    let layer = RetryLayer::new()
+        .with_max_times(policy.max_attempts.saturating_sub(1))
+        .with_min_delay(policy.initial_delay)
+        .with_max_delay(policy.max_delay)
+        .with_factor(policy.factor);
+
+    if policy.jitter {
+        return layer.with_jitter();
+    }
+
+    layer
+}
+
+/// Applies a retry policy to every operator produced by an inner factory,
+/// optionally retrying the factory itself when loading an operator fails with a transient error
+/// (e.g. a credential provider being temporarily unreachable).
+///
+/// ```
+/// use opendal_util::{DefaultOperatorFactory, OperatorFactory, RetryPolicy, RetryingOperatorFactory};
+///
+/// let factory = RetryingOperatorFactory::new(DefaultOperatorFactory::new(), RetryPolicy::default())
+///     .with_load_retry(RetryPolicy::default());
+///
+/// assert!(factory.load("memory:///").is_ok());
+/// ```
+pub struct RetryingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    inner: Inner,
+    policy: RetryPolicy,
+    load_policy: RetryPolicy,
+}
+
+impl<Inner> RetryingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    /// Retry temporary errors of operators according to a policy.
+    ///
+    /// The retryable predicate of the policy is ignored: OpenDAL retries errors marked as temporary.
+    pub fn new(inner: Inner, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            load_policy: RetryPolicy::none(),
+        }
+    }
+
+    /// Retry loading operators according to a policy (not retried by default).
+    ///
+    /// Loading is synchronous, so the current thread is blocked between attempts.
+    pub fn with_load_retry(mut self, policy: RetryPolicy) -> Self {
+        self.load_policy = policy;
+        self
+    }
+
+    fn retry_load<T>(&self, mut load: impl FnMut() -> Result<T, Error>) -> Result<T, Error> {
+        let mut attempt = 1;
+
+        loop {
+            match load() {
+                Ok(value) => return Ok(value),
+                Err(err)
+                    if attempt < self.load_policy.max_attempts
+                        && self.load_policy.is_retryable(&err) =>
+                {
+                    std::thread::sleep(self.load_policy.delay(attempt));
+
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<Inner> OperatorFactory for RetryingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        let op = self.retry_load(|| self.inner.load(uri))?;
+
+        Ok(op.layer(retry_layer(&self.policy)))
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        let location = self.retry_load(|| self.inner.resolve(uri))?;
+
+        Ok(ResolvedLocation {
+            operator: location.operator.layer(retry_layer(&self.policy)),
+            path: location.path,
+        })
+    }
+}
+
+/// Hooks running before and after an [`OperatorFactory`] loads an operator
+/// (e.g. to rewrite URIs, audit loads or enforce policies).
+pub trait OperatorFactoryInterceptor: Send + Sync {
+    /// Called before loading an operator (or resolving a location, with the full URI including the path).
+    ///
+    /// Returns the URI to load (possibly rewritten) or an error to reject the URI.
+    fn before_load(&self, uri: &str) -> Result<String, Error> {
+        Ok(uri.to_string())
+    }
+
+    /// Called with the result of loading an operator from the (possibly rewritten) URI.
+    fn after_load(&self, uri: &str, result: Result<Operator, Error>) -> Result<Operator, Error> {
+        let _ = uri;
+
+        result
+    }
+}
+
+/// Runs the hooks of an [`OperatorFactoryInterceptor`] around an inner factory.
+///
+/// Usually created with [`OperatorFactory::with_interceptor`].
+pub struct InterceptedOperatorFactory<Inner, I>
+where
+    Inner: OperatorFactory,
+    I: OperatorFactoryInterceptor,
+{
+    inner: Inner,
+    interceptor: I,
+}
+
+impl<Inner, I> InterceptedOperatorFactory<Inner, I>
+where
+    Inner: OperatorFactory,
+    I: OperatorFactoryInterceptor,
+{
+    pub fn new(inner: Inner, interceptor: I) -> Self {
+        Self { inner, interceptor }
+    }
+}
+
+impl<Inner, I> OperatorFactory for InterceptedOperatorFactory<Inner, I>
+where
+    Inner: OperatorFactory,
+    I: OperatorFactoryInterceptor,
+{
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        let uri = self.interceptor.before_load(uri)?;
+
+        let result = self.inner.load(&uri);
+
+        self.interceptor.after_load(&uri, result)
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        // Intercept the full URI, so interceptors can rewrite the path (or turn bare paths into URIs)
+        let uri = self.interceptor.before_load(uri)?;
+
+        let (result, path) = match self.inner.resolve(&uri) {
+            Ok(location) => (Ok(location.operator), location.path),
+            Err(err) => (Err(err), String::new()),
+        };
+
+        Ok(ResolvedLocation {
+            operator: self.interceptor.after_load(&uri, result)?,
+            path,
+        })
+    }
+
+    fn check<'a>(&'a self, uri: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move { self.resolve(uri)?.operator.check().await })
+    }
+}
+
+/// Rejects URIs with any of the listed schemes (e.g. `fs` in production).
+pub struct DenySchemes {
+    schemes: Vec<String>,
+}
+
+impl DenySchemes {
+    pub fn new<I, S>(schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            schemes: schemes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl OperatorFactoryInterceptor for DenySchemes {
+    fn before_load(&self, uri: &str) -> Result<String, Error> {
+        let scheme = uri
+            .split_once("://")
+            .map(|(scheme, _)| scheme)
+            .unwrap_or(uri);
+
+        if self
+            .schemes
+            .iter()
+            .any(|denied| denied.eq_ignore_ascii_case(scheme))
+        {
+            return Err(
+                Error::new(ErrorKind::PermissionDenied, "Scheme is not allowed")
+                    .with_context("scheme", scheme)
+                    .with_context("uri", uri),
+            );
+        }
+
+        Ok(uri.to_string())
+    }
+}
+
+/// Rewrites URIs according to a mapping table before delegating to an inner factory
+/// (e.g. to transparently redirect a bucket while migrating storage providers).
+///
+/// Mappings replace a URI prefix with another one; the longest matching prefix wins:
+///
+/// ```
+/// use opendal_util::{DefaultOperatorFactory, MappingOperatorFactory};
+///
+/// let factory = MappingOperatorFactory::new(DefaultOperatorFactory::new())
+///     .with_mapping("gs://bucket/", "s3://mirror-bucket/")
+///     .with_mapping("legacy://", "azblob://");
+///
+/// assert_eq!(factory.rewrite("gs://bucket/x"), "s3://mirror-bucket/x");
+/// ```
+pub struct MappingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    inner: Inner,
+    mappings: Vec<(String, String)>,
+}
+
+impl<Inner> MappingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    pub fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            mappings: Vec::new(),
+        }
+    }
+
+    /// Replace the `from` prefix of URIs with `to`.
+    pub fn with_mapping(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.mappings.push((from.into(), to.into()));
+        self
+    }
+
+    /// Apply the mapping with the longest matching prefix to a URI.
+    pub fn rewrite(&self, uri: &str) -> String {
+        self.mappings
+            .iter()
+            .filter(|(from, _)| uri.starts_with(from.as_str()))
+            .max_by_key(|(from, _)| from.len())
+            .map(|(from, to)| format!("{}{}", to, &uri[from.len()..]))
+            .unwrap_or_else(|| uri.to_string())
+    }
+}
+
+impl<Inner> OperatorFactory for MappingOperatorFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        self.inner.load(&self.rewrite(uri))
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        // Rewrite the full URI, since mappings may include a path
+        self.inner.resolve(&self.rewrite(uri))
+    }
+}
+
+/// Accepts bare paths (`/data/file.txt`, `./relative`) besides URIs,
+/// so that CLIs can accept both through a single factory.
+///
+/// URIs are loaded by the inner factory; bare paths are routed to a configured operator
+/// (typically a filesystem operator). Relative paths are resolved against the current directory,
+/// then made relative to the root of the operator.
+pub struct DefaultSchemeFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    inner: Inner,
+    operator: Operator,
+}
+
+impl<Inner> DefaultSchemeFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    pub fn new(inner: Inner, operator: Operator) -> Self {
+        Self { inner, operator }
+    }
+
+    // The path relative to the root of the operator.
+    fn relative_path(&self, path: &str) -> Result<String, Error> {
+        let absolute = std::path::absolute(path).map_err(|err| {
+            Error::new(ErrorKind::ConfigInvalid, "Failed to make path absolute")
+                .with_context("path", path)
+                .set_source(err)
+        })?;
+
+        // Normalized paths have no leading slash
+        let absolute = normalize_path(&absolute.to_string_lossy().replace('\\', "/"));
+        let absolute = absolute.as_str().trim_end_matches('/');
+
+        let root = self.operator.info().root();
+        let root = root.trim_matches('/');
+
+        let relative = if root.is_empty() {
+            Some(absolute)
+        } else {
+            absolute
+                .strip_prefix(root)
+                .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+        };
+
+        let relative = relative
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "Path is outside of the operator root",
+                )
+                .with_context("path", path)
+                .with_context("root", self.operator.info().root())
+            })?
+            .trim_start_matches('/');
+
+        // Keep the trailing slash of directories
+        if path.ends_with('/') && !relative.is_empty() {
+            return Ok(format!("{}/", relative));
+        }
+
+        Ok(relative.to_string())
+    }
+}
+
+impl<Inner> OperatorFactory for DefaultSchemeFactory<Inner>
+where
+    Inner: OperatorFactory,
+{
+    /// Load an operator from a URI or return the configured operator for bare paths.
+    fn load(&self, uri: &str) -> Result<Operator, Error> {
+        if is_bare_path(uri) {
+            return Ok(self.operator.clone());
+        }
+
+        self.inner.load(uri)
+    }
+
+    fn resolve(&self, uri: &str) -> Result<ResolvedLocation, Error> {
+        if is_bare_path(uri) {
+            return Ok(ResolvedLocation {
+                operator: self.operator.clone(),
+                path: self.relative_path(uri)?,
+            });
+        }
+
+        self.inner.resolve(uri)
+    }
+}
+
+fn is_bare_path(uri: &str) -> bool {
+    !uri.contains("://")
+}
+
+/// Configuration of a profile of a [`ProfileOperatorFactory`].
+///
+/// Options are passed to the service as strings.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct ProfileConfig {
+    /// Service of the profile (e.g. `s3`); may be inherited from the extended profile.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+
+    /// Name of the profile to inherit options from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
+    /// Service options (e.g. `bucket`, `region`).
+    #[serde(flatten)]
+    pub options: HashMap<String, String>,
+}
+
+/// Configuration of operator factories, typically loaded from a configuration file:
+///
+/// ```yaml
+/// default_scheme: fs
+/// layers:
+///   timeout: 30
+/// profiles:
+///   prod:
+///     type: s3
+///     bucket: data
+///     region: eu-west-1
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct FactoryConfig {
+    /// Profiles, keyed by name (the scheme of URIs using them).
+    pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Alternative names of profiles.
+    pub aliases: HashMap<String, String>,
+
+    /// Layers applied to every operator.
+    pub layers: LayerConfig,
+
+    /// Scheme (or profile) of bare paths without a scheme (e.g. `fs`).
+    pub default_scheme: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl FactoryConfig {
+    /// Build a factory loading operators from the configured profiles,
+    /// falling back to [`DefaultOperatorFactory`] for other schemes.
+    ///
+    /// Bare paths are turned into URIs with the default scheme (relative paths are made absolute).
+    pub fn build(self) -> Box<dyn OperatorFactory> {
+        let layers = self.layers.clone();
+        let default_scheme = self.default_scheme.clone();
+
+        let chain = ChainOperatorFactory::builder()
+            .then(ProfileOperatorFactory::from_config(self))
+            .then(DefaultOperatorFactory::new())
+            .build();
+
+        let factory = LayeredOperatorFactory::new(chain, layers);
+
+        match default_scheme {
+            Some(scheme) => Box::new(factory.with_interceptor(DefaultScheme(scheme))),
+            None => Box::new(factory),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ProfileOperatorFactory {
+    /// Create a factory from the profiles and aliases of a configuration.
+    pub fn from_config(config: FactoryConfig) -> Self {
+        let profiles = config
+            .profiles
+            .into_iter()
+            .map(|(name, profile)| {
+                let mut options = profile.options;
+
+                if let Some(service) = profile.service {
+                    options.insert("type".to_string(), service);
+                }
+
+                if let Some(extends) = profile.extends {
+                    options.insert(EXTENDS_KEY.to_string(), extends);
+                }
+
+                (name, options)
+            })
+            .collect();
+
+        let mut factory = Self::new(profiles);
+        factory.aliases = config.aliases;
+
+        factory
+    }
+}
+
+// Turns bare paths into URIs with a default scheme.
+#[cfg(feature = "serde")]
+struct DefaultScheme(String);
+
+#[cfg(feature = "serde")]
+impl OperatorFactoryInterceptor for DefaultScheme {
+    fn before_load(&self, uri: &str) -> Result<String, Error> {
+        if uri.contains("://") {
+            return Ok(uri.to_string());
+        }
+
+        let path = std::path::absolute(uri).map_err(|err| {
+            Error::new(ErrorKind::ConfigInvalid, "Failed to make path absolute")
+                .with_context("path", uri)
+                .set_source(err)
+        })?;
+
+        Ok(format!("{}://{}", self.0, path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_uri() {
+        assert_eq!(
+            split_uri("s3://bucket/prefix/file%20name.txt?region=eu-west-1").unwrap(),
+            (
+                "s3://bucket/?region=eu-west-1".to_string(),
+                "prefix/file name.txt".to_string()
+            )
+        );
+        assert_eq!(
+            split_uri("fs:///tmp/data/").unwrap(),
+            ("fs:///".to_string(), "tmp/data/".to_string())
+        );
+        assert_eq!(
+            split_uri("s3://bucket").unwrap(),
+            ("s3://bucket/".to_string(), "".to_string())
+        );
+        assert!(split_uri("not a uri").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve() -> Result<(), Error> {
+        let location = DefaultOperatorFactory::new().resolve("memory:///dir/file.txt")?;
+
+        assert_eq!(location.path, "dir/file.txt");
+        assert_eq!(location.operator.info().root(), "/");
+
+        location.operator.write(&location.path, "foo").await?;
+        assert!(location.operator.exists("dir/file.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_with_interceptor() -> Result<(), Error> {
+        use std::sync::Mutex;
+
+        // Redirects a legacy scheme and records every load
+        struct Audit(Mutex<Vec<String>>);
+
+        impl OperatorFactoryInterceptor for &Audit {
+            fn before_load(&self, uri: &str) -> Result<String, Error> {
+                Ok(uri.replace("legacy://", "memory://"))
+            }
+
+            fn after_load(
+                &self,
+                uri: &str,
+                result: Result<Operator, Error>,
+            ) -> Result<Operator, Error> {
+                self.0.lock().unwrap().push(uri.to_string());
+
+                result
+            }
+        }
+
+        let audit = Audit(Mutex::new(Vec::new()));
+
+        let factory = DefaultOperatorFactory::new()
+            .with_interceptor(&audit)
+            .with_interceptor(DenySchemes::new(["fs"]));
+
+        assert!(factory.load("legacy:///").is_ok());
+
+        let err = factory.load("fs:///tmp").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        assert_eq!(*audit.0.lock().unwrap(), vec!["memory:///".to_string()]);
+
+        // Interceptors see the full URI when resolving
+        let location = factory.resolve("legacy:///path/to/file.txt")?;
+        assert_eq!(location.path, "path/to/file.txt");
+        assert_eq!(location.operator.info().scheme(), "memory");
+
+        let err = factory.resolve("fs:///tmp/file.txt").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        assert_eq!(
+            *audit.0.lock().unwrap(),
+            vec![
+                "memory:///".to_string(),
+                "memory:///path/to/file.txt".to_string()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chain_operator_factory_errors() {
+        let factory = ChainOperatorFactory::builder()
+            .then(ProfileOperatorFactory::new(HashMap::new()))
+            .then(DefaultOperatorFactory::new())
+            .build();
+
+        assert!(factory.load("memory:///").is_ok());
+
+        let err = factory.load("unknown:///").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let chain_error = ChainError::from_error(&err).expect("chain error");
+        assert_eq!(chain_error.errors.len(), 2);
+        assert_eq!(chain_error.errors[0].0, 0);
+        assert_eq!(chain_error.errors[0].1.message(), "Profile not found");
+        assert_eq!(chain_error.errors[1].0, 1);
+    }
+
+    #[test]
+    fn test_mapping_operator_factory() -> Result<(), Error> {
+        let factory = MappingOperatorFactory::new(DefaultOperatorFactory::new())
+            .with_mapping("gs://", "memory:///gs/")
+            .with_mapping("gs://bucket/", "memory:///mirror/")
+            .with_mapping("legacy://", "memory://");
+
+        assert_eq!(factory.rewrite("gs://bucket/x"), "memory:///mirror/x");
+        assert_eq!(factory.rewrite("gs://other/x"), "memory:///gs/other/x");
+        assert_eq!(factory.rewrite("s3://bucket/x"), "s3://bucket/x");
+
+        let location = factory.resolve("gs://bucket/dir/file.txt")?;
+        assert_eq!(location.path, "mirror/dir/file.txt");
+
+        assert!(factory.load("legacy:///").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_retrying_operator_factory() -> Result<(), Error> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Flaky(AtomicUsize);
+
+        impl OperatorFactory for Flaky {
+            fn load(&self, uri: &str) -> Result<Operator, Error> {
+                if self.0.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(Error::new(ErrorKind::Unexpected, "Flaky").set_temporary());
+                }
+
+                DefaultOperatorFactory::new().load(uri)
+            }
+        }
+
+        let policy = RetryPolicy {
+            initial_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+
+        let factory = RetryingOperatorFactory::new(Flaky(AtomicUsize::new(0)), policy.clone());
+        assert!(factory.load("memory:///").is_err());
+
+        let factory = RetryingOperatorFactory::new(Flaky(AtomicUsize::new(0)), policy.clone())
+            .with_load_retry(policy);
+
+        let location = factory.resolve("memory:///dir/file.txt")?;
+        assert_eq!(location.path, "dir/file.txt");
+        assert_eq!(factory.inner.0.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_profile_operator_factory_validate() {
+        let profiles = HashMap::from([
+            (
+                "data".to_string(),
+                HashMap::from([("type".to_string(), "memory".to_string())]),
+            ),
+            (
+                "missing-type".to_string(),
+                HashMap::from([("root".to_string(), "/".to_string())]),
+            ),
+            (
+                "unknown".to_string(),
+                HashMap::from([("type".to_string(), "unknown".to_string())]),
+            ),
+        ]);
+
+        let factory = ProfileOperatorFactory::new(profiles).with_alias("default", "data");
+
+        let report = factory.validate(true).await;
+        assert!(!report.is_valid());
+        assert_eq!(report.valid, vec!["data", "default"]);
+        assert_eq!(
+            report.invalid.keys().collect::<Vec<_>>(),
+            vec!["missing-type", "unknown"]
+        );
+
+        assert!(factory.check("data:///").await.is_ok());
+        assert!(factory.check("unknown:///").await.is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_factory_config() -> Result<(), Error> {
+        let config: FactoryConfig = serde_json::from_value(serde_json::json!({
+            "profiles": {
+                "base": { "type": "memory", "root": "/base" },
+                "data": { "extends": "base", "root": "/data" },
+            },
+            "aliases": { "default": "data" },
+        }))
+        .map_err(|err| Error::new(ErrorKind::ConfigInvalid, "Invalid config").set_source(err))?;
+
+        let factory = ProfileOperatorFactory::from_config(config.clone());
+        assert_eq!(factory.load("default:///")?.info().root(), "/data/");
+
+        let factory = config.build();
+        assert_eq!(factory.load("base:///")?.info().root(), "/base/");
+        assert_eq!(factory.load("memory:///other")?.info().root(), "/other/");
+
+        let factory = FactoryConfig {
+            default_scheme: Some("memory".to_string()),
+            ..Default::default()
+        }
+        .build();
+        assert_eq!(factory.load("/bare")?.info().root(), "/bare/");
+
+        let location = factory.resolve("/some/path")?;
+        assert_eq!(location.operator.info().scheme(), "memory");
+        assert_eq!(location.path, "some/path");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_factory_config_keys() -> Result<(), Error> {
+        let config: FactoryConfig = serde_json::from_value(serde_json::json!({
+            "default_scheme": "memory",
+            "layers": { "timeout": 30, "io_timeout": 0.5, "concurrency_limit": 4 },
+        }))
+        .map_err(|err| Error::new(ErrorKind::ConfigInvalid, "Invalid config").set_source(err))?;
+
+        assert_eq!(config.default_scheme.as_deref(), Some("memory"));
+        assert_eq!(config.layers.timeout, Some(Duration::from_secs(30)));
+        assert_eq!(config.layers.io_timeout, Some(Duration::from_millis(500)));
+        assert_eq!(config.layers.concurrency_limit, Some(4));
+
+        let value = serde_json::to_value(&config.layers).unwrap();
+        assert_eq!(value["timeout"], serde_json::json!(30.0));
+        assert_eq!(value["retry"], serde_json::Value::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_scheme_factory() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default().root("/srv"))?.finish();
+
+        let factory = DefaultSchemeFactory::new(DefaultOperatorFactory::new(), operator);
+
+        let location = factory.resolve("/srv/data/file.txt")?;
+        assert_eq!(location.operator.info().root(), "/srv/");
+        assert_eq!(location.path, "data/file.txt");
+
+        assert_eq!(factory.resolve("/srv/data/")?.path, "data/");
+        assert_eq!(factory.resolve("/srv")?.path, "");
+
+        assert!(factory.resolve("/srvx/file.txt").is_err());
+        assert!(factory.resolve("/other/file.txt").is_err());
+
+        let location = factory.resolve("memory:///dir/file.txt")?;
+        assert_eq!(location.operator.info().root(), "/");
+        assert_eq!(location.path, "dir/file.txt");
+
+        Ok(())
+    }
+
+    struct StaticSecretResolver;
+
+    impl SecretResolver for StaticSecretResolver {
+        fn resolve(&self, provider: &str, key: &str) -> Result<String, Error> {
+            match (provider, key) {
+                ("vault", "root") => Ok("/data".to_string()),
+                _ => DefaultSecretResolver::new().resolve(provider, key),
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_placeholders() {
+        let resolver = StaticSecretResolver;
+
+        assert_eq!(resolve_placeholders("plain", &resolver).unwrap(), "plain");
+        assert_eq!(
+            resolve_placeholders("${vault:root}/sub/${vault:root}", &resolver).unwrap(),
+            "/data/sub//data"
+        );
+
+        let path = std::env::temp_dir().join(format!("opendal-util-secret-{}", std::process::id()));
+        std::fs::write(&path, "token\n").unwrap();
+        assert_eq!(
+            resolve_placeholders(&format!("${{file:{}}}", path.display()), &resolver).unwrap(),
+            "token"
+        );
+        std::fs::remove_file(path).unwrap();
+
+        assert!(resolve_placeholders("${vault:root", &resolver).is_err());
+        assert!(resolve_placeholders("${novalue}", &resolver).is_err());
+        assert!(resolve_placeholders("${unknown:key}", &resolver).is_err());
+    }
+
+    #[test]
+    fn test_profile_operator_factory_inheritance() {
+        let profile = |options: &[(&str, &str)]| {
+            options
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let profiles = HashMap::from([
+            (
+                "s3".to_string(),
+                profile(&[("type", "s3"), ("region", "us-east-1"), ("bucket", "dev")]),
+            ),
+            (
+                "s3-prod".to_string(),
+                profile(&[("extends", "s3"), ("bucket", "prod")]),
+            ),
+            (
+                "s3-prod-eu".to_string(),
+                profile(&[("extends", "s3-prod"), ("region", "eu-west-1")]),
+            ),
+            ("a".to_string(), profile(&[("extends", "b")])),
+            ("b".to_string(), profile(&[("extends", "a")])),
+        ]);
+
+        let factory = ProfileOperatorFactory::new(profiles).with_alias("prod", "s3-prod-eu");
+
+        assert_eq!(
+            factory.profile("prod").unwrap(),
+            profile(&[("type", "s3"), ("region", "eu-west-1"), ("bucket", "prod")])
+        );
+
+        let err = factory.profile("a").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = factory.profile("missing").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    fn parse_profiles(content: &str) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+        // One profile per line: name root
+        content
+            .lines()
+            .map(|line| {
+                let (name, root) = line
+                    .split_once(' ')
+                    .ok_or_else(|| Error::new(ErrorKind::ConfigInvalid, "Invalid profile"))?;
+
+                let profile = HashMap::from([
+                    ("type".to_string(), "memory".to_string()),
+                    ("root".to_string(), root.to_string()),
+                ]);
+
+                Ok((name.to_string(), profile))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_profile_operator_factory_watch_file() -> Result<(), Error> {
+        let path = std::env::temp_dir().join(format!(
+            "opendal-util-profiles-{}-{}",
+            std::process::id(),
+            fastrand::u32(..)
+        ));
+        std::fs::write(&path, "data /v1").unwrap();
+
+        let (factory, mut watcher) =
+            ProfileOperatorFactory::watch_file(&path, Duration::from_millis(10), parse_profiles)?;
+
+        assert_eq!(factory.load("data:///")?.info().root(), "/v1/");
+
+        // Make sure the modification time changes
+        std::fs::write(&path, "data /v2\nother /other").unwrap();
+        let modified = SystemTime::now() + Duration::from_secs(1);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(modified))
+            .unwrap();
+
+        let mut root = String::new();
+        for _ in 0..100 {
+            root = factory.load("data:///")?.info().root().to_string();
+
+            if root == "/v2/" {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(root, "/v2/");
+
+        // Invalid files are ignored
+        std::fs::write(&path, "invalid").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(modified + Duration::from_secs(1)))
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(factory.load("other:///").is_ok());
+
+        watcher.stop().await;
+        std::fs::remove_file(path).unwrap();
+
+        factory.reload(HashMap::new());
+        assert!(factory.load("data:///").is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_profile_operator_factory_secrets() -> Result<(), Error> {
+        let profiles = HashMap::from([(
+            "data".to_string(),
+            HashMap::from([
+                ("type".to_string(), "memory".to_string()),
+                ("root".to_string(), "${vault:root}".to_string()),
+            ]),
+        )]);
+
+        let factory =
+            ProfileOperatorFactory::new(profiles).with_secret_resolver(StaticSecretResolver);
+
+        let op = factory.load("data:///")?;
+        assert_eq!(op.info().root(), "/data/");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_layered_operator_factory() -> Result<(), Error> {
+        let config = LayerConfig {
+            retry: Some(RetryPolicy::default()),
+            timeout: Some(Duration::from_secs(10)),
+            concurrency_limit: Some(4),
+            ..Default::default()
+        };
+
+        let factory = LayeredOperatorFactory::new(DefaultOperatorFactory::new(), config);
+
+        let op = factory.load("memory:///")?;
+
+        op.write("file.txt", "foo").await?;
+        assert_eq!(op.read("file.txt").await?.to_vec(), b"foo");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,352 @@
+use bytes::Bytes;
+use futures::stream::{BoxStream, Stream};
+use futures::{StreamExt, TryStreamExt};
+use opendal::{Buffer, Error, ErrorKind, Metadata, Operator};
+
+use crate::copy::open_writer;
+
+/// Reads files from replicas stored in several operators (e.g. unreliable mirrors).
+///
+/// Every file is read from the first source that has it. When reading fails with a temporary error
+/// in the middle of a file, reading resumes from the same offset on the next source (with a ranged read),
+/// so bytes already read are not transferred again.
+///
+/// Replicas are expected to be identical: sources whose copy of a file has a different size are skipped.
+///
+/// ```no_run
+/// # async fn example(mirrors: Vec<opendal::Operator>, destination: opendal::Operator) -> Result<(), opendal::Error> {
+/// use opendal_util::FailoverReader;
+///
+/// let reader = FailoverReader::new(mirrors);
+///
+/// reader.copy("releases/v1.tar.gz", &destination, "releases/v1.tar.gz").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FailoverReader {
+    sources: Vec<Operator>,
+}
+
+impl FailoverReader {
+    /// Create a reader trying the sources in order.
+    pub fn new(sources: impl IntoIterator<Item = Operator>) -> Self {
+        Self {
+            sources: sources.into_iter().collect(),
+        }
+    }
+
+    pub fn sources(&self) -> &[Operator] {
+        &self.sources
+    }
+
+    /// Read a whole file.
+    pub async fn read(&self, path: &str) -> Result<Buffer, Error> {
+        let chunks: Vec<Bytes> = self.stream(path).await?.try_collect().await?;
+
+        Ok(chunks.into_iter().collect())
+    }
+
+    /// Stream the content of a file, failing over to the next source on temporary errors.
+    ///
+    /// Fails if no source has the file; the returned stream fails once every source failed.
+    pub async fn stream(
+        &self,
+        path: &str,
+    ) -> Result<BoxStream<'static, Result<Bytes, Error>>, Error> {
+        let mut state = State {
+            sources: self.sources.clone(),
+            path: path.to_string(),
+            next: 0,
+            offset: 0,
+            size: None,
+            stream: None,
+            last_error: None,
+        };
+
+        // Open the first replica eagerly, so that missing files fail early
+        state.open().await?;
+
+        Ok(futures::stream::try_unfold(state, |mut state| async move {
+            let chunk = state.next_chunk().await?;
+
+            Ok(chunk.map(|chunk| (chunk, state)))
+        })
+        .boxed())
+    }
+
+    /// Copy a file to a destination, returning the number of bytes copied.
+    ///
+    /// The destination file is written with the metadata of the first source that has the file.
+    pub async fn copy(
+        &self,
+        path: &str,
+        destination: &Operator,
+        destination_path: &str,
+    ) -> Result<u64, Error> {
+        let meta = self.stat(path).await?;
+        let mut stream = self.stream(path).await?;
+
+        let mut writer = open_writer(destination, destination_path, &meta).await?;
+        let mut bytes = 0;
+
+        loop {
+            let chunk = match stream.try_next().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(err) => {
+                    let _ = writer.abort().await;
+
+                    return Err(err);
+                }
+            };
+
+            bytes += chunk.len() as u64;
+
+            writer.write(chunk).await?;
+        }
+
+        writer.close().await?;
+
+        Ok(bytes)
+    }
+
+    // The metadata of the file in the first source that has it.
+    async fn stat(&self, path: &str) -> Result<Metadata, Error> {
+        let mut last_error = None;
+
+        for source in &self.sources {
+            match source.stat(path).await {
+                Ok(meta) => return Ok(meta),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(exhausted(path, last_error))
+    }
+}
+
+struct State {
+    sources: Vec<Operator>,
+    path: String,
+
+    // Index of the next source to try
+    next: usize,
+    offset: u64,
+
+    // Size of the file, as reported by the first source that has it
+    size: Option<u64>,
+    stream: Option<BoxStream<'static, Result<Bytes, Error>>>,
+    last_error: Option<Error>,
+}
+
+impl State {
+    // Open the file at the current offset on the next source that has an identical replica.
+    async fn open(&mut self) -> Result<(), Error> {
+        while let Some(source) = self.sources.get(self.next).cloned() {
+            self.next += 1;
+
+            match self.open_source(&source).await {
+                Ok(stream) => {
+                    self.stream = Some(stream.boxed());
+
+                    return Ok(());
+                }
+                Err(err) => self.last_error = Some(err),
+            }
+        }
+
+        Err(exhausted(&self.path, self.last_error.take()))
+    }
+
+    async fn open_source(
+        &mut self,
+        source: &Operator,
+    ) -> Result<impl Stream<Item = Result<Bytes, Error>> + use<>, Error> {
+        let size = source.stat(&self.path).await?.content_length();
+
+        match self.size {
+            Some(expected) if expected != size => {
+                return Err(Error::new(ErrorKind::Unexpected, "Replica size differs")
+                    .with_context("expected", expected)
+                    .with_context("actual", size));
+            }
+            _ => self.size = Some(size),
+        }
+
+        let stream = source
+            .reader(&self.path)
+            .await?
+            .into_stream(self.offset..)
+            .await?;
+
+        Ok(stream.map_ok(|buffer| buffer.to_bytes()))
+    }
+
+    async fn next_chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        loop {
+            let Some(stream) = &mut self.stream else {
+                return Ok(None);
+            };
+
+            match stream.try_next().await {
+                Ok(Some(chunk)) => {
+                    self.offset += chunk.len() as u64;
+
+                    return Ok(Some(chunk));
+                }
+                Ok(None) => {
+                    self.stream = None;
+
+                    return Ok(None);
+                }
+                Err(err) if err.is_temporary() => {
+                    self.last_error = Some(err);
+
+                    self.open().await?;
+                }
+                Err(err) => {
+                    self.stream = None;
+
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+fn exhausted(path: &str, last_error: Option<Error>) -> Error {
+    match last_error {
+        Some(err) => err.with_context("failover", "every source failed"),
+        None => {
+            Error::new(ErrorKind::NotFound, "No source to read from").with_context("path", path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::raw::{
+        Access, Layer, LayeredAccess, OpList, OpRead, OpWrite, RpDelete, RpList, RpRead, RpWrite,
+        oio,
+    };
+    use opendal::services::Memory;
+
+    use super::*;
+
+    // Fails every read with a temporary error after returning the first `limit` bytes.
+    #[derive(Debug, Clone, Copy)]
+    struct FlakyLayer {
+        limit: usize,
+    }
+
+    #[derive(Debug)]
+    struct FlakyAccessor<A> {
+        inner: A,
+        limit: usize,
+    }
+
+    struct FlakyReader<R> {
+        inner: R,
+        remaining: usize,
+    }
+
+    impl<A: Access> Layer<A> for FlakyLayer {
+        type LayeredAccess = FlakyAccessor<A>;
+
+        fn layer(&self, inner: A) -> Self::LayeredAccess {
+            FlakyAccessor {
+                inner,
+                limit: self.limit,
+            }
+        }
+    }
+
+    impl<A: Access> LayeredAccess for FlakyAccessor<A> {
+        type Inner = A;
+        type Reader = FlakyReader<A::Reader>;
+        type Writer = A::Writer;
+        type Lister = A::Lister;
+        type Deleter = A::Deleter;
+        type Copier = A::Copier;
+
+        fn inner(&self) -> &Self::Inner {
+            &self.inner
+        }
+
+        async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+            let (rp, inner) = self.inner.read(path, args).await?;
+
+            Ok((
+                rp,
+                FlakyReader {
+                    inner,
+                    remaining: self.limit,
+                },
+            ))
+        }
+
+        async fn write(
+            &self,
+            path: &str,
+            args: OpWrite,
+        ) -> opendal::Result<(RpWrite, Self::Writer)> {
+            self.inner.write(path, args).await
+        }
+
+        async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+            self.inner.delete().await
+        }
+
+        async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+            self.inner.list(path, args).await
+        }
+    }
+
+    impl<R: oio::Read> oio::Read for FlakyReader<R> {
+        async fn read(&mut self) -> opendal::Result<Buffer> {
+            if self.remaining == 0 {
+                return Err(Error::new(ErrorKind::Unexpected, "Connection reset").set_temporary());
+            }
+
+            let buffer = self.inner.read().await?;
+            let len = buffer.len().min(self.remaining);
+
+            self.remaining -= len;
+
+            Ok(buffer.slice(0..len))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_reader() -> Result<(), Error> {
+        let missing = Operator::new(Memory::default())?.finish();
+        let flaky = Operator::new(Memory::default())?
+            .layer(FlakyLayer { limit: 4 })
+            .finish();
+        let stable = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        flaky.write("file.txt", "0123456789").await?;
+        stable.write("file.txt", "0123456789").await?;
+
+        let reader = FailoverReader::new([missing.clone(), flaky.clone(), stable.clone()]);
+
+        assert_eq!(reader.read("file.txt").await?.to_vec(), b"0123456789");
+
+        let bytes = reader.copy("file.txt", &destination, "copy.txt").await?;
+        assert_eq!(bytes, 10);
+        assert_eq!(destination.read("copy.txt").await?.to_vec(), b"0123456789");
+
+        let err = FailoverReader::new([missing, flaky])
+            .read("file.txt")
+            .await
+            .unwrap_err();
+        assert!(err.is_temporary());
+
+        let err = reader.read("other.txt").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+}
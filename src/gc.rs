@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use futures::TryStreamExt;
+use opendal::{Error, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+/// Options for [`gc`].
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GcOptions {
+    /// Report the orphans without deleting them.
+    pub dry_run: bool,
+
+    /// Minimum age of the orphans to delete.
+    ///
+    /// Protects objects written after the manifest was captured (e.g. by a copy in progress).
+    /// Objects without a modification time are only deleted when the grace period is zero.
+    pub grace_period: Duration,
+}
+
+/// Outcome of a [`gc`] call.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct GcReport {
+    /// Orphans deleted (or that would be deleted in a dry run).
+    pub deleted: Vec<String>,
+
+    /// Orphans kept because they are younger than the grace period.
+    pub kept: Vec<String>,
+}
+
+/// Delete the files under `prefix` that are not referenced by a manifest.
+///
+/// Paths in the manifest are relative to the prefix (like the entries of a manifest captured from the prefix).
+///
+/// ```no_run
+/// # async fn example(operator: opendal::Operator) -> Result<(), opendal::Error> {
+/// use std::time::Duration;
+///
+/// use opendal_util::{GcOptions, gc};
+///
+/// let manifest = vec!["index.html".to_string(), "assets/app.js".to_string()];
+///
+/// let options = GcOptions {
+///     grace_period: Duration::from_secs(24 * 60 * 60),
+///     ..Default::default()
+/// };
+///
+/// gc(&operator, "site/", manifest, options).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn gc(
+    operator: &Operator,
+    prefix: &str,
+    manifest: impl IntoIterator<Item = String>,
+    options: GcOptions,
+) -> Result<GcReport, Error> {
+    let prefix = match prefix.trim_matches('/') {
+        "" => String::new(),
+        prefix => format!("{}/", prefix),
+    };
+
+    let referenced: HashSet<String> = manifest
+        .into_iter()
+        .map(|path| path.trim_start_matches('/').to_string())
+        .collect();
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, &prefix, Some(list_options)).await?;
+
+    let now = SystemTime::now();
+    let mut report = GcReport::default();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(&prefix).unwrap_or(entry.path());
+
+        if referenced.contains(relative) {
+            continue;
+        }
+
+        let expired = if options.grace_period.is_zero() {
+            true
+        } else {
+            let mut last_modified = entry.metadata().last_modified();
+
+            // Listing does not necessarily return the modification time
+            if last_modified.is_none() {
+                last_modified = operator.stat(entry.path()).await?.last_modified();
+            }
+
+            last_modified.is_some_and(|last_modified| {
+                now.duration_since(SystemTime::from(last_modified))
+                    .unwrap_or_default()
+                    >= options.grace_period
+            })
+        };
+
+        if expired {
+            report.deleted.push(entry.path().to_string());
+        } else {
+            report.kept.push(entry.path().to_string());
+        }
+    }
+
+    if !options.dry_run && !report.deleted.is_empty() {
+        operator.delete_iter(report.deleted.clone()).await?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gc() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data/a.txt", "a").await?;
+        operator.write("data/sub/b.txt", "b").await?;
+        operator.write("data/orphan.txt", "c").await?;
+        operator.write("other.txt", "d").await?;
+
+        let manifest = vec!["a.txt".to_string(), "sub/b.txt".to_string()];
+
+        let options = GcOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = gc(&operator, "data", manifest.clone(), options).await?;
+        assert_eq!(report.deleted, vec!["data/orphan.txt"]);
+        assert!(operator.exists("data/orphan.txt").await?);
+
+        // The memory service has no modification times, so orphans are kept during the grace period
+        let options = GcOptions {
+            dry_run: false,
+            grace_period: Duration::from_secs(60),
+        };
+
+        let report = gc(&operator, "data/", manifest.clone(), options).await?;
+        assert!(report.deleted.is_empty());
+        assert_eq!(report.kept, vec!["data/orphan.txt"]);
+
+        let report = gc(&operator, "data/", manifest, GcOptions::default()).await?;
+        assert_eq!(report.deleted, vec!["data/orphan.txt"]);
+        assert!(!operator.exists("data/orphan.txt").await?);
+        assert!(operator.exists("data/sub/b.txt").await?);
+        assert!(operator.exists("other.txt").await?);
+
+        Ok(())
+    }
+}
@@ -1,3 +1,84 @@
+use futures::{StreamExt, TryStreamExt, future, stream::BoxStream};
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+/// Options for [`expand`].
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ExpandOptions {
+    /// Include directories matching the pattern (only files are returned by default).
+    pub include_dirs: bool,
+
+    /// Stop after this many matches.
+    pub limit: Option<usize>,
+}
+
+/// The paths matching a glob pattern, without reading or copying anything.
+///
+/// Only the literal prefix of the pattern is listed (e.g. `logs/2024/` for `logs/2024/*/app-*.log`).
+/// A pattern without glob characters matches the path itself, if it exists.
+///
+/// ```no_run
+/// # async fn example(operator: opendal::Operator) -> Result<(), opendal::Error> {
+/// use opendal_util::glob::{ExpandOptions, expand};
+///
+/// let logs = expand(&operator, "logs/2024/*/app-*.log", ExpandOptions::default()).await?;
+/// operator.delete_iter(logs).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn expand(
+    operator: &Operator,
+    pattern: &str,
+    options: ExpandOptions,
+) -> Result<Vec<String>, Error> {
+    expand_stream(operator, pattern, options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Stream the paths matching a glob pattern (see [`expand`]).
+pub async fn expand_stream(
+    operator: &Operator,
+    pattern: &str,
+    options: ExpandOptions,
+) -> Result<BoxStream<'static, Result<String, Error>>, Error> {
+    let limit = options.limit.unwrap_or(usize::MAX);
+
+    if !has_glob_chars(pattern) {
+        let path = match operator.stat(pattern).await {
+            Ok(meta) if meta.is_file() || options.include_dirs => Some(pattern.to_string()),
+            Ok(_) => None,
+            Err(err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+
+        return Ok(futures::stream::iter(path.map(Ok)).take(limit).boxed());
+    }
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    Ok(list::lister(operator, pattern, Some(list_options))
+        .await?
+        .try_filter(move |entry| future::ready(entry.metadata().is_file() || options.include_dirs))
+        .map_ok(|entry| entry.path().to_string())
+        .take(limit)
+        .boxed())
+}
+
 pub(crate) fn extract_glob_prefix(pattern: &str) -> Option<String> {
     let mut parts = Vec::new();
     let mut found_glob = false;
@@ -24,6 +105,8 @@ pub(crate) fn has_glob_chars(s: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use opendal::services::Memory;
+
     use super::*;
 
     #[test]
@@ -41,4 +124,36 @@ mod tests {
             assert_eq!(result, expected, "Failed for input: {}", input);
         }
     }
+
+    #[tokio::test]
+    async fn test_expand() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("logs/2024/01/app-1.log", "").await?;
+        operator.write("logs/2024/02/app-2.log", "").await?;
+        operator.write("logs/2024/02/other.log", "").await?;
+        operator.write("logs/2023/01/app-0.log", "").await?;
+
+        let options = ExpandOptions::default();
+
+        let paths = expand(&operator, "logs/2024/*/app-*.log", options).await?;
+        assert_eq!(
+            paths,
+            vec!["logs/2024/01/app-1.log", "logs/2024/02/app-2.log"]
+        );
+
+        let limited = ExpandOptions {
+            limit: Some(1),
+            ..Default::default()
+        };
+
+        let paths = expand(&operator, "logs/**/*.log", limited).await?;
+        assert_eq!(paths.len(), 1);
+
+        let paths = expand(&operator, "logs/2023/01/app-0.log", options).await?;
+        assert_eq!(paths, vec!["logs/2023/01/app-0.log"]);
+        assert!(expand(&operator, "missing.log", options).await?.is_empty());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,295 @@
+use futures::TryStreamExt;
+use opendal::{Error, Operator, options::ListOptions};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+#[cfg(feature = "csv")]
+use crate::{CsvOptions, CsvWriter};
+use crate::{JsonlWriter, list};
+
+// Default number of records per inventory file.
+const DEFAULT_RECORDS_PER_FILE: usize = 1_000_000;
+
+/// File format of an inventory.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum InventoryFormat {
+    #[cfg(feature = "csv")]
+    Csv,
+
+    #[default]
+    Jsonl,
+}
+
+impl InventoryFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "csv")]
+            InventoryFormat::Csv => "csv",
+            InventoryFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Options for controlling how an inventory is written.
+#[derive(Debug, Copy, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct InventoryOptions {
+    pub format: InventoryFormat,
+
+    /// Maximum number of records written to a single file (defaults to 1 000 000).
+    pub records_per_file: usize,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        Self {
+            format: InventoryFormat::default(),
+            records_per_file: DEFAULT_RECORDS_PER_FILE,
+        }
+    }
+}
+
+/// A single object recorded in an inventory.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InventoryRecord {
+    pub path: String,
+
+    /// Size of the object in bytes.
+    pub size: u64,
+
+    pub etag: Option<String>,
+
+    /// Last modification time in RFC 3339 format.
+    pub last_modified: Option<String>,
+}
+
+/// Result of [`inventory`].
+#[derive(Debug, Clone, Default)]
+pub struct InventoryReport {
+    /// Paths of the inventory files written to the destination, in order.
+    pub files: Vec<String>,
+
+    /// Total number of records.
+    pub records: u64,
+}
+
+/// Write an inventory of every file under `prefix` to a destination directory.
+pub async fn inventory(
+    operator: &Operator,
+    prefix: &str,
+    destination: (Operator, String),
+    format: InventoryFormat,
+) -> Result<InventoryReport, Error> {
+    let options = InventoryOptions {
+        format,
+        ..Default::default()
+    };
+
+    inventory_options(operator, prefix, destination, options).await
+}
+
+/// Write an inventory of every file under `prefix` to a destination directory.
+///
+/// Objects are streamed from the listing into the inventory,
+/// which is split into numbered files (`inventory-00000.jsonl`, `inventory-00001.jsonl`, ...)
+/// of at most [`InventoryOptions::records_per_file`] records each.
+pub async fn inventory_options(
+    operator: &Operator,
+    prefix: &str,
+    destination: (Operator, String),
+    options: InventoryOptions,
+) -> Result<InventoryReport, Error> {
+    let (destination, destination_prefix) = destination;
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, prefix, Some(list_options)).await?;
+
+    let mut report = InventoryReport::default();
+    let mut writer: Option<RecordWriter> = None;
+    let mut records_in_file = 0;
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let mut meta = entry.metadata().clone();
+
+        // Some services don't return the size or modification time when listing
+        if meta.content_length() == 0 || meta.last_modified().is_none() {
+            meta = operator.stat(entry.path()).await?;
+        }
+
+        let record = InventoryRecord {
+            path: entry.path().to_string(),
+            size: meta.content_length(),
+            etag: meta.etag().map(String::from),
+            last_modified: meta.last_modified().map(|ts| ts.to_string()),
+        };
+
+        if records_in_file >= options.records_per_file.max(1)
+            && let Some(writer) = writer.take()
+        {
+            writer.close().await?;
+        }
+
+        let current = match &mut writer {
+            Some(writer) => writer,
+            None => {
+                let path = format!(
+                    "{}/inventory-{:05}.{}",
+                    destination_prefix.trim_end_matches('/'),
+                    report.files.len(),
+                    options.format.extension()
+                );
+                let path = path.trim_start_matches('/').to_string();
+
+                records_in_file = 0;
+                report.files.push(path.clone());
+
+                writer.insert(RecordWriter::new(&destination, &path, options.format).await?)
+            }
+        };
+
+        current.write(&record).await?;
+
+        records_in_file += 1;
+        report.records += 1;
+    }
+
+    if let Some(writer) = writer {
+        writer.close().await?;
+    }
+
+    Ok(report)
+}
+
+enum RecordWriter {
+    #[cfg(feature = "csv")]
+    Csv(Box<CsvWriter>),
+    Jsonl(JsonlWriter),
+}
+
+impl RecordWriter {
+    async fn new(operator: &Operator, path: &str, format: InventoryFormat) -> Result<Self, Error> {
+        match format {
+            #[cfg(feature = "csv")]
+            InventoryFormat::Csv => Ok(Self::Csv(Box::new(
+                CsvWriter::new(operator, path, CsvOptions::default()).await?,
+            ))),
+            InventoryFormat::Jsonl => Ok(Self::Jsonl(JsonlWriter::new(operator, path).await?)),
+        }
+    }
+
+    async fn write(&mut self, record: &InventoryRecord) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "csv")]
+            Self::Csv(writer) => writer.write(record).await,
+            Self::Jsonl(writer) => writer.write(record).await,
+        }
+    }
+
+    async fn close(self) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "csv")]
+            Self::Csv(writer) => writer.close().await,
+            Self::Jsonl(writer) => writer.close().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+    use crate::read_jsonl;
+
+    #[tokio::test]
+    async fn test_inventory() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        operator.write("bucket/a.txt", "a").await?;
+        operator.write("bucket/b.txt", "bb").await?;
+        operator.write("bucket/sub/c.txt", "ccc").await?;
+
+        let options = InventoryOptions {
+            records_per_file: 2,
+            ..Default::default()
+        };
+
+        let report = inventory_options(
+            &operator,
+            "bucket/",
+            (destination.clone(), "reports/".to_string()),
+            options,
+        )
+        .await?;
+
+        assert_eq!(report.records, 3);
+        assert_eq!(
+            report.files,
+            vec![
+                "reports/inventory-00000.jsonl",
+                "reports/inventory-00001.jsonl"
+            ]
+        );
+
+        let mut records: Vec<InventoryRecord> = Vec::new();
+        for file in &report.files {
+            let mut chunk: Vec<InventoryRecord> =
+                read_jsonl(&destination, file).await?.try_collect().await?;
+
+            records.append(&mut chunk);
+        }
+
+        let sizes: Vec<_> = records.iter().map(|r| (r.path.as_str(), r.size)).collect();
+        assert_eq!(
+            sizes,
+            vec![
+                ("bucket/a.txt", 1),
+                ("bucket/b.txt", 2),
+                ("bucket/sub/c.txt", 3)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "csv")]
+    #[tokio::test]
+    async fn test_inventory_csv() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        operator.write("a.txt", "a").await?;
+
+        let report = inventory(
+            &operator,
+            "",
+            (destination.clone(), "".to_string()),
+            InventoryFormat::Csv,
+        )
+        .await?;
+
+        assert_eq!(report.files, vec!["inventory-00000.csv"]);
+
+        let content = destination.read("inventory-00000.csv").await?;
+        assert_eq!(
+            content.to_vec(),
+            b"path,size,etag,last_modified\na.txt,1,,\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,896 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use futures::stream::FuturesUnordered;
+use opendal::{Error, ErrorKind, Operator};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "cron")]
+use std::{sync::Arc, time::SystemTime};
+#[cfg(feature = "cron")]
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::copy::normalize_dir;
+use crate::{Copier, CopyOptions, Digest, HashAlgorithm, LocationType, RetryPolicy, checksum};
+#[cfg(feature = "cron")]
+use crate::{MissedRunPolicy, Schedule, mirror::Ticker};
+
+/// Options for controlling sync behavior.
+#[derive(Debug, Copy, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SyncOptions {
+    /// Whether to delete files from the destination that don't exist in the source.
+    #[serde(default)]
+    pub delete_extraneous: bool,
+}
+
+/// A unit of data movement that can be stored (in config files, databases, workflow state)
+/// and executed later.
+///
+/// Locations are generic over [`LocationType`], so specs can reference either plain paths or URLs.
+///
+/// Specs are (de)serialized as internally tagged objects:
+///
+/// ```json
+/// { "kind": "copy", "source": "s3://bucket/data/", "destination": "fs:///backup/" }
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase", bound = "")]
+#[cfg_attr(feature = "restate", serde(rename_all_fields = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum JobSpec<L: LocationType = String> {
+    /// Copy files from the source to the destination.
+    Copy {
+        source: L,
+        destination: L,
+
+        #[serde(default)]
+        options: CopyOptions,
+    },
+
+    /// Make the destination tree match the source tree.
+    Sync {
+        source: L,
+        destination: L,
+
+        #[serde(default)]
+        options: SyncOptions,
+    },
+
+    /// Delete a file or a directory.
+    Delete {
+        target: L,
+
+        /// Whether to delete the contents of a directory recursively.
+        #[serde(default)]
+        recursive: bool,
+    },
+
+    /// Move files from the source to the destination (e.g. to colder storage).
+    Archive {
+        source: L,
+        destination: L,
+
+        #[serde(default)]
+        options: CopyOptions,
+    },
+
+    /// Compute the checksum of a file.
+    Checksum {
+        target: L,
+
+        #[serde(default)]
+        algorithm: HashAlgorithm,
+    },
+}
+
+impl<L: LocationType> JobSpec<L> {
+    /// Name of the job kind, as used in the serialized form.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobSpec::Copy { .. } => "copy",
+            JobSpec::Sync { .. } => "sync",
+            JobSpec::Delete { .. } => "delete",
+            JobSpec::Archive { .. } => "archive",
+            JobSpec::Checksum { .. } => "checksum",
+        }
+    }
+}
+
+/// A [`JobSpec`] with an identifier, dependencies and a retry policy, executed by a [`JobRunner`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(bound = "")]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Job<L: LocationType = String> {
+    /// Unique identifier of the job within a run.
+    pub id: String,
+
+    pub spec: JobSpec<L>,
+
+    /// Identifiers of the jobs that must succeed before this job starts.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+
+    /// Retry the whole job according to a policy.
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
+
+    /// Cron expression (with seconds, e.g. `0 0 3 * * *`) for running the job with a [`JobScheduler`].
+    #[cfg(feature = "cron")]
+    #[serde(default)]
+    pub schedule: Option<String>,
+}
+
+impl<L: LocationType> Job<L> {
+    pub fn new(id: impl Into<String>, spec: JobSpec<L>) -> Self {
+        Self {
+            id: id.into(),
+            spec,
+            depends_on: Vec::new(),
+            retry_policy: None,
+            #[cfg(feature = "cron")]
+            schedule: None,
+        }
+    }
+
+    pub fn depends_on(mut self, id: impl Into<String>) -> Self {
+        self.depends_on.push(id.into());
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    #[cfg(feature = "cron")]
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> Self {
+        self.schedule = Some(expression.into());
+        self
+    }
+}
+
+/// Outcome of a single job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    Succeeded,
+    Failed(String),
+
+    /// The job did not run because one of its dependencies did not succeed.
+    Skipped,
+}
+
+/// Result of a single job in a [`RunReport`].
+#[derive(Debug, Clone)]
+pub struct JobResult {
+    pub status: JobStatus,
+    pub duration: Duration,
+
+    /// Digest computed by checksum jobs.
+    pub digest: Option<Digest>,
+}
+
+/// Result of [`JobRunner::run`].
+#[derive(Debug, Clone, Default)]
+pub struct RunReport {
+    /// Results keyed by job identifier.
+    pub jobs: BTreeMap<String, JobResult>,
+
+    pub duration: Duration,
+}
+
+impl RunReport {
+    pub fn is_success(&self) -> bool {
+        self.jobs
+            .values()
+            .all(|result| result.status == JobStatus::Succeeded)
+    }
+
+    /// Identifiers of the jobs that failed or were skipped.
+    pub fn failed(&self) -> Vec<&str> {
+        self.jobs
+            .iter()
+            .filter(|(_, result)| result.status != JobStatus::Succeeded)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+}
+
+type Resolver<L> = Box<dyn Fn(&L) -> Result<(Operator, String), Error> + Send + Sync>;
+
+/// Executes a set of [`Job`]s.
+///
+/// Jobs start as soon as all of their dependencies succeeded, with at most
+/// [`JobRunner::with_concurrency`] jobs running at the same time.
+/// Jobs depending on a failed job are skipped; independent jobs keep running.
+///
+/// Locations are turned into an operator and a path by a resolver function.
+pub struct JobRunner<L: LocationType = String> {
+    resolver: Resolver<L>,
+    concurrency: usize,
+}
+
+impl<L: LocationType> JobRunner<L> {
+    pub fn new(
+        resolver: impl Fn(&L) -> Result<(Operator, String), Error> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            resolver: Box::new(resolver),
+            concurrency: 4,
+        }
+    }
+
+    /// Maximum number of jobs running at the same time (defaults to 4).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Run a set of jobs.
+    ///
+    /// Fails without running anything if the job graph is invalid
+    /// (duplicate identifiers, unknown dependencies or cycles).
+    /// Job failures are reported in the [`RunReport`].
+    pub async fn run(&self, jobs: Vec<Job<L>>) -> Result<RunReport, Error> {
+        validate(&jobs)?;
+
+        let start = Instant::now();
+
+        let mut report = RunReport::default();
+        let mut pending: Vec<&Job<L>> = jobs.iter().collect();
+        let mut running = FuturesUnordered::new();
+
+        loop {
+            // Skipping a job may cause jobs depending on it to be skipped as well
+            loop {
+                let mut changed = false;
+
+                pending.retain(|job| {
+                    let blocked = job.depends_on.iter().any(|dependency| {
+                        report
+                            .jobs
+                            .get(dependency)
+                            .is_some_and(|result| result.status != JobStatus::Succeeded)
+                    });
+
+                    if blocked {
+                        report.jobs.insert(
+                            job.id.clone(),
+                            JobResult {
+                                status: JobStatus::Skipped,
+                                duration: Duration::ZERO,
+                                digest: None,
+                            },
+                        );
+                        changed = true;
+                    }
+
+                    !blocked
+                });
+
+                if !changed {
+                    break;
+                }
+            }
+
+            pending.retain(|job| {
+                let ready = running.len() < self.concurrency
+                    && job
+                        .depends_on
+                        .iter()
+                        .all(|dependency| report.jobs.contains_key(dependency));
+
+                if ready {
+                    running.push(self.run_job(job));
+                }
+
+                !ready
+            });
+
+            let Some((id, result)) = running.next().await else {
+                break;
+            };
+
+            report.jobs.insert(id, result);
+        }
+
+        report.duration = start.elapsed();
+
+        Ok(report)
+    }
+
+    async fn run_job(&self, job: &Job<L>) -> (String, JobResult) {
+        let start = Instant::now();
+
+        let result = match &job.retry_policy {
+            Some(retry_policy) => retry_policy.retry(|| self.execute(&job.spec)).await,
+            None => self.execute(&job.spec).await,
+        };
+
+        let (status, digest) = match result {
+            Ok(digest) => (JobStatus::Succeeded, digest),
+            Err(err) => (JobStatus::Failed(err.to_string()), None),
+        };
+
+        let result = JobResult {
+            status,
+            duration: start.elapsed(),
+            digest,
+        };
+
+        (job.id.clone(), result)
+    }
+
+    async fn execute(&self, spec: &JobSpec<L>) -> Result<Option<Digest>, Error> {
+        match spec {
+            JobSpec::Copy {
+                source,
+                destination,
+                options,
+            } => {
+                let (source, source_path) = (self.resolver)(source)?;
+                let (destination, destination_path) = (self.resolver)(destination)?;
+
+                Copier::new(source, destination)
+                    .copy_options(source_path, destination_path, *options)
+                    .await?;
+            }
+            JobSpec::Sync {
+                source,
+                destination,
+                options,
+            } => {
+                let (source, source_path) = (self.resolver)(source)?;
+                let (destination, destination_path) = (self.resolver)(destination)?;
+
+                let copy_options = CopyOptions {
+                    recursive: true,
+                    disable_glob: true,
+                    ..Default::default()
+                };
+
+                Copier::new(source, destination)
+                    .sync(
+                        &normalize_dir(&source_path),
+                        &normalize_dir(&destination_path),
+                        copy_options,
+                        options.delete_extraneous,
+                    )
+                    .await?;
+            }
+            JobSpec::Delete { target, recursive } => {
+                let (operator, path) = (self.resolver)(target)?;
+
+                operator.delete_with(&path).recursive(*recursive).await?;
+            }
+            JobSpec::Archive {
+                source,
+                destination,
+                options,
+            } => {
+                let (source, source_path) = (self.resolver)(source)?;
+                let (destination, destination_path) = (self.resolver)(destination)?;
+
+                Copier::new(source.clone(), destination)
+                    .copy_options(source_path.as_str(), destination_path, *options)
+                    .await?;
+
+                source
+                    .delete_with(&source_path)
+                    .recursive(options.recursive)
+                    .await?;
+            }
+            JobSpec::Checksum { target, algorithm } => {
+                let (operator, path) = (self.resolver)(target)?;
+
+                return Ok(Some(checksum(&operator, &path, *algorithm).await?));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Runs [`Job`]s with a schedule in the background, every time their cron expression fires (in UTC).
+///
+/// Every run executes the scheduled job together with the jobs it (transitively) depends on.
+/// Runs overlapping the next scheduled run are handled according to a [`MissedRunPolicy`].
+#[cfg(feature = "cron")]
+pub struct JobScheduler<L: LocationType = String> {
+    runner: Arc<JobRunner<L>>,
+    scheduled: Arc<Vec<ScheduledJob<L>>>,
+    missed_run_policy: MissedRunPolicy,
+    reports: watch::Sender<BTreeMap<String, RunReport>>,
+    task: Option<(watch::Sender<bool>, Vec<JoinHandle<()>>)>,
+}
+
+#[cfg(feature = "cron")]
+struct ScheduledJob<L: LocationType> {
+    id: String,
+    schedule: Schedule,
+
+    // The scheduled job and its dependencies
+    jobs: Vec<Job<L>>,
+}
+
+#[cfg(feature = "cron")]
+impl<L: LocationType + Clone + Send + Sync + 'static> JobScheduler<L> {
+    /// Create a scheduler for the jobs that have a schedule.
+    ///
+    /// Fails if the job graph or a cron expression is invalid.
+    pub fn new(runner: JobRunner<L>, jobs: Vec<Job<L>>) -> Result<Self, Error> {
+        validate(&jobs)?;
+
+        let mut scheduled = Vec::new();
+
+        for job in &jobs {
+            let Some(expression) = &job.schedule else {
+                continue;
+            };
+
+            let schedule =
+                Schedule::cron(expression).map_err(|err| err.with_context("job", &job.id))?;
+
+            scheduled.push(ScheduledJob {
+                id: job.id.clone(),
+                schedule,
+                jobs: with_dependencies(&jobs, &job.id),
+            });
+        }
+
+        Ok(Self {
+            runner: Arc::new(runner),
+            scheduled: Arc::new(scheduled),
+            missed_run_policy: MissedRunPolicy::default(),
+            reports: watch::Sender::new(BTreeMap::new()),
+            task: None,
+        })
+    }
+
+    /// Set how runs overlapping the next scheduled run are handled (takes effect on the next start).
+    pub fn with_missed_run_policy(mut self, missed_run_policy: MissedRunPolicy) -> Self {
+        self.missed_run_policy = missed_run_policy;
+        self
+    }
+
+    /// The next time each scheduled job runs, keyed by job identifier.
+    pub fn next_runs(&self) -> BTreeMap<String, SystemTime> {
+        let now = SystemTime::now();
+
+        self.scheduled
+            .iter()
+            .filter_map(|job| Some((job.id.clone(), job.schedule.next_after(now)?)))
+            .collect()
+    }
+
+    /// Start running the scheduled jobs in the background.
+    ///
+    /// Calling `start` on a scheduler that is already running has no effect.
+    pub fn start(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        let tasks = (0..self.scheduled.len())
+            .map(|index| {
+                tokio::spawn(run_scheduled(
+                    self.runner.clone(),
+                    self.scheduled.clone(),
+                    index,
+                    self.missed_run_policy,
+                    self.reports.clone(),
+                    shutdown_rx.clone(),
+                ))
+            })
+            .collect();
+
+        self.task = Some((shutdown, tasks));
+    }
+
+    /// Stop the scheduler.
+    ///
+    /// Runs that are already in progress are allowed to finish before this method returns.
+    pub async fn stop(&mut self) {
+        if let Some((shutdown, tasks)) = self.task.take() {
+            let _ = shutdown.send(true);
+
+            for task in tasks {
+                let _ = task.await;
+            }
+        }
+    }
+
+    /// The report of the most recent run of each scheduled job, keyed by job identifier.
+    pub fn reports(&self) -> BTreeMap<String, RunReport> {
+        self.reports.borrow().clone()
+    }
+
+    /// Subscribe to run reports (e.g. to observe every completed run).
+    pub fn subscribe(&self) -> watch::Receiver<BTreeMap<String, RunReport>> {
+        self.reports.subscribe()
+    }
+}
+
+#[cfg(feature = "cron")]
+impl<L: LocationType> Drop for JobScheduler<L> {
+    fn drop(&mut self) {
+        if let Some((shutdown, _)) = self.task.take() {
+            let _ = shutdown.send(true);
+        }
+    }
+}
+
+#[cfg(feature = "cron")]
+async fn run_scheduled<L: LocationType + Clone + Send + Sync + 'static>(
+    runner: Arc<JobRunner<L>>,
+    scheduled: Arc<Vec<ScheduledJob<L>>>,
+    index: usize,
+    missed_run_policy: MissedRunPolicy,
+    reports: watch::Sender<BTreeMap<String, RunReport>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let job = &scheduled[index];
+
+    let mut ticker = Ticker::new(&job.schedule, missed_run_policy);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.changed() => break,
+        }
+
+        // The graph was validated when the scheduler was created
+        if let Ok(report) = runner.run(job.jobs.clone()).await {
+            reports.send_modify(|reports| {
+                reports.insert(job.id.clone(), report);
+            });
+        }
+    }
+}
+
+// A job and the jobs it transitively depends on, in their original order.
+#[cfg(feature = "cron")]
+fn with_dependencies<L: LocationType + Clone>(jobs: &[Job<L>], id: &str) -> Vec<Job<L>> {
+    let mut included = HashSet::from([id]);
+    let mut queue = vec![id];
+
+    while let Some(id) = queue.pop() {
+        let Some(job) = jobs.iter().find(|job| job.id == id) else {
+            continue;
+        };
+
+        for dependency in &job.depends_on {
+            if included.insert(dependency.as_str()) {
+                queue.push(dependency.as_str());
+            }
+        }
+    }
+
+    jobs.iter()
+        .filter(|job| included.contains(job.id.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn validate<L: LocationType>(jobs: &[Job<L>]) -> Result<(), Error> {
+    let mut ids = HashSet::new();
+
+    for job in jobs {
+        if !ids.insert(job.id.as_str()) {
+            return Err(Error::new(ErrorKind::ConfigInvalid, "Duplicate job id")
+                .with_context("job", &job.id));
+        }
+    }
+
+    for job in jobs {
+        if let Some(dependency) = job
+            .depends_on
+            .iter()
+            .find(|dependency| !ids.contains(dependency.as_str()))
+        {
+            return Err(
+                Error::new(ErrorKind::ConfigInvalid, "Unknown job dependency")
+                    .with_context("job", &job.id)
+                    .with_context("dependency", dependency),
+            );
+        }
+    }
+
+    // Kahn's algorithm: every job must be reachable by repeatedly removing jobs without dependencies
+    let mut remaining: HashMap<&str, usize> = jobs
+        .iter()
+        .map(|job| (job.id.as_str(), job.depends_on.len()))
+        .collect();
+    let mut ready: Vec<&str> = remaining
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| *id)
+        .collect();
+
+    while let Some(id) = ready.pop() {
+        remaining.remove(id);
+
+        for job in jobs {
+            for _ in job.depends_on.iter().filter(|dependency| *dependency == id) {
+                if let Some(count) = remaining.get_mut(job.id.as_str()) {
+                    *count -= 1;
+
+                    if *count == 0 {
+                        ready.push(job.id.as_str());
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(id) = remaining.keys().min() {
+        return Err(
+            Error::new(ErrorKind::ConfigInvalid, "Job dependency cycle").with_context("job", *id)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn test_job_spec_serde() {
+        let specs: Vec<JobSpec> = serde_json::from_str(
+            r#"[
+                { "kind": "copy", "source": "data/", "destination": "backup/" },
+                { "kind": "delete", "target": "tmp/", "recursive": true },
+                { "kind": "checksum", "target": "data/file.txt" }
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            &specs[0],
+            JobSpec::Copy { source, options, .. } if source == "data/" && !options.recursive
+        ));
+        assert_eq!(specs[1].kind(), "delete");
+        assert!(matches!(
+            specs[2],
+            JobSpec::Checksum {
+                algorithm: HashAlgorithm::Sha256,
+                ..
+            }
+        ));
+
+        let spec = JobSpec::Sync {
+            source: Url::parse("s3://bucket/data/").unwrap(),
+            destination: Url::parse("fs:///backup/").unwrap(),
+            options: SyncOptions::default(),
+        };
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["kind"], "sync");
+        assert_eq!(json["source"], "s3://bucket/data/");
+
+        let spec: JobSpec<Url> = serde_json::from_value(json).unwrap();
+        assert_eq!(spec.kind(), "sync");
+    }
+
+    fn runner(operator: Operator) -> JobRunner {
+        JobRunner::new(move |path: &String| Ok((operator.clone(), path.clone())))
+    }
+
+    #[tokio::test]
+    async fn test_job_runner() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default())?.finish();
+
+        operator.write("data/a.txt", "foo").await?;
+        operator.write("data/sub/b.txt", "bar").await?;
+        operator.write("mirror/stale.txt", "stale").await?;
+
+        let jobs = vec![
+            Job::new(
+                "checksum",
+                JobSpec::Checksum {
+                    target: "backup/a.txt".to_string(),
+                    algorithm: HashAlgorithm::Md5,
+                },
+            )
+            .depends_on("backup"),
+            Job::new(
+                "backup",
+                JobSpec::Copy {
+                    source: "data/".to_string(),
+                    destination: "backup/".to_string(),
+                    options: CopyOptions {
+                        recursive: true,
+                        ..Default::default()
+                    },
+                },
+            ),
+            Job::new(
+                "sync",
+                JobSpec::Sync {
+                    source: "data".to_string(),
+                    destination: "mirror".to_string(),
+                    options: SyncOptions {
+                        delete_extraneous: true,
+                    },
+                },
+            ),
+            Job::new(
+                "archive",
+                JobSpec::Archive {
+                    source: "data/sub/".to_string(),
+                    destination: "cold/".to_string(),
+                    options: CopyOptions {
+                        recursive: true,
+                        ..Default::default()
+                    },
+                },
+            )
+            .depends_on("backup")
+            .depends_on("sync"),
+        ];
+
+        let report = runner(operator.clone())
+            .with_concurrency(2)
+            .run(jobs)
+            .await?;
+
+        assert!(report.is_success(), "{:?}", report);
+        assert_eq!(
+            report.jobs["checksum"].digest.as_ref().unwrap().to_string(),
+            "md5:acbd18db4cc2f85cedef654fccc4a4d8"
+        );
+
+        assert_eq!(operator.read("backup/sub/b.txt").await?.to_vec(), b"bar");
+        assert_eq!(operator.read("mirror/sub/b.txt").await?.to_vec(), b"bar");
+        assert!(!operator.exists("mirror/stale.txt").await?);
+        assert_eq!(operator.read("cold/b.txt").await?.to_vec(), b"bar");
+        assert!(!operator.exists("data/sub/b.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_job_runner_skips_dependents_of_failed_jobs() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default())?.finish();
+
+        let jobs = vec![
+            Job::new(
+                "copy",
+                JobSpec::Copy {
+                    source: "missing.txt".to_string(),
+                    destination: "copy.txt".to_string(),
+                    options: CopyOptions::default(),
+                },
+            ),
+            Job::new(
+                "checksum",
+                JobSpec::Checksum {
+                    target: "copy.txt".to_string(),
+                    algorithm: HashAlgorithm::Sha256,
+                },
+            )
+            .depends_on("copy"),
+            Job::new(
+                "delete",
+                JobSpec::Delete {
+                    target: "copy.txt".to_string(),
+                    recursive: false,
+                },
+            )
+            .depends_on("checksum"),
+        ];
+
+        let report = runner(operator).run(jobs).await?;
+
+        assert!(matches!(report.jobs["copy"].status, JobStatus::Failed(_)));
+        assert_eq!(report.jobs["checksum"].status, JobStatus::Skipped);
+        assert_eq!(report.jobs["delete"].status, JobStatus::Skipped);
+        assert_eq!(report.failed(), vec!["checksum", "copy", "delete"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_job_runner_invalid_graph() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default())?.finish();
+
+        let delete = |id: &str| {
+            Job::new(
+                id,
+                JobSpec::Delete {
+                    target: "file.txt".to_string(),
+                    recursive: false,
+                },
+            )
+        };
+
+        let cycle = vec![delete("a").depends_on("b"), delete("b").depends_on("a")];
+        let err = runner(operator.clone()).run(cycle).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let unknown = vec![delete("a").depends_on("missing")];
+        assert!(runner(operator.clone()).run(unknown).await.is_err());
+
+        let duplicate = vec![delete("a"), delete("a")];
+        assert!(runner(operator).run(duplicate).await.is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cron")]
+    #[tokio::test]
+    async fn test_job_scheduler() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default())?.finish();
+
+        operator.write("data/file.txt", "foo").await?;
+
+        let jobs = vec![
+            Job::new(
+                "backup",
+                JobSpec::Copy {
+                    source: "data/file.txt".to_string(),
+                    destination: "backup/file.txt".to_string(),
+                    options: CopyOptions::default(),
+                },
+            ),
+            Job::new(
+                "checksum",
+                JobSpec::Checksum {
+                    target: "backup/file.txt".to_string(),
+                    algorithm: HashAlgorithm::Md5,
+                },
+            )
+            .depends_on("backup")
+            .with_schedule("* * * * * *"),
+        ];
+
+        let mut scheduler = JobScheduler::new(runner(operator.clone()), jobs)?
+            .with_missed_run_policy(MissedRunPolicy::Skip);
+
+        assert_eq!(
+            scheduler.next_runs().keys().collect::<Vec<_>>(),
+            vec!["checksum"]
+        );
+
+        let mut reports = scheduler.subscribe();
+
+        scheduler.start();
+
+        reports
+            .wait_for(|reports| reports.contains_key("checksum"))
+            .await
+            .expect("scheduler should report runs");
+
+        scheduler.stop().await;
+
+        // Dependencies run as part of the scheduled run
+        let report = &scheduler.reports()["checksum"];
+        assert!(report.is_success());
+        assert_eq!(report.jobs.len(), 2);
+
+        let invalid = vec![
+            Job::new(
+                "invalid",
+                JobSpec::Delete {
+                    target: "file.txt".to_string(),
+                    recursive: false,
+                },
+            )
+            .with_schedule("not a cron expression"),
+        ];
+        assert!(JobScheduler::new(runner(operator), invalid).is_err());
+
+        Ok(())
+    }
+}
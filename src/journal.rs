@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use opendal::{Error, ErrorKind, Operator};
+use tokio::sync::Mutex;
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Records the files completed by a [`Copier`](crate::Copier), so that an interrupted copy can be resumed.
+///
+/// The journal is stored as a plain text object with one completed file per line
+/// (source and destination path separated by a tab).
+///
+/// The object is rewritten every [`CopyJournal::with_flush_every`] completed files
+/// and when a copy returns (successfully or not).
+/// Files completed after the last flush are copied again on restart.
+///
+/// Journals are cheap to clone; clones share the same state.
+///
+/// ```no_run
+/// # async fn example(source: opendal::Operator, destination: opendal::Operator) -> Result<(), opendal::Error> {
+/// use opendal_util::{Copier, CopyJournal};
+///
+/// let journal = CopyJournal::open(destination.clone(), ".copy-journal").await?;
+///
+/// let copier = Copier::new(source, destination).with_journal(journal.clone());
+/// copier.copy("data/", "backup/").await?;
+///
+/// // Start from scratch next time
+/// journal.clear().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct CopyJournal {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    // Not set for in-memory journals
+    operator: Option<Operator>,
+    path: String,
+    state: Mutex<State>,
+}
+
+struct State {
+    completed: HashSet<(String, String)>,
+    flush_every: usize,
+
+    // Entries recorded since the last flush
+    pending: usize,
+}
+
+impl CopyJournal {
+    /// Open the journal stored at `path`, loading the entries of a previous copy if it exists.
+    pub async fn open(operator: Operator, path: impl Into<String>) -> Result<Self, Error> {
+        let path = path.into();
+
+        let completed = match operator.read(&path).await {
+            Ok(buffer) => parse(&String::from_utf8_lossy(&buffer.to_vec()))
+                .map_err(|err| err.with_context("path", &path))?,
+            Err(err) if err.kind() == ErrorKind::NotFound => HashSet::new(),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Self::with_state(Some(operator), path, completed))
+    }
+
+    // A journal that is never written, used to track checkpoints.
+    pub(crate) fn in_memory(checkpoint: CopyCheckpoint) -> Self {
+        let completed = checkpoint
+            .copied
+            .into_iter()
+            .map(|entry| (entry.source, entry.destination))
+            .collect();
+
+        Self::with_state(None, String::new(), completed)
+    }
+
+    fn with_state(
+        operator: Option<Operator>,
+        path: String,
+        completed: HashSet<(String, String)>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                operator,
+                path,
+                state: Mutex::new(State {
+                    completed,
+                    flush_every: 100,
+                    pending: 0,
+                }),
+            }),
+        }
+    }
+
+    /// Number of completed files recorded between two writes of the journal object (defaults to 100).
+    pub fn with_flush_every(self, flush_every: usize) -> Self {
+        self.inner
+            .state
+            .try_lock()
+            .expect("journal not in use")
+            .flush_every = flush_every.max(1);
+
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.inner.path
+    }
+
+    /// Number of completed files in the journal.
+    pub async fn len(&self) -> usize {
+        self.inner.state.lock().await.completed.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+
+    /// Whether copying `source` to `destination` has been completed.
+    pub async fn is_completed(&self, source: &str, destination: &str) -> bool {
+        self.inner
+            .state
+            .lock()
+            .await
+            .completed
+            .contains(&(source.to_string(), destination.to_string()))
+    }
+
+    /// Record that copying `source` to `destination` has been completed.
+    pub async fn record(&self, source: &str, destination: &str) -> Result<(), Error> {
+        let mut state = self.inner.state.lock().await;
+
+        if state
+            .completed
+            .insert((source.to_string(), destination.to_string()))
+        {
+            state.pending += 1;
+        }
+
+        if state.pending >= state.flush_every {
+            self.write(&mut state).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the journal object if entries were recorded since the last write.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let mut state = self.inner.state.lock().await;
+
+        if state.pending > 0 {
+            self.write(&mut state).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The completed files, sorted by source path.
+    pub(crate) async fn checkpoint(&self) -> CopyCheckpoint {
+        let state = self.inner.state.lock().await;
+
+        let mut copied: Vec<_> = state
+            .completed
+            .iter()
+            .map(|(source, destination)| CopiedEntry {
+                source: source.clone(),
+                destination: destination.clone(),
+            })
+            .collect();
+        copied.sort();
+
+        CopyCheckpoint { copied }
+    }
+
+    /// Forget every entry and delete the journal object.
+    pub async fn clear(&self) -> Result<(), Error> {
+        let mut state = self.inner.state.lock().await;
+
+        if let Some(operator) = &self.inner.operator {
+            operator.delete(&self.inner.path).await?;
+        }
+
+        state.completed.clear();
+        state.pending = 0;
+
+        Ok(())
+    }
+
+    async fn write(&self, state: &mut State) -> Result<(), Error> {
+        let Some(operator) = &self.inner.operator else {
+            state.pending = 0;
+
+            return Ok(());
+        };
+
+        let mut entries: Vec<_> = state.completed.iter().collect();
+        entries.sort();
+
+        let content: String = entries
+            .into_iter()
+            .map(|(source, destination)| format!("{}\t{}\n", source, destination))
+            .collect();
+
+        operator.write(&self.inner.path, content).await?;
+
+        state.pending = 0;
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for CopyJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CopyJournal")
+            .field("path", &self.inner.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Files completed by a copy, to be persisted between invocations of a resumable copy
+/// (see [`Copier::copy_resumable`](crate::Copier::copy_resumable)).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CopyCheckpoint {
+    pub copied: Vec<CopiedEntry>,
+}
+
+/// A file copied from `source` to `destination`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct CopiedEntry {
+    pub source: String,
+    pub destination: String,
+}
+
+fn parse(content: &str) -> Result<HashSet<(String, String)>, Error> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (source, destination) = line.split_once('\t').ok_or_else(|| {
+                Error::new(ErrorKind::Unexpected, "Invalid journal entry")
+                    .with_context("line", line)
+            })?;
+
+            Ok((source.to_string(), destination.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+    use crate::{Copier, CopyOptions};
+
+    #[tokio::test]
+    async fn test_copy_journal() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "foo").await?;
+        source.write("data/b.txt", "bar").await?;
+
+        let journal = CopyJournal::open(destination.clone(), ".journal").await?;
+
+        let copier = Copier::new(source.clone(), destination.clone()).with_journal(journal);
+        copier.copy("data/", "backup/").await?;
+
+        // The journal is written when the copy returns
+        let content = destination.read(".journal").await?;
+        assert_eq!(
+            String::from_utf8_lossy(&content.to_vec()),
+            "data/a.txt\tbackup/a.txt\ndata/b.txt\tbackup/b.txt\n"
+        );
+
+        // Completed files are skipped when resuming
+        source.write("data/a.txt", "changed").await?;
+        source.write("data/c.txt", "baz").await?;
+
+        let journal = CopyJournal::open(destination.clone(), ".journal").await?;
+        assert_eq!(journal.len().await, 2);
+
+        let copier = Copier::new(source, destination.clone()).with_journal(journal.clone());
+        copier
+            .copy_options("data/", "backup/", CopyOptions::default())
+            .await?;
+
+        assert_eq!(destination.read("backup/a.txt").await?.to_vec(), b"foo");
+        assert_eq!(destination.read("backup/c.txt").await?.to_vec(), b"baz");
+        assert!(journal.is_completed("data/c.txt", "backup/c.txt").await);
+
+        journal.clear().await?;
+        assert!(journal.is_empty().await);
+        assert!(!destination.exists(".journal").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_resumable() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "foo").await?;
+        source.write("data/b.txt", "bar").await?;
+
+        let copier = Copier::new(source.clone(), destination.clone());
+
+        let checkpoint = CopyCheckpoint {
+            copied: vec![CopiedEntry {
+                source: "data/a.txt".to_string(),
+                destination: "backup/a.txt".to_string(),
+            }],
+        };
+
+        let (result, checkpoint) = copier
+            .copy_resumable(checkpoint, "data/", "backup/", CopyOptions::default())
+            .await;
+        result?;
+
+        // Files in the checkpoint are skipped
+        assert!(!destination.exists("backup/a.txt").await?);
+        assert_eq!(destination.read("backup/b.txt").await?.to_vec(), b"bar");
+
+        assert_eq!(
+            checkpoint.copied,
+            vec![
+                CopiedEntry {
+                    source: "data/a.txt".to_string(),
+                    destination: "backup/a.txt".to_string(),
+                },
+                CopiedEntry {
+                    source: "data/b.txt".to_string(),
+                    destination: "backup/b.txt".to_string(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_journal_flush_every() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let journal = CopyJournal::open(operator.clone(), "journal")
+            .await?
+            .with_flush_every(2);
+
+        journal.record("a", "b").await?;
+        assert!(!operator.exists("journal").await?);
+
+        journal.record("c", "d").await?;
+        assert!(operator.exists("journal").await?);
+
+        operator.write("invalid", "no tab here").await?;
+        assert!(CopyJournal::open(operator, "invalid").await.is_err());
+
+        Ok(())
+    }
+}
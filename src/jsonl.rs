@@ -0,0 +1,180 @@
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use opendal::{Error, ErrorKind, Operator, Writer};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::copy::IoErrorExt;
+use crate::read::split_lines;
+
+// Default amount of serialized lines buffered before they are handed to the writer.
+const DEFAULT_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Stream the JSON lines of an object, deserializing every line as `T`.
+///
+/// The object is streamed chunk by chunk and never read into memory as a whole.
+/// Empty lines are skipped.
+pub async fn read_jsonl<T>(
+    operator: &Operator,
+    path: &str,
+) -> Result<BoxStream<'static, Result<T, Error>>, Error>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let reader = operator.reader(path).await?;
+    let stream = reader
+        .into_bytes_stream(..)
+        .await?
+        .map_err(IoErrorExt::into_opendal_error);
+
+    let path = path.to_string();
+
+    Ok(split_lines(stream)
+        .try_filter(|line| futures::future::ready(!line.iter().all(u8::is_ascii_whitespace)))
+        .and_then(move |line| {
+            let result = serde_json::from_slice(&line).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "Invalid JSON line")
+                    .with_context("path", &path)
+                    .set_source(err)
+            });
+
+            futures::future::ready(result)
+        })
+        .boxed())
+}
+
+/// Writes values as JSON lines to an object.
+///
+/// Serialized lines are buffered and handed to the underlying writer once the buffer is full
+/// (see [`JsonlWriter::with_buffer_size`]) or when [`JsonlWriter::flush`] is called.
+/// The object is only complete once [`JsonlWriter::close`] returns.
+pub struct JsonlWriter {
+    writer: Writer,
+    buffer: Vec<u8>,
+    buffer_size: usize,
+}
+
+impl JsonlWriter {
+    pub async fn new(operator: &Operator, path: &str) -> Result<Self, Error> {
+        let writer = operator
+            .writer_with(path)
+            .content_type("application/jsonl")
+            .await?;
+
+        Ok(Self {
+            writer,
+            buffer: Vec::new(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+        })
+    }
+
+    /// Number of bytes buffered before they are handed to the writer (defaults to 1 MiB).
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Serialize a value and append it as a line.
+    pub async fn write<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.buffer, value).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Failed to serialize JSON line").set_source(err)
+        })?;
+        self.buffer.push(b'\n');
+
+        if self.buffer.len() >= self.buffer_size {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hand the buffered lines to the underlying writer.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let buffer = std::mem::take(&mut self.buffer);
+
+        self.writer.write(buffer).await
+    }
+
+    /// Flush the remaining lines and complete the object.
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.flush().await?;
+        self.writer.close().await?;
+
+        Ok(())
+    }
+
+    /// Abort the write, discarding everything written so far.
+    pub async fn abort(mut self) -> Result<(), Error> {
+        self.writer.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Event {
+        id: u32,
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_roundtrip() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let mut writer = JsonlWriter::new(&operator, "events.jsonl")
+            .await?
+            .with_buffer_size(16);
+
+        for id in 0..10 {
+            writer
+                .write(&Event {
+                    id,
+                    name: format!("event-{}", id),
+                })
+                .await?;
+        }
+
+        writer.close().await?;
+
+        let events: Vec<Event> = read_jsonl(&operator, "events.jsonl")
+            .await?
+            .try_collect()
+            .await?;
+
+        assert_eq!(events.len(), 10);
+        assert_eq!(
+            events[9],
+            Event {
+                id: 9,
+                name: "event-9".to_string()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_jsonl_invalid_line() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write("events.jsonl", "{\"id\":1,\"name\":\"a\"}\nnot json\n")
+            .await?;
+
+        let mut stream = read_jsonl::<Event>(&operator, "events.jsonl").await?;
+
+        assert!(stream.try_next().await.is_ok());
+        assert!(stream.try_next().await.is_err());
+
+        Ok(())
+    }
+}
@@ -1,17 +1,178 @@
-mod glob;
+mod content_type;
+mod crc32c;
+mod telemetry;
+mod template;
+
+pub mod append;
+pub use append::*;
+
+#[cfg(feature = "serde")]
+pub mod backup;
+
+pub mod cache;
+pub use cache::*;
+
+pub mod cas;
+
+pub mod checksum;
+pub use checksum::*;
 
 pub mod copy;
 pub use copy::*;
 
+#[cfg(feature = "crypto")]
+pub mod crypto;
+
+pub mod dir;
+pub use dir::*;
+
+#[cfg(feature = "serde")]
+pub mod inventory;
+#[cfg(feature = "serde")]
+pub use inventory::*;
+
+#[cfg(feature = "serde")]
+pub mod jobs;
+#[cfg(feature = "serde")]
+pub use jobs::*;
+
+pub mod journal;
+pub use journal::*;
+
+#[cfg(feature = "serde")]
+pub mod jsonl;
+#[cfg(feature = "serde")]
+pub use jsonl::*;
+
 pub mod list;
 pub use list::*;
 
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "csv")]
+pub use csv::*;
+
+#[cfg(feature = "serde")]
+pub mod document;
+#[cfg(feature = "serde")]
+pub use document::*;
+
+pub mod duplicates;
+pub use duplicates::*;
+
+pub mod failover;
+pub use failover::*;
+
+pub mod gc;
+pub use gc::*;
+
+pub mod glob;
+
 mod factory;
 pub use factory::*;
 
+pub mod local;
+pub use local::*;
+
 pub mod location;
 pub use location::*;
 
+pub mod lock;
+pub use lock::*;
+
+#[cfg(feature = "serde")]
+pub mod manifest;
+#[cfg(feature = "serde")]
+pub use manifest::*;
+
+pub mod metadata;
+
+pub mod mirror;
+pub use mirror::*;
+
+pub mod presign;
+pub use presign::*;
+
+#[cfg(feature = "progress")]
+pub mod progress;
+#[cfg(feature = "progress")]
+pub use progress::*;
+
+pub mod queue;
+pub use queue::*;
+
+pub mod quota;
+
+pub mod rate_limit;
+pub use rate_limit::*;
+
+pub mod rearrange;
+pub use rearrange::*;
+
+pub mod read;
+pub use read::*;
+
+pub mod remove;
+pub use remove::*;
+
+#[cfg(feature = "serde")]
+pub mod restore;
+#[cfg(feature = "serde")]
+pub use restore::*;
+
+pub mod retry;
+pub use retry::*;
+
+#[cfg(feature = "http")]
+pub mod serve;
+#[cfg(feature = "http")]
+pub use serve::*;
+
+#[cfg(feature = "signature")]
+pub mod signature;
+
+pub mod snapshot;
+
+pub mod staged;
+pub use staged::*;
+
+pub mod stats;
+pub use stats::*;
+
+#[cfg(feature = "serde")]
+pub mod status;
+#[cfg(feature = "serde")]
+pub use status::*;
+
+pub mod sink;
+pub use sink::*;
+
+pub mod split;
+pub use split::*;
+
+pub mod tee;
+pub use tee::*;
+
+pub mod transform;
+pub use transform::*;
+
+pub mod trash;
+
+#[cfg(feature = "testkit")]
+pub mod testkit;
+
+pub mod touch;
+pub use touch::*;
+
+pub mod verify;
+pub use verify::*;
+
+pub mod watch;
+pub use watch::*;
+
+pub mod write;
+pub use write::*;
+
 #[cfg(feature = "restate")]
 pub mod restate;
 #[cfg(feature = "restate")]
@@ -1,23 +1,52 @@
-use futures::{StreamExt, TryStreamExt, stream::BoxStream};
+use std::time::Instant;
+
+use futures::{StreamExt, TryStreamExt, future, stream::BoxStream};
 use globset::Glob;
-use opendal::{Entry, Error, ErrorKind, Operator, options::ListOptions};
+use opendal::{Entry, Error, ErrorKind, Metadata, Operator, options::ListOptions};
 
-use crate::glob;
+use crate::{glob, telemetry};
 
 pub async fn list(
     operator: &Operator,
     path: &str,
     options: Option<ListOptions>,
 ) -> Result<Vec<Entry>, Error> {
+    let start = Instant::now();
+
     let entries: Vec<Entry> = lister(operator, path, options).await?.try_collect().await?;
 
+    telemetry::record_list(start.elapsed(), entries.len());
+
     Ok(entries)
 }
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        name = "list",
+        skip_all,
+        fields(path = path, scheme = operator.info().scheme()),
+        err(Display)
+    )
+)]
 pub async fn lister(
     operator: &Operator,
     path: &str,
     options: Option<ListOptions>,
+) -> Result<BoxStream<'static, Result<Entry, Error>>, Error> {
+    let start = Instant::now();
+
+    let result = open_lister(operator, path, options).await;
+
+    telemetry::record_lister(start.elapsed(), &result);
+
+    result
+}
+
+async fn open_lister(
+    operator: &Operator,
+    path: &str,
+    options: Option<ListOptions>,
 ) -> Result<BoxStream<'static, Result<Entry, Error>>, Error> {
     if let Some(prefix) = glob::extract_glob_prefix(path) {
         // Glob pattern needs recursive listing
@@ -48,6 +77,176 @@ pub async fn lister(
     operator.lister(path).await.map(|l| l.boxed())
 }
 
+/// Returns `true` if an object exists at the path, or if a file matches the glob pattern.
+///
+/// Stops listing at the first match, so checking for markers (e.g. `incoming/*.ready`) stays cheap on large prefixes.
+pub async fn exists(operator: &Operator, path: &str) -> Result<bool, Error> {
+    if !glob::has_glob_chars(path) {
+        return operator.exists(path).await;
+    }
+
+    let mut files = files(operator, path).await?;
+
+    Ok(files.try_next().await?.is_some())
+}
+
+/// Count the files matching a glob pattern, or under a path (recursively).
+///
+/// A path pointing to a file counts as one.
+pub async fn count(operator: &Operator, path: &str) -> Result<u64, Error> {
+    let files = files(operator, path).await?;
+
+    files
+        .try_fold(0, |count, _| future::ready(Ok(count + 1)))
+        .await
+}
+
+// Files matching a glob pattern, or under a path (recursively).
+async fn files(
+    operator: &Operator,
+    path: &str,
+) -> Result<BoxStream<'static, Result<Entry, Error>>, Error> {
+    let options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    Ok(lister(operator, path, Some(options))
+        .await?
+        .try_filter(|entry| future::ready(entry.metadata().is_file()))
+        .boxed())
+}
+
+/// The `n` most recently modified files matching a glob pattern (or under a path), newest first.
+///
+/// Files are stat'ed when the listing does not return their modification time.
+/// Files without a modification time are considered the oldest.
+///
+/// ```no_run
+/// # async fn example(operator: opendal::Operator) -> Result<(), opendal::Error> {
+/// let latest = opendal_util::newest(&operator, "backups/backup-*.tar.gz", 1).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn newest(
+    operator: &Operator,
+    path: &str,
+    n: usize,
+) -> Result<Vec<(String, Metadata)>, Error> {
+    let files = modified_files(operator, path).await?;
+
+    Ok(select(files, n, true))
+}
+
+/// The `n` least recently modified files matching a glob pattern (or under a path), oldest first.
+///
+/// See [`newest`] for details.
+pub async fn oldest(
+    operator: &Operator,
+    path: &str,
+    n: usize,
+) -> Result<Vec<(String, Metadata)>, Error> {
+    let files = modified_files(operator, path).await?;
+
+    Ok(select(files, n, false))
+}
+
+// Files with their modification time (backfilled using stat).
+async fn modified_files(operator: &Operator, path: &str) -> Result<Vec<(String, Metadata)>, Error> {
+    let mut files = files(operator, path).await?;
+    let mut result = Vec::new();
+
+    while let Some(entry) = files.try_next().await? {
+        let (path, mut meta) = entry.into_parts();
+
+        if meta.last_modified().is_none() {
+            meta = operator.stat(&path).await?;
+        }
+
+        result.push((path, meta));
+    }
+
+    Ok(result)
+}
+
+fn select(mut files: Vec<(String, Metadata)>, n: usize, newest: bool) -> Vec<(String, Metadata)> {
+    files.sort_by(|(a_path, a), (b_path, b)| {
+        a.last_modified()
+            .cmp(&b.last_modified())
+            .then_with(|| a_path.cmp(b_path))
+    });
+
+    if newest {
+        files.reverse();
+    }
+
+    files.truncate(n);
+
+    files
+}
+
+/// A page of entries returned by [`list_page`].
+#[derive(Debug, Clone)]
+pub struct ListPage {
+    pub entries: Vec<Entry>,
+
+    /// Marker to pass as [`ListOptions::start_after`] to get the next page (`None` on the last page).
+    pub next: Option<String>,
+}
+
+/// List a page of entries.
+///
+/// [`ListOptions::limit`] is the size of the page and [`ListOptions::start_after`] the marker returned
+/// in [`ListPage::next`] by the previous page.
+/// Glob patterns are applied before the limit, so a page contains `limit` matching entries
+/// (unless it is the last one). A limit of zero fails with [`ErrorKind::ConfigInvalid`].
+///
+/// The marker is also supported by services that can't start listing after a path,
+/// as long as they list entries in lexicographic order.
+pub async fn list_page(
+    operator: &Operator,
+    path: &str,
+    mut options: ListOptions,
+) -> Result<ListPage, Error> {
+    let limit = options.limit.take();
+    let start_after = options.start_after.take();
+
+    if limit == Some(0) {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "Page limit must be greater than zero",
+        )
+        .with_context("path", path));
+    }
+
+    if operator.info().full_capability().list_with_start_after {
+        options.start_after = start_after.clone();
+    }
+
+    let mut lister = lister(operator, path, Some(options)).await?;
+
+    let mut entries = Vec::new();
+    let mut next = None;
+
+    while let Some(entry) = lister.try_next().await? {
+        if let Some(start_after) = &start_after
+            && entry.path() <= start_after.as_str()
+        {
+            continue;
+        }
+
+        if limit.is_some_and(|limit| entries.len() >= limit) {
+            next = entries.last().map(|entry: &Entry| entry.path().to_string());
+
+            break;
+        }
+
+        entries.push(entry);
+    }
+
+    Ok(ListPage { entries, next })
+}
+
 pub async fn glob_lister(
     operator: &Operator,
     prefix: &str,
@@ -97,6 +296,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[allow(clippy::field_reassign_with_default)]
     async fn test_list_recursive() -> Result<(), Error> {
         let builder = Memory::default();
         let operator = Operator::new(builder)?.finish();
@@ -130,4 +330,131 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_list_page() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        for name in ["a.txt", "b.log", "c.txt", "d.txt", "e.log"] {
+            operator.write(&format!("dir/{}", name), "").await?;
+        }
+
+        let mut options = ListOptions {
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let mut pages = Vec::new();
+
+        loop {
+            let page = list_page(&operator, "dir/*.txt", options.clone()).await?;
+
+            pages.push(
+                page.entries
+                    .iter()
+                    .map(|entry| entry.path().to_string())
+                    .collect::<Vec<_>>(),
+            );
+
+            match page.next {
+                Some(next) => options.start_after = Some(next),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            pages,
+            vec![vec!["dir/a.txt", "dir/c.txt"], vec!["dir/d.txt"]]
+        );
+
+        options.limit = Some(0);
+
+        let err = list_page(&operator, "dir/*.txt", options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exists_and_count() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("incoming/a.csv", "").await?;
+        operator.write("incoming/a.ready", "").await?;
+        operator.write("incoming/sub/b.ready", "").await?;
+
+        assert!(exists(&operator, "incoming/a.csv").await?);
+        assert!(exists(&operator, "incoming/").await?);
+        assert!(!exists(&operator, "missing.txt").await?);
+        assert!(exists(&operator, "incoming/**/*.ready").await?);
+        assert!(!exists(&operator, "incoming/*.done").await?);
+
+        assert_eq!(count(&operator, "incoming/**/*.ready").await?, 2);
+        assert_eq!(count(&operator, "incoming/").await?, 3);
+        assert_eq!(count(&operator, "incoming/a.csv").await?, 1);
+        assert_eq!(count(&operator, "missing/").await?, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_newest_and_oldest() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("backups/backup-1.tar.gz", "").await?;
+        operator.write("backups/backup-2.tar.gz", "").await?;
+        operator.write("backups/other.txt", "").await?;
+
+        // The memory service has no modification times, so files are ordered by path
+        let paths = |files: Vec<(String, Metadata)>| -> Vec<String> {
+            files.into_iter().map(|(path, _)| path).collect()
+        };
+
+        assert_eq!(
+            paths(newest(&operator, "backups/backup-*.tar.gz", 1).await?),
+            vec!["backups/backup-2.tar.gz"]
+        );
+        assert_eq!(
+            paths(oldest(&operator, "backups/", 5).await?),
+            vec![
+                "backups/backup-1.tar.gz",
+                "backups/backup-2.tar.gz",
+                "backups/other.txt"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_select() {
+        use opendal::EntryMode;
+        use opendal::raw::Timestamp;
+
+        let file = |path: &str, second: Option<i64>| {
+            let mut meta = Metadata::new(EntryMode::FILE);
+
+            if let Some(second) = second {
+                meta = meta.with_last_modified(Timestamp::from_second(second).unwrap());
+            }
+
+            (path.to_string(), meta)
+        };
+
+        let files = vec![
+            file("b", Some(200)),
+            file("a", Some(300)),
+            file("c", None),
+            file("d", Some(100)),
+        ];
+
+        let paths = |files: Vec<(String, Metadata)>| -> Vec<String> {
+            files.into_iter().map(|(path, _)| path).collect()
+        };
+
+        assert_eq!(paths(select(files.clone(), 2, true)), vec!["a", "b"]);
+        assert_eq!(paths(select(files, 2, false)), vec!["c", "d"]);
+    }
 }
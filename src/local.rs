@@ -0,0 +1,501 @@
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use futures::{TryFutureExt, TryStreamExt};
+use opendal::{EntryMode, Error, ErrorKind, Metadata, Operator};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use typed_path::Utf8UnixPathBuf;
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::checksum::{Digest, Hasher};
+use crate::copy::{IoErrorExt, normalize_path, open_writer};
+use crate::transform::Pipeline;
+
+// Size of the chunks read from local files before handing them to the writer.
+const CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Options for controlling uploads from the local filesystem.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct UploadOptions {
+    /// Content type to set on uploaded objects.
+    pub content_type: Option<String>,
+
+    /// Whether to upload directories recursively.
+    ///
+    /// Only used by [`upload_dir`]. When `false`, only the immediate files of the directory are uploaded.
+    pub recursive: bool,
+
+    /// Transforms applied to the content of local files before it is uploaded.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub pipeline: Option<Pipeline>,
+}
+
+/// Options for controlling downloads to the local filesystem.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct DownloadOptions {
+    /// Expected digest of the downloaded content.
+    ///
+    /// When set, the digest is computed while downloading (before the pipeline)
+    /// and the file is only moved into place if it matches.
+    pub checksum: Option<Digest>,
+
+    /// Transforms applied to the downloaded content before it is written to the local file.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub pipeline: Option<Pipeline>,
+}
+
+/// Upload a local file to a destination operator.
+///
+/// If the destination path is empty, ends with a slash or points to an existing directory,
+/// the file is uploaded into that directory under its local file name.
+pub async fn upload(
+    local_path: impl AsRef<Path>,
+    destination: (Operator, String),
+    options: UploadOptions,
+) -> Result<(), Error> {
+    let local_path = local_path.as_ref();
+    let (operator, path) = destination;
+    let path = normalize_path(&path);
+
+    let into_dir = path.as_str().is_empty() || path.as_str().ends_with('/');
+
+    let path = match operator.stat(path.as_str()).await {
+        Ok(stat) if stat.is_dir() => path.join(local_file_name(local_path)?), // Destination exists and is a directory
+        Ok(_) => path, // Destination exists and is a file (overwrite)
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let path = if into_dir {
+                path.join(local_file_name(local_path)?)
+            } else {
+                path
+            };
+
+            // Destination does not exist, ensure parent directory exists
+            if let Some(parent) = path.parent() {
+                operator.create_dir(&format!("{}/", parent)).await?;
+            }
+
+            path
+        }
+        Err(e) => return Err(e),
+    };
+
+    upload_file(local_path, &operator, path.as_str(), &options).await
+}
+
+/// Upload the contents of a local directory to a destination operator.
+///
+/// Files are uploaded under the destination path, preserving their relative paths.
+pub async fn upload_dir(
+    local_dir: impl AsRef<Path>,
+    destination: (Operator, String),
+    options: UploadOptions,
+) -> Result<(), Error> {
+    let (operator, path) = destination;
+    let destination = normalize_path(&path);
+
+    let mut pending: Vec<(PathBuf, Utf8UnixPathBuf)> =
+        vec![(local_dir.as_ref().to_path_buf(), destination)];
+
+    while let Some((dir, destination)) = pending.pop() {
+        operator
+            .create_dir(&format!("{}/", destination.as_str().trim_end_matches('/')))
+            .await?;
+
+        let mut entries = tokio::fs::read_dir(&dir)
+            .await
+            .map_err(IoErrorExt::into_opendal_error)?;
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(IoErrorExt::into_opendal_error)?
+        {
+            let local_path = entry.path();
+            let dest_path = destination.join(local_file_name(&local_path)?);
+
+            // Follows symlinks
+            let meta = tokio::fs::metadata(&local_path)
+                .await
+                .map_err(IoErrorExt::into_opendal_error)?;
+
+            if meta.is_dir() {
+                if options.recursive {
+                    pending.push((local_path, dest_path));
+                }
+
+                continue;
+            }
+
+            upload_file(&local_path, &operator, dest_path.as_str(), &options).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Upload a local file to a destination path.
+// This function expects that the destination path has been validated (points to a file).
+async fn upload_file(
+    local_path: &Path,
+    operator: &Operator,
+    path: &str,
+    options: &UploadOptions,
+) -> Result<(), Error> {
+    let mut file = tokio::fs::File::open(local_path)
+        .await
+        .map_err(IoErrorExt::into_opendal_error)?;
+
+    let size = file
+        .metadata()
+        .await
+        .map_err(IoErrorExt::into_opendal_error)?
+        .len();
+
+    let mut meta = Metadata::new(EntryMode::FILE).with_content_length(size);
+    if let Some(content_type) = &options.content_type {
+        meta.set_content_type(content_type);
+    }
+
+    let mut writer = open_writer(operator, path, &meta).await?;
+    let mut transform = options.pipeline.as_ref().map(Pipeline::build);
+
+    loop {
+        let mut chunk = Vec::with_capacity(size.min(CHUNK_SIZE) as usize);
+
+        let n = (&mut file)
+            .take(CHUNK_SIZE)
+            .read_to_end(&mut chunk)
+            .await
+            .map_err(IoErrorExt::into_opendal_error)?;
+
+        if n == 0 {
+            break;
+        }
+
+        let chunk = match &mut transform {
+            Some(transform) => transform.transform(Bytes::from(chunk))?,
+            None => Bytes::from(chunk),
+        };
+
+        if !chunk.is_empty() {
+            writer.write(chunk).await?;
+        }
+    }
+
+    if let Some(transform) = &mut transform {
+        let chunk = transform.finish()?;
+
+        if !chunk.is_empty() {
+            writer.write(chunk).await?;
+        }
+    }
+
+    writer.close().await?;
+
+    Ok(())
+}
+
+/// Download an object from a source operator to a local file.
+///
+/// The content is streamed to a temporary file next to `local_path` which is renamed into place
+/// once the download (and the optional checksum verification) succeeds,
+/// so `local_path` never contains a partially downloaded file.
+///
+/// If `local_path` is an existing directory, the object is downloaded into it under its own file name.
+pub async fn download(
+    source: (Operator, String),
+    local_path: impl AsRef<Path>,
+    options: DownloadOptions,
+) -> Result<(), Error> {
+    let (operator, path) = source;
+    let path = normalize_path(&path);
+
+    let mut local_path = local_path.as_ref().to_path_buf();
+
+    if tokio::fs::metadata(&local_path)
+        .await
+        .is_ok_and(|meta| meta.is_dir())
+    {
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::new(ErrorKind::Unexpected, "Source has no filename"))?;
+
+        local_path.push(name);
+    }
+
+    let temp_path = local_path.with_file_name(format!(
+        ".{}.{}.tmp",
+        local_file_name(&local_path)?,
+        fastrand::u32(..)
+    ));
+
+    let result = download_file(&operator, path.as_str(), &temp_path, &options).await;
+
+    let result = match result {
+        Ok(()) => tokio::fs::rename(&temp_path, &local_path)
+            .await
+            .map_err(IoErrorExt::into_opendal_error),
+        Err(err) => Err(err),
+    };
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+
+    result
+}
+
+async fn download_file(
+    operator: &Operator,
+    path: &str,
+    local_path: &Path,
+    options: &DownloadOptions,
+) -> Result<(), Error> {
+    let reader = operator.reader(path).await?;
+    let mut stream = reader.into_bytes_stream(..).await?;
+
+    let mut file = tokio::fs::File::create(local_path)
+        .await
+        .map_err(IoErrorExt::into_opendal_error)?;
+
+    let mut hasher = options
+        .checksum
+        .as_ref()
+        .map(|digest| Hasher::new(digest.algorithm));
+
+    let mut transform = options.pipeline.as_ref().map(Pipeline::build);
+
+    while let Some(chunk) = stream
+        .try_next()
+        .map_err(IoErrorExt::into_opendal_error)
+        .await?
+    {
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+
+        let chunk = match &mut transform {
+            Some(transform) => transform.transform(chunk)?,
+            None => chunk,
+        };
+
+        file.write_all(&chunk)
+            .await
+            .map_err(IoErrorExt::into_opendal_error)?;
+    }
+
+    if let Some(transform) = &mut transform {
+        file.write_all(&transform.finish()?)
+            .await
+            .map_err(IoErrorExt::into_opendal_error)?;
+    }
+
+    file.sync_all()
+        .await
+        .map_err(IoErrorExt::into_opendal_error)?;
+
+    if let (Some(hasher), Some(expected)) = (hasher, options.checksum.as_ref()) {
+        let actual = hasher.finalize();
+
+        if &actual != expected {
+            return Err(Error::new(ErrorKind::Unexpected, "Checksum mismatch")
+                .with_context("path", path)
+                .with_context("expected", expected)
+                .with_context("actual", actual));
+        }
+    }
+
+    Ok(())
+}
+
+fn local_file_name(path: &Path) -> Result<&str, Error> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "Local path has no valid file name")
+                .with_context("path", path.display())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    // Creates a fresh directory under the system temp directory for a single test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("opendal-util-{}-{}", name, std::process::id()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_upload() -> Result<(), Error> {
+        let dir = temp_dir("upload");
+        std::fs::write(dir.join("file.txt"), "foo").unwrap();
+
+        let destination = Operator::new(Memory::default())?.finish();
+
+        upload(
+            dir.join("file.txt"),
+            (destination.clone(), "path/to/".to_string()),
+            UploadOptions {
+                content_type: Some("text/plain".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let buffer = destination.read("path/to/file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        let stat = destination.stat("path/to/file.txt").await?;
+        assert_eq!(stat.content_type(), Some("text/plain"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upload_dir_recursive() -> Result<(), Error> {
+        let dir = temp_dir("upload-dir");
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("file1.txt"), "content1").unwrap();
+        std::fs::write(dir.join("nested/file2.txt"), "content2").unwrap();
+
+        let destination = Operator::new(Memory::default())?.finish();
+
+        upload_dir(
+            &dir,
+            (destination.clone(), "backup/".to_string()),
+            UploadOptions {
+                recursive: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let buffer1 = destination.read("backup/file1.txt").await?;
+        assert_eq!(buffer1.to_vec(), b"content1");
+
+        let buffer2 = destination.read("backup/nested/file2.txt").await?;
+        assert_eq!(buffer2.to_vec(), b"content2");
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download() -> Result<(), Error> {
+        let dir = temp_dir("download");
+
+        let source = Operator::new(Memory::default())?.finish();
+        source.write("path/to/file.txt", "foo").await?;
+
+        download(
+            (source, "path/to/file.txt".to_string()),
+            &dir,
+            DownloadOptions {
+                checksum: Some("md5:acbd18db4cc2f85cedef654fccc4a4d8".parse()?),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        assert_eq!(std::fs::read(dir.join("file.txt")).unwrap(), b"foo");
+
+        // Only the downloaded file should be left behind
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_checksum_mismatch_should_error() -> Result<(), Error> {
+        let dir = temp_dir("download-mismatch");
+
+        let source = Operator::new(Memory::default())?.finish();
+        source.write("file.txt", "foo").await?;
+
+        let result = download(
+            (source, "file.txt".to_string()),
+            dir.join("file.txt"),
+            DownloadOptions {
+                checksum: Some("md5:00000000000000000000000000000000".parse()?),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        // Neither the file nor the temporary file should exist
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upload_download_pipeline() -> Result<(), Error> {
+        let dir = temp_dir("pipeline");
+        std::fs::write(dir.join("file.txt"), "foo").unwrap();
+
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let uppercase =
+            Pipeline::new().with(|| |chunk: Bytes| Ok(Bytes::from(chunk.to_ascii_uppercase())));
+
+        upload(
+            dir.join("file.txt"),
+            (operator.clone(), "file.txt".to_string()),
+            UploadOptions {
+                pipeline: Some(uppercase),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        assert_eq!(operator.read("file.txt").await?.to_vec(), b"FOO");
+
+        let reverse =
+            Pipeline::new().with(|| |chunk: Bytes| Ok(chunk.iter().rev().copied().collect()));
+
+        download(
+            (operator, "file.txt".to_string()),
+            dir.join("downloaded.txt"),
+            DownloadOptions {
+                // Digest of the object, not of the local file
+                checksum: Some("md5:901890a8e9c8cf6d5a1a542b229febff".parse()?),
+                pipeline: Some(reverse),
+            },
+        )
+        .await?;
+
+        assert_eq!(std::fs::read(dir.join("downloaded.txt")).unwrap(), b"OOF");
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        Ok(())
+    }
+}
@@ -1,21 +1,53 @@
+use std::fmt;
+use std::str::FromStr;
+
+use opendal::{Buffer, Error, ErrorKind, Metadata, Operator};
+use percent_encoding::percent_decode_str;
 use url::Url;
 
+use crate::{Copier, CopyError, OperatorFactory};
+
 mod sealed {
     use url::Url;
 
+    use super::Location;
+
     pub trait Sealed {
         fn example() -> Self;
+
+        /// The location as passed to [`OperatorFactory::resolve`](crate::OperatorFactory::resolve).
+        fn as_uri(&self) -> &str;
     }
 
     impl Sealed for Url {
         fn example() -> Self {
             Url::parse("https://example.com/path/to/file.pdf").unwrap()
         }
+
+        fn as_uri(&self) -> &str {
+            self.as_str()
+        }
     }
     impl Sealed for String {
         fn example() -> Self {
             "path/to/file.pdf".to_string()
         }
+
+        fn as_uri(&self) -> &str {
+            self.as_str()
+        }
+    }
+    impl Sealed for Location {
+        fn example() -> Self {
+            Location::Uri(Url::example())
+        }
+
+        fn as_uri(&self) -> &str {
+            match self {
+                Location::Uri(url) => url.as_str(),
+                Location::Path(path) => path.as_str(),
+            }
+        }
     }
 }
 
@@ -48,3 +80,413 @@ pub trait LocationType: sealed::Sealed + SerdeSupport + SchemaSupport {}
 
 impl LocationType for Url {}
 impl LocationType for String {}
+impl LocationType for Location {}
+
+/// A location of a file or a directory: either a URI or a bare path.
+///
+/// Locations are (de)serialized as strings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// A URI with a scheme (e.g. `s3://bucket/path/to/file.txt`).
+    Uri(Url),
+
+    /// A bare path (e.g. `/data/file.txt` or `./relative`).
+    Path(String),
+}
+
+impl Location {
+    /// Parse a URI or a bare path (anything without `://`).
+    pub fn parse(location: &str) -> Result<Self, Error> {
+        if !location.contains("://") {
+            return Ok(Location::Path(location.to_string()));
+        }
+
+        let url = Url::parse(location).map_err(|err| {
+            Error::new(ErrorKind::ConfigInvalid, "Failed to parse location")
+                .with_context("location", location)
+                .set_source(err)
+        })?;
+
+        Ok(Location::Uri(url))
+    }
+
+    /// Append a path to the location.
+    pub fn join(&self, path: &str) -> Self {
+        let path = path.trim_start_matches('/');
+
+        match self {
+            Location::Uri(url) => {
+                let mut url = url.clone();
+                let joined = format!("{}/{}", url.path().trim_end_matches('/'), path);
+                url.set_path(&joined);
+
+                Location::Uri(url)
+            }
+            Location::Path(base) if base.is_empty() => Location::Path(path.to_string()),
+            Location::Path(base) => {
+                Location::Path(format!("{}/{}", base.trim_end_matches('/'), path))
+            }
+        }
+    }
+
+    /// The last segment of the path, unless the location is a directory (ends with a slash).
+    pub fn file_name(&self) -> Option<String> {
+        let path = match self {
+            Location::Uri(url) => percent_decode_str(url.path())
+                .decode_utf8_lossy()
+                .into_owned(),
+            Location::Path(path) => path.clone(),
+        };
+
+        match path.rsplit_once('/') {
+            Some((_, name)) => Some(name),
+            None => Some(path.as_str()),
+        }
+        .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+        .map(String::from)
+    }
+
+    /// Resolve the location into an operator and a path relative to it using a factory.
+    ///
+    /// Bare paths ([`Location::Path`]) need a factory handling them, like [`DefaultSchemeFactory`](crate::DefaultSchemeFactory):
+    /// other factories only resolve URIs.
+    ///
+    /// With the `http` feature, `http://` and `https://` URIs resolve to a read-only HTTP operator
+    /// (query strings are not supported).
+    pub fn resolve<F>(&self, factory: &F) -> Result<(Operator, String), Error>
+    where
+        F: OperatorFactory + ?Sized,
+    {
+        let location = factory.resolve(sealed::Sealed::as_uri(self))?;
+
+        Ok((location.operator, location.path))
+    }
+}
+
+/// A location together with the operator and path it resolves to.
+///
+/// Bare paths ([`Location::Path`]) need a factory handling them, like [`DefaultSchemeFactory`](crate::DefaultSchemeFactory).
+///
+/// ```no_run
+/// # async fn example(filesystem: opendal::Operator) -> Result<(), opendal::Error> {
+/// use opendal_util::{DefaultOperatorFactory, DefaultSchemeFactory, Location, TypedLocation};
+///
+/// // Bare paths are resolved against a filesystem operator
+/// let factory = DefaultSchemeFactory::new(DefaultOperatorFactory::new(), filesystem);
+///
+/// let source = TypedLocation::resolve(Location::parse("s3://bucket/data.csv")?, &factory)?;
+/// let destination = TypedLocation::resolve(Location::parse("/backup/")?, &factory)?;
+///
+/// let content = source.read().await?;
+/// source.copy_to(&destination).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypedLocation<T: LocationType> {
+    location: T,
+    operator: Operator,
+    path: String,
+}
+
+impl<T: LocationType> TypedLocation<T> {
+    pub fn new(location: T, operator: Operator, path: impl Into<String>) -> Self {
+        Self {
+            location,
+            operator,
+            path: path.into(),
+        }
+    }
+
+    /// Resolve a location into an operator and a path using a factory.
+    pub fn resolve<F>(location: T, factory: &F) -> Result<Self, Error>
+    where
+        F: OperatorFactory + ?Sized,
+    {
+        let resolved = factory.resolve(sealed::Sealed::as_uri(&location))?;
+
+        Ok(Self::new(location, resolved.operator, resolved.path))
+    }
+
+    pub fn location(&self) -> &T {
+        &self.location
+    }
+
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn into_parts(self) -> (T, Operator, String) {
+        (self.location, self.operator, self.path)
+    }
+
+    pub async fn read(&self) -> Result<Buffer, Error> {
+        self.operator.read(&self.path).await
+    }
+
+    pub async fn write(&self, bytes: impl Into<Buffer>) -> Result<Metadata, Error> {
+        self.operator.write(&self.path, bytes).await
+    }
+
+    pub async fn stat(&self) -> Result<Metadata, Error> {
+        self.operator.stat(&self.path).await
+    }
+
+    pub async fn exists(&self) -> Result<bool, Error> {
+        self.operator.exists(&self.path).await
+    }
+
+    pub async fn delete(&self) -> Result<(), Error> {
+        self.operator.delete(&self.path).await
+    }
+
+    /// Copy the file or directory to another location (see [`Copier::copy`]).
+    pub async fn copy_to<U: LocationType>(
+        &self,
+        destination: &TypedLocation<U>,
+    ) -> Result<(), CopyError> {
+        Copier::new(self.operator.clone(), destination.operator.clone())
+            .copy(self.path.as_str(), destination.path.as_str())
+            .await
+    }
+}
+
+impl<T: LocationType> From<TypedLocation<T>> for (Operator, String) {
+    fn from(location: TypedLocation<T>) -> Self {
+        (location.operator, location.path)
+    }
+}
+
+/// Read a whole file from a location (a URI, a bare path or a [`Location`]) resolved by a factory.
+///
+/// Bare paths need a factory handling them, like [`DefaultSchemeFactory`](crate::DefaultSchemeFactory).
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), opendal::Error> {
+/// use opendal::{Operator, services::Memory};
+/// use opendal_util::{DefaultOperatorFactory, DefaultSchemeFactory, Location, fetch, store};
+///
+/// // Bare paths are resolved against an operator, URIs are loaded by the inner factory
+/// let local = Operator::new(Memory::default())?.finish();
+/// let factory = DefaultSchemeFactory::new(DefaultOperatorFactory::new(), local);
+///
+/// store(&"/backup/data.csv".to_string(), &factory, "id,name\n").await?;
+///
+/// let content = fetch(&Location::parse("/backup/data.csv")?, &factory).await?;
+/// assert_eq!(content.to_vec(), b"id,name\n");
+///
+/// store(&Location::parse("memory:///data.csv")?, &factory, content).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn fetch<L, F>(location: &L, factory: &F) -> Result<Buffer, Error>
+where
+    L: LocationType,
+    F: OperatorFactory + ?Sized,
+{
+    let resolved = factory.resolve(sealed::Sealed::as_uri(location))?;
+
+    resolved.operator.read(&resolved.path).await
+}
+
+/// Write a whole file to a location (a URI, a bare path or a [`Location`]) resolved by a factory.
+pub async fn store<L, F>(
+    location: &L,
+    factory: &F,
+    bytes: impl Into<Buffer>,
+) -> Result<Metadata, Error>
+where
+    L: LocationType,
+    F: OperatorFactory + ?Sized,
+{
+    let resolved = factory.resolve(sealed::Sealed::as_uri(location))?;
+
+    resolved.operator.write(&resolved.path, bytes).await
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Uri(url) => url.fmt(f),
+            Location::Path(path) => path.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Location {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Location::parse(s)
+    }
+}
+
+impl From<Url> for Location {
+    fn from(url: Url) -> Self {
+        Location::Uri(url)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Location {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Location {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let location = String::deserialize(deserializer)?;
+
+        Location::parse(&location).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Location {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Location".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "A URI (e.g. s3://bucket/path) or a bare path",
+        })
+    }
+}
+
+/// An operator and a path relative to it, resolved from a URI by an [`OperatorFactory`].
+#[derive(Debug, Clone)]
+pub struct ResolvedLocation {
+    pub operator: Operator,
+    pub path: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultOperatorFactory;
+
+    #[test]
+    fn test_location_parse() {
+        let location = Location::parse("s3://bucket/dir/file%20name.txt").unwrap();
+        assert!(matches!(location, Location::Uri(_)));
+        assert_eq!(location.file_name().as_deref(), Some("file name.txt"));
+        assert_eq!(location.to_string(), "s3://bucket/dir/file%20name.txt");
+
+        let location: Location = "./data/file.txt".parse().unwrap();
+        assert_eq!(location, Location::Path("./data/file.txt".to_string()));
+        assert_eq!(location.file_name().as_deref(), Some("file.txt"));
+
+        assert_eq!(
+            Location::parse("s3://bucket/dir/").unwrap().file_name(),
+            None
+        );
+        assert!(Location::parse("bad://[").is_err());
+    }
+
+    #[test]
+    fn test_location_join() {
+        let location = Location::parse("s3://bucket/dir/").unwrap();
+        assert_eq!(
+            location.join("sub/file.txt").to_string(),
+            "s3://bucket/dir/sub/file.txt"
+        );
+
+        let location = Location::parse("s3://bucket").unwrap();
+        assert_eq!(
+            location.join("file.txt").to_string(),
+            "s3://bucket/file.txt"
+        );
+
+        let location = Location::Path("/data".to_string());
+        assert_eq!(location.join("/file.txt").to_string(), "/data/file.txt");
+    }
+
+    #[test]
+    fn test_location_resolve() -> Result<(), Error> {
+        let location = Location::parse("memory:///dir/file.txt")?;
+
+        let (operator, path) = location.resolve(&DefaultOperatorFactory::new())?;
+        assert_eq!(operator.info().root(), "/");
+        assert_eq!(path, "dir/file.txt");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_location_resolve_http() -> Result<(), Error> {
+        let location = Location::parse("https://example.com/files/data.csv")?;
+
+        let (operator, path) = location.resolve(&DefaultOperatorFactory::new())?;
+        assert_eq!(operator.info().scheme(), "http");
+        assert_eq!(path, "files/data.csv");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_typed_location() -> Result<(), Error> {
+        let factory = DefaultOperatorFactory::new();
+
+        let source = TypedLocation::resolve(Location::parse("memory:///dir/file.txt")?, &factory)?;
+        assert_eq!(source.path(), "dir/file.txt");
+
+        source.write("foo").await?;
+        assert!(source.exists().await?);
+        assert_eq!(source.read().await?.to_vec(), b"foo");
+
+        let destination = TypedLocation::new(
+            "memory:///copy.txt".to_string(),
+            source.operator().clone(),
+            "copy.txt",
+        );
+        source.copy_to(&destination).await?;
+        assert_eq!(destination.stat().await?.content_length(), 3);
+
+        destination.delete().await?;
+        assert!(!destination.exists().await?);
+
+        let (operator, path) = source.into();
+        assert!(operator.exists(&path).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_and_store() -> Result<(), Error> {
+        let operator = Operator::new(opendal::services::Memory::default())?.finish();
+        let factory = crate::DefaultSchemeFactory::new(DefaultOperatorFactory::new(), operator);
+
+        store(&"/dir/file.txt".to_string(), &factory, "foo").await?;
+
+        let location = Location::parse("/dir/file.txt")?;
+        assert_eq!(fetch(&location, &factory).await?.to_vec(), b"foo");
+
+        let err = fetch(&"/missing.txt".to_string(), &factory)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_location_serde() {
+        let location: Location = serde_json::from_str("\"s3://bucket/file.txt\"").unwrap();
+        assert_eq!(location, Location::parse("s3://bucket/file.txt").unwrap());
+        assert_eq!(
+            serde_json::to_string(&location).unwrap(),
+            "\"s3://bucket/file.txt\""
+        );
+    }
+}
@@ -0,0 +1,644 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use opendal::{Error, ErrorKind, Operator};
+use tokio::task::JoinHandle;
+
+/// Content of a lock object.
+///
+/// Lock objects are stored as plain text so that they can be inspected by hand:
+///
+/// ```text
+/// owner worker-1
+/// expires_at 1700000000000
+/// ```
+///
+/// The expiry is stored in milliseconds since the Unix epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockInfo {
+    pub owner: String,
+    pub expires_at: SystemTime,
+}
+
+impl LockInfo {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+}
+
+impl fmt::Display for LockInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expires_at = self
+            .expires_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        writeln!(f, "owner {}", self.owner)?;
+        writeln!(f, "expires_at {}", expires_at)
+    }
+}
+
+impl FromStr for LockInfo {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::new(ErrorKind::Unexpected, "Invalid lock");
+
+        let mut owner = None;
+        let mut expires_at = None;
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line.split_once(' ').ok_or_else(invalid)?;
+
+            match key {
+                "owner" => owner = Some(value.to_string()),
+                "expires_at" => {
+                    let millis: u64 = value
+                        .trim()
+                        .parse()
+                        .map_err(|err| invalid().with_context("line", line).set_source(err))?;
+
+                    expires_at = Some(UNIX_EPOCH + Duration::from_millis(millis));
+                }
+                _ => return Err(invalid().with_context("line", line)),
+            }
+        }
+
+        Ok(LockInfo {
+            owner: owner.ok_or_else(invalid)?,
+            expires_at: expires_at.ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// An advisory lock backed by a lock object.
+///
+/// A lock is acquired by creating the lock object if it does not exist
+/// (which requires a backend supporting conditional writes),
+/// so only one owner can hold it at a time. Locks expire after their TTL,
+/// allowing other owners to take over locks abandoned by crashed processes.
+///
+/// Taking over an expired lock replaces the lock object with a compare-and-swap write,
+/// which requires a backend supporting `if_match` writes and returning etags.
+/// Other backends fail with [`ErrorKind::Unsupported`] instead, and expired locks have to be released by hand.
+///
+/// Locks are advisory: they only protect against writers that also take the lock.
+#[derive(Debug, Clone)]
+pub struct Lock {
+    operator: Operator,
+    path: String,
+    owner: String,
+    ttl: Duration,
+    poll_interval: Duration,
+}
+
+impl Lock {
+    pub fn new(
+        operator: Operator,
+        path: impl Into<String>,
+        owner: impl Into<String>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            operator,
+            path: path.into(),
+            owner: owner.into(),
+            ttl,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// How often [`Lock::acquire`] checks whether the lock became available (defaults to one second).
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Wait until the lock is acquired.
+    ///
+    /// Use [`tokio::time::timeout`] to give up after a while.
+    pub async fn acquire(&self) -> Result<(), Error> {
+        while !self.try_acquire().await? {
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        Ok(())
+    }
+
+    /// Try to acquire the lock without waiting.
+    ///
+    /// Returns `false` if the lock is held by another owner.
+    /// Acquiring a lock that is already held by the same owner extends its expiry.
+    /// Taking over a lock expired by another owner fails without compare-and-swap writes (see [`Lock`]).
+    pub async fn try_acquire(&self) -> Result<bool, Error> {
+        let capability = self.operator.info().full_capability();
+
+        if !capability.write_with_if_not_exists {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Locking requires conditional writes",
+            ));
+        }
+
+        match self
+            .operator
+            .write_with(&self.path, self.new_info().to_string())
+            .if_not_exists(true)
+            .await
+        {
+            Ok(_) => return Ok(true),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::ConditionNotMatch | ErrorKind::AlreadyExists
+                ) => {}
+            Err(err) => return Err(err),
+        }
+
+        let (current, etag) = match self.read().await {
+            Ok(current) => current,
+            // Released in the meantime, try again next time
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        if current.owner != self.owner && !current.is_expired() {
+            return Ok(false);
+        }
+
+        // Take over an expired lock (or extend our own)
+        let mut write = self
+            .operator
+            .write_with(&self.path, self.new_info().to_string());
+
+        match etag {
+            Some(etag) if capability.write_with_if_match => write = write.if_match(&etag),
+            // Deleting and recreating the lock is not atomic: another contender taking over the same lock
+            // could delete ours, granting the lock twice
+            _ if current.owner != self.owner => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "Taking over an expired lock requires compare-and-swap writes",
+                )
+                .with_context("path", &self.path)
+                .with_context("owner", &current.owner));
+            }
+            // Without compare-and-swap writes nobody else takes over the lock, so the owner recreates it.
+            // A contender may still create the lock between deleting and recreating it: the lock is lost then.
+            _ => {
+                self.operator.delete(&self.path).await?;
+
+                write = write.if_not_exists(true);
+            }
+        }
+
+        match write.await {
+            Ok(_) => Ok(true),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::ConditionNotMatch | ErrorKind::AlreadyExists
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Wait until the lock is acquired and return a guard that keeps it alive.
+    ///
+    /// See [`LockGuard`] for details.
+    pub async fn acquire_guard(self) -> Result<LockGuard, Error> {
+        self.acquire().await?;
+
+        Ok(LockGuard::new(self))
+    }
+
+    /// Try to acquire the lock without waiting and return a guard that keeps it alive.
+    ///
+    /// Returns `None` if the lock is held by another owner.
+    pub async fn try_acquire_guard(self) -> Result<Option<LockGuard>, Error> {
+        if !self.try_acquire().await? {
+            return Ok(None);
+        }
+
+        Ok(Some(LockGuard::new(self)))
+    }
+
+    /// Extend the expiry of a lock held by this owner.
+    ///
+    /// Returns `false` if the lock has been taken over by another owner.
+    pub async fn renew(&self) -> Result<bool, Error> {
+        self.try_acquire().await
+    }
+
+    /// Release the lock.
+    ///
+    /// Releasing a lock that does not exist is not an error,
+    /// but releasing a lock held by another owner is.
+    ///
+    /// With compare-and-swap writes the lock object is expired in place instead of being deleted,
+    /// so a lock taken over by another owner in the meantime is left alone.
+    /// Otherwise the lock object is deleted: expired locks cannot be taken over on such backends,
+    /// but a lock released by hand and acquired by another owner after checking the owner is deleted as well.
+    pub async fn release(&self) -> Result<(), Error> {
+        let (current, etag) = match self.read().await {
+            Ok(current) => current,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        if current.owner != self.owner {
+            return Err(Error::new(
+                ErrorKind::ConditionNotMatch,
+                "Lock is held by another owner",
+            )
+            .with_context("path", &self.path)
+            .with_context("owner", &current.owner));
+        }
+
+        let Some(etag) =
+            etag.filter(|_| self.operator.info().full_capability().write_with_if_match)
+        else {
+            return self.operator.delete(&self.path).await;
+        };
+
+        let released = LockInfo {
+            owner: self.owner.clone(),
+            expires_at: UNIX_EPOCH,
+        };
+
+        match self
+            .operator
+            .write_with(&self.path, released.to_string())
+            .if_match(&etag)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::ConditionNotMatch => Err(Error::new(
+                ErrorKind::ConditionNotMatch,
+                "Lock is held by another owner",
+            )
+            .with_context("path", &self.path)
+            .set_source(err)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Read the current content of the lock object.
+    pub async fn current(&self) -> Result<LockInfo, Error> {
+        self.read().await.map(|(info, _)| info)
+    }
+
+    async fn read(&self) -> Result<(LockInfo, Option<String>), Error> {
+        let meta = self.operator.stat(&self.path).await?;
+        let buffer = self.operator.read(&self.path).await?;
+
+        let content = String::from_utf8(buffer.to_vec()).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Invalid lock")
+                .with_context("path", &self.path)
+                .set_source(err)
+        })?;
+
+        let info = content
+            .parse::<LockInfo>()
+            .map_err(|err| err.with_context("path", &self.path))?;
+
+        Ok((info, meta.etag().map(String::from)))
+    }
+
+    fn new_info(&self) -> LockInfo {
+        LockInfo {
+            owner: self.owner.clone(),
+            expires_at: SystemTime::now() + self.ttl,
+        }
+    }
+}
+
+/// Holds a [`Lock`] for as long as it is alive.
+///
+/// A background task renews the lock every third of its TTL,
+/// so long-running jobs don't lose the lock halfway through.
+/// The lock is released when the guard is dropped (in the background)
+/// or explicitly with [`LockGuard::release`].
+#[derive(Debug)]
+pub struct LockGuard {
+    lock: Lock,
+    lost: Arc<AtomicBool>,
+    heartbeat: Option<JoinHandle<()>>,
+}
+
+impl LockGuard {
+    fn new(lock: Lock) -> Self {
+        let lost = Arc::new(AtomicBool::new(false));
+        let heartbeat = tokio::spawn(heartbeat(lock.clone(), lost.clone()));
+
+        Self {
+            lock,
+            lost,
+            heartbeat: Some(heartbeat),
+        }
+    }
+
+    pub fn lock(&self) -> &Lock {
+        &self.lock
+    }
+
+    /// Returns `true` if renewing the lock failed and another owner may have taken it over.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Stop renewing and release the lock.
+    pub async fn release(mut self) -> Result<(), Error> {
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.abort();
+        }
+
+        self.lock.release().await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let Some(heartbeat) = self.heartbeat.take() else {
+            return; // Already released
+        };
+
+        heartbeat.abort();
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let lock = self.lock.clone();
+
+            handle.spawn(async move {
+                let _ = lock.release().await;
+            });
+        }
+    }
+}
+
+async fn heartbeat(lock: Lock, lost: Arc<AtomicBool>) {
+    let mut interval = tokio::time::interval((lock.ttl / 3).max(Duration::from_millis(1)));
+
+    // The lock has just been acquired
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        match lock.renew().await {
+            Ok(true) => (),
+            Ok(false) => {
+                lost.store(true, Ordering::SeqCst);
+
+                return;
+            }
+            // Transient failures are retried on the next tick while the lock is still valid
+            Err(_) => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use opendal::raw::{
+        Access, Layer, LayeredAccess, OpList, OpRead, OpStat, OpWrite, RpDelete, RpList, RpRead,
+        RpStat, RpWrite,
+    };
+    use opendal::services::Memory;
+
+    use super::*;
+
+    // Adds etags and compare-and-swap writes to the memory service by counting writes per path.
+    #[derive(Debug, Clone, Default)]
+    struct CasLayer {
+        versions: Arc<Mutex<HashMap<String, u64>>>,
+    }
+
+    #[derive(Debug)]
+    struct CasAccessor<A> {
+        inner: A,
+        versions: Arc<Mutex<HashMap<String, u64>>>,
+    }
+
+    impl<A: Access> Layer<A> for CasLayer {
+        type LayeredAccess = CasAccessor<A>;
+
+        fn layer(&self, inner: A) -> Self::LayeredAccess {
+            inner.info().update_full_capability(|mut capability| {
+                capability.write_with_if_match = true;
+                capability
+            });
+
+            CasAccessor {
+                inner,
+                versions: self.versions.clone(),
+            }
+        }
+    }
+
+    impl<A: Access> LayeredAccess for CasAccessor<A> {
+        type Inner = A;
+        type Reader = A::Reader;
+        type Writer = A::Writer;
+        type Lister = A::Lister;
+        type Deleter = A::Deleter;
+        type Copier = A::Copier;
+
+        fn inner(&self) -> &Self::Inner {
+            &self.inner
+        }
+
+        async fn stat(&self, path: &str, args: OpStat) -> opendal::Result<RpStat> {
+            let meta = self.inner.stat(path, args).await?.into_metadata();
+            let version = self.versions.lock().unwrap().get(path).copied();
+
+            Ok(RpStat::new(
+                meta.with_etag(format!("\"{}\"", version.unwrap_or_default())),
+            ))
+        }
+
+        async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+            self.inner.read(path, args).await
+        }
+
+        async fn write(
+            &self,
+            path: &str,
+            args: OpWrite,
+        ) -> opendal::Result<(RpWrite, Self::Writer)> {
+            {
+                let mut versions = self.versions.lock().unwrap();
+                let version = versions.entry(path.to_string()).or_default();
+
+                if args
+                    .if_match()
+                    .is_some_and(|etag| etag != format!("\"{}\"", version))
+                {
+                    return Err(Error::new(ErrorKind::ConditionNotMatch, "Etag mismatch"));
+                }
+
+                *version += 1;
+            }
+
+            self.inner.write(path, args).await
+        }
+
+        async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+            self.inner.delete().await
+        }
+
+        async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+            self.inner.list(path, args).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lock() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let lock1 = Lock::new(
+            operator.clone(),
+            "locks/job",
+            "one",
+            Duration::from_secs(60),
+        );
+        let lock2 = Lock::new(
+            operator.clone(),
+            "locks/job",
+            "two",
+            Duration::from_secs(60),
+        );
+
+        assert!(lock1.try_acquire().await?);
+        assert!(!lock2.try_acquire().await?);
+
+        // Re-acquiring our own lock succeeds
+        assert!(lock1.try_acquire().await?);
+
+        assert_eq!(lock1.current().await?.owner, "one");
+
+        // Releasing someone else's lock is an error
+        assert_eq!(
+            lock2.release().await.unwrap_err().kind(),
+            ErrorKind::ConditionNotMatch
+        );
+
+        lock1.release().await?;
+        assert!(lock2.try_acquire().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_expired() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let lock1 = Lock::new(operator.clone(), "lock", "one", Duration::ZERO);
+        let lock2 = Lock::new(operator.clone(), "lock", "two", Duration::from_secs(60));
+
+        assert!(lock1.try_acquire().await?);
+
+        // The first lock expired immediately, but the memory service has no compare-and-swap writes
+        assert_eq!(
+            lock2.try_acquire().await.unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+        assert_eq!(lock2.current().await?.owner, "one");
+
+        lock1.release().await?;
+        assert!(lock2.try_acquire().await?);
+        assert_eq!(lock2.current().await?.owner, "two");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_compare_and_swap() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?
+            .layer(CasLayer::default())
+            .finish();
+
+        let lock1 = Lock::new(operator.clone(), "lock", "one", Duration::ZERO);
+        let lock2 = Lock::new(operator.clone(), "lock", "two", Duration::from_secs(60));
+        let lock3 = Lock::new(operator.clone(), "lock", "three", Duration::from_secs(60));
+
+        assert!(lock1.try_acquire().await?);
+
+        // The first lock expired immediately and is taken over
+        assert!(lock2.try_acquire().await?);
+        assert_eq!(lock2.current().await?.owner, "two");
+
+        // The previous owner cannot release it anymore
+        assert_eq!(
+            lock1.release().await.unwrap_err().kind(),
+            ErrorKind::ConditionNotMatch
+        );
+        assert!(!lock3.try_acquire().await?);
+
+        // Releasing expires the lock in place, so it can be taken over
+        lock2.release().await?;
+        assert!(lock2.current().await?.is_expired());
+
+        assert!(lock3.try_acquire().await?);
+        assert_eq!(lock3.current().await?.owner, "three");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lock_guard() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let lock1 = Lock::new(operator.clone(), "lock", "one", Duration::from_millis(300));
+        let lock2 = Lock::new(operator.clone(), "lock", "two", Duration::from_secs(60))
+            .with_poll_interval(Duration::from_millis(10));
+
+        let guard = lock1.acquire_guard().await?;
+
+        // The lock outlives its TTL thanks to renewals
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(!lock2.try_acquire().await?);
+        assert!(!guard.is_lost());
+
+        // Dropping the guard releases the lock
+        drop(guard);
+
+        tokio::time::timeout(Duration::from_secs(1), lock2.acquire())
+            .await
+            .expect("lock should be released")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_info_roundtrip() {
+        let info = LockInfo {
+            owner: "worker 1".to_string(),
+            expires_at: UNIX_EPOCH + Duration::from_millis(1_700_000_000_000),
+        };
+
+        assert_eq!(
+            info.to_string(),
+            "owner worker 1\nexpires_at 1700000000000\n"
+        );
+        assert_eq!(info.to_string().parse::<LockInfo>().unwrap(), info);
+        assert!("owner x".parse::<LockInfo>().is_err());
+    }
+}
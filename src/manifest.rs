@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::SystemTime;
+
+use futures::TryStreamExt;
+use opendal::{Error, ErrorKind, Metadata, Operator, options::ListOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::{Digest, HashAlgorithm, checksum, list};
+
+/// Version of the manifest format written by this crate.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Whether a [`Manifest`] describes a whole tree or only the changes since a previous snapshot.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestKind {
+    #[default]
+    Full,
+    Incremental,
+}
+
+/// A single file recorded in a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ManifestEntry {
+    /// Size of the file in bytes.
+    pub size: u64,
+
+    pub digest: Digest,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+
+    /// Last modification time in RFC 3339 format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// A snapshot of a tree: paths, sizes, hashes and metadata of every file under a root.
+///
+/// Manifests are stored as JSON and carry a format version (see [`MANIFEST_VERSION`]),
+/// so tooling can refuse manifests written by a newer version of this crate.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Manifest {
+    pub version: u32,
+
+    pub kind: ManifestKind,
+
+    /// Root the entry paths are relative to.
+    pub root: String,
+
+    pub created_at: SystemTime,
+
+    /// Files keyed by their path relative to the root.
+    pub entries: BTreeMap<String, ManifestEntry>,
+
+    /// Paths removed since the previous snapshot (incremental manifests only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+}
+
+/// Differences between two manifests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    pub added: Vec<String>,
+    pub modified: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl Manifest {
+    /// Capture a full snapshot of every file under `root`.
+    pub async fn capture(operator: &Operator, root: &str) -> Result<Self, Error> {
+        capture(operator, root, None).await
+    }
+
+    /// Capture an incremental snapshot containing only files that were added or modified
+    /// since `previous`, along with the paths that were removed.
+    ///
+    /// Files whose size and ETag match the previous snapshot are not read again.
+    pub async fn capture_incremental(
+        operator: &Operator,
+        root: &str,
+        previous: &Manifest,
+    ) -> Result<Self, Error> {
+        capture(operator, root, Some(previous)).await
+    }
+
+    /// Read a manifest written by [`Manifest::write`].
+    pub async fn read(operator: &Operator, path: &str) -> Result<Self, Error> {
+        let buffer = operator.read(path).await?;
+
+        let manifest: Manifest = serde_json::from_slice(&buffer.to_vec()).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Invalid manifest")
+                .with_context("path", path)
+                .set_source(err)
+        })?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "Unsupported manifest version")
+                    .with_context("path", path)
+                    .with_context("version", manifest.version),
+            );
+        }
+
+        Ok(manifest)
+    }
+
+    /// Write the manifest as JSON.
+    pub async fn write(&self, operator: &Operator, path: &str) -> Result<(), Error> {
+        let content = serde_json::to_vec_pretty(self).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Failed to serialize manifest").set_source(err)
+        })?;
+
+        operator
+            .write_with(path, content)
+            .content_type("application/json")
+            .await?;
+
+        Ok(())
+    }
+
+    /// Compare the manifest with a previous (full) snapshot.
+    pub fn diff(&self, previous: &Manifest) -> ManifestDiff {
+        let mut diff = ManifestDiff::default();
+
+        for (path, entry) in &self.entries {
+            match previous.entries.get(path) {
+                None => diff.added.push(path.clone()),
+                Some(prev) if prev.size != entry.size || prev.digest != entry.digest => {
+                    diff.modified.push(path.clone())
+                }
+                Some(_) => (),
+            }
+        }
+
+        if self.kind == ManifestKind::Incremental {
+            diff.removed = self.removed.clone();
+        } else {
+            diff.removed = previous
+                .entries
+                .keys()
+                .filter(|path| !self.entries.contains_key(*path))
+                .cloned()
+                .collect();
+        }
+
+        diff
+    }
+}
+
+async fn capture(
+    operator: &Operator,
+    root: &str,
+    previous: Option<&Manifest>,
+) -> Result<Manifest, Error> {
+    let root = root.trim_end_matches('/');
+    let prefix = if root.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", root)
+    };
+
+    let options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, &prefix, Some(options)).await?;
+
+    let mut entries = BTreeMap::new();
+    let mut seen = HashSet::new();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let path = entry.path().to_string();
+        let relative = path.strip_prefix(&prefix).unwrap_or(&path).to_string();
+
+        // Listing does not necessarily return every metadata field
+        let meta = operator.stat(&path).await?;
+
+        let previous_entry = previous.and_then(|previous| previous.entries.get(&relative));
+
+        seen.insert(relative.clone());
+
+        if let Some(prev) = previous_entry
+            && prev.size == meta.content_length()
+            && prev.etag.is_some()
+            && prev.etag.as_deref() == meta.etag()
+        {
+            continue;
+        }
+
+        let digest = checksum(operator, &path, HashAlgorithm::Sha256).await?;
+
+        if let Some(prev) = previous_entry
+            && prev.size == meta.content_length()
+            && prev.digest == digest
+        {
+            continue;
+        }
+
+        entries.insert(relative, new_entry(&meta, digest));
+    }
+
+    let removed = match previous {
+        Some(previous) => previous
+            .entries
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(Manifest {
+        version: MANIFEST_VERSION,
+        kind: if previous.is_some() {
+            ManifestKind::Incremental
+        } else {
+            ManifestKind::Full
+        },
+        root: root.to_string(),
+        created_at: SystemTime::now(),
+        entries,
+        removed,
+    })
+}
+
+fn new_entry(meta: &Metadata, digest: Digest) -> ManifestEntry {
+    ManifestEntry {
+        size: meta.content_length(),
+        digest,
+        content_type: meta.content_type().map(String::from),
+        etag: meta.etag().map(String::from),
+        last_modified: meta.last_modified().map(|ts| ts.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_capture() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write_with("data/a.txt", "foo")
+            .content_type("text/plain")
+            .await?;
+        operator.write("data/nested/b.txt", "barbaz").await?;
+
+        let manifest = Manifest::capture(&operator, "data/").await?;
+
+        assert_eq!(manifest.kind, ManifestKind::Full);
+        assert_eq!(manifest.root, "data");
+        assert_eq!(
+            manifest.entries.keys().collect::<Vec<_>>(),
+            vec!["a.txt", "nested/b.txt"]
+        );
+
+        let entry = &manifest.entries["a.txt"];
+        assert_eq!(entry.size, 3);
+        assert_eq!(entry.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(
+            entry.digest.to_string(),
+            "sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+
+        manifest.write(&operator, "manifests/1.json").await?;
+        assert_eq!(
+            Manifest::read(&operator, "manifests/1.json").await?,
+            manifest
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_capture_incremental() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data/a.txt", "foo").await?;
+        operator.write("data/b.txt", "bar").await?;
+        operator.write("data/c.txt", "baz").await?;
+
+        let full = Manifest::capture(&operator, "data").await?;
+
+        operator.write("data/b.txt", "qux").await?;
+        operator.delete("data/c.txt").await?;
+        operator.write("data/d.txt", "new").await?;
+
+        let incremental = Manifest::capture_incremental(&operator, "data", &full).await?;
+
+        assert_eq!(incremental.kind, ManifestKind::Incremental);
+        assert_eq!(
+            incremental.entries.keys().collect::<Vec<_>>(),
+            vec!["b.txt", "d.txt"]
+        );
+        assert_eq!(incremental.removed, vec!["c.txt"]);
+
+        let diff = incremental.diff(&full);
+        assert_eq!(diff.added, vec!["d.txt"]);
+        assert_eq!(diff.modified, vec!["b.txt"]);
+        assert_eq!(diff.removed, vec!["c.txt"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_unsupported_version() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let mut manifest = Manifest::capture(&operator, "data").await?;
+        manifest.version = MANIFEST_VERSION + 1;
+        manifest.write(&operator, "manifest.json").await?;
+
+        let result = Manifest::read(&operator, "manifest.json").await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Unsupported);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use futures::TryStreamExt;
+use opendal::{Error, ErrorKind, Metadata, Operator};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+// Number of objects rewritten at the same time.
+const CONCURRENCY: usize = 8;
+
+/// Changes applied to the metadata of objects by [`update`].
+///
+/// Fields left empty keep their current value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MetadataPatch {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+
+    /// User metadata merged into the current user metadata of the objects.
+    pub user_metadata: HashMap<String, String>,
+}
+
+impl MetadataPatch {
+    // Whether applying the patch changes the metadata.
+    fn changes(&self, meta: &Metadata) -> bool {
+        let differs = |patch: &Option<String>, current: Option<&str>| {
+            patch.as_deref().is_some_and(|value| Some(value) != current)
+        };
+
+        differs(&self.content_type, meta.content_type())
+            || differs(&self.cache_control, meta.cache_control())
+            || differs(&self.content_disposition, meta.content_disposition())
+            || self.user_metadata.iter().any(|(key, value)| {
+                meta.user_metadata().and_then(|current| current.get(key)) != Some(value)
+            })
+    }
+}
+
+/// Outcome of an [`update`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct UpdateReport {
+    /// Number of files matching the path.
+    pub matched: u64,
+
+    /// Number of files rewritten (files already matching the patch are left alone).
+    pub updated: u64,
+}
+
+/// Update the metadata of the files matching a path or a glob pattern (e.g. to fix wrong content types).
+///
+/// OpenDAL can't change the metadata of an existing object, so every file is rewritten in place
+/// with its current metadata and the patch applied. Files are read into memory,
+/// and are only overwritten if they haven't changed in the meantime on services supporting conditional writes.
+///
+/// Fails with [`ErrorKind::Unsupported`] if the service can't store a field of the patch.
+pub async fn update(
+    operator: &Operator,
+    path: &str,
+    patch: &MetadataPatch,
+) -> Result<UpdateReport, Error> {
+    check_capability(operator, patch)?;
+
+    let lister = list::lister(operator, path, None).await?;
+
+    lister
+        .try_filter(|entry| futures::future::ready(entry.metadata().is_file()))
+        .map_ok(|entry| async move { update_file(operator, entry.path(), patch).await })
+        .try_buffer_unordered(CONCURRENCY)
+        .try_fold(UpdateReport::default(), |mut report, updated| {
+            report.matched += 1;
+
+            if updated {
+                report.updated += 1;
+            }
+
+            futures::future::ready(Ok(report))
+        })
+        .await
+}
+
+fn check_capability(operator: &Operator, patch: &MetadataPatch) -> Result<(), Error> {
+    let capability = operator.info().full_capability();
+
+    let unsupported = [
+        (
+            "content_type",
+            patch.content_type.is_some() && !capability.write_with_content_type,
+        ),
+        (
+            "cache_control",
+            patch.cache_control.is_some() && !capability.write_with_cache_control,
+        ),
+        (
+            "content_disposition",
+            patch.content_disposition.is_some() && !capability.write_with_content_disposition,
+        ),
+        (
+            "user_metadata",
+            !patch.user_metadata.is_empty() && !capability.write_with_user_metadata,
+        ),
+    ];
+
+    match unsupported.iter().find(|(_, unsupported)| *unsupported) {
+        Some((field, _)) => Err(Error::new(
+            ErrorKind::Unsupported,
+            "Service does not support updating metadata field",
+        )
+        .with_context("field", *field)),
+        None => Ok(()),
+    }
+}
+
+// Rewrite a file with the patch applied, returning false if the file already matches the patch.
+async fn update_file(
+    operator: &Operator,
+    path: &str,
+    patch: &MetadataPatch,
+) -> Result<bool, Error> {
+    let meta = operator.stat(path).await?;
+
+    if !patch.changes(&meta) {
+        return Ok(false);
+    }
+
+    let capability = operator.info().full_capability();
+
+    let mut read = operator.read_with(path);
+
+    if let Some(etag) = meta.etag().filter(|_| capability.read_with_if_match) {
+        read = read.if_match(etag);
+    }
+
+    let buffer = read.await?;
+
+    let mut write = operator.write_with(path, buffer);
+
+    let field = |patch: &Option<String>, current: Option<&str>, supported: bool| {
+        patch
+            .clone()
+            .or_else(|| current.map(String::from))
+            .filter(|_| supported)
+    };
+
+    if let Some(value) = field(
+        &patch.content_type,
+        meta.content_type(),
+        capability.write_with_content_type,
+    ) {
+        write = write.content_type(&value);
+    }
+
+    if let Some(value) = field(
+        &patch.cache_control,
+        meta.cache_control(),
+        capability.write_with_cache_control,
+    ) {
+        write = write.cache_control(&value);
+    }
+
+    if let Some(value) = field(
+        &patch.content_disposition,
+        meta.content_disposition(),
+        capability.write_with_content_disposition,
+    ) {
+        write = write.content_disposition(&value);
+    }
+
+    if let Some(value) = meta
+        .content_encoding()
+        .filter(|_| capability.write_with_content_encoding)
+    {
+        write = write.content_encoding(value);
+    }
+
+    if capability.write_with_user_metadata {
+        let mut user_metadata = meta.user_metadata().cloned().unwrap_or_default();
+        user_metadata.extend(patch.user_metadata.clone());
+
+        write = write.user_metadata(user_metadata);
+    }
+
+    if let Some(etag) = meta.etag().filter(|_| capability.write_with_if_match) {
+        write = write.if_match(etag);
+    }
+
+    write.await.map_err(|err| err.with_context("path", path))?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_update() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write_with("site/index.html", "<html></html>")
+            .content_type("text/plain")
+            .cache_control("no-cache")
+            .await?;
+        operator
+            .write_with("site/about.html", "<html></html>")
+            .content_type("text/html")
+            .await?;
+        operator.write("site/style.css", "body {}").await?;
+
+        let patch = MetadataPatch {
+            content_type: Some("text/html".to_string()),
+            ..Default::default()
+        };
+
+        let report = update(&operator, "site/*.html", &patch).await?;
+        assert_eq!(
+            report,
+            UpdateReport {
+                matched: 2,
+                updated: 1,
+            }
+        );
+
+        let meta = operator.stat("site/index.html").await?;
+        assert_eq!(meta.content_type(), Some("text/html"));
+        assert_eq!(meta.cache_control(), Some("no-cache"));
+        assert_eq!(
+            operator.read("site/index.html").await?.to_vec(),
+            b"<html></html>"
+        );
+
+        assert_eq!(operator.stat("site/style.css").await?.content_type(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_unsupported() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let patch = MetadataPatch {
+            user_metadata: HashMap::from([("owner".to_string(), "team".to_string())]),
+            ..Default::default()
+        };
+
+        let err = update(&operator, "**", &patch).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        Ok(())
+    }
+}
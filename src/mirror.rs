@@ -0,0 +1,586 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use opendal::{Error, ErrorKind};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::{Copier, CopyOptions, Lock};
+
+/// When a [`Mirror`] runs.
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// Run immediately after starting, then every time the interval elapses.
+    Interval(Duration),
+
+    /// Run every time the cron expression fires (in UTC).
+    #[cfg(feature = "cron")]
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    /// Parse a cron expression (with seconds, e.g. `0 */5 * * * *`).
+    #[cfg(feature = "cron")]
+    pub fn cron(expression: &str) -> Result<Self, opendal::Error> {
+        use std::str::FromStr;
+
+        let schedule = cron::Schedule::from_str(expression).map_err(|err| {
+            opendal::Error::new(opendal::ErrorKind::ConfigInvalid, "Invalid cron expression")
+                .with_context("expression", expression)
+                .set_source(err)
+        })?;
+
+        Ok(Schedule::Cron(Box::new(schedule)))
+    }
+
+    /// The first time the schedule fires after `time`.
+    ///
+    /// Returns `None` if the schedule never fires again.
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        match self {
+            Schedule::Interval(period) => time.checked_add(*period),
+            #[cfg(feature = "cron")]
+            Schedule::Cron(schedule) => schedule
+                .after(&chrono::DateTime::<chrono::Utc>::from(time))
+                .next()
+                .map(SystemTime::from),
+        }
+    }
+}
+
+/// What happens when a run takes longer than the time until the next scheduled run.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum MissedRunPolicy {
+    /// Start the next run right after the run that overlapped it.
+    ///
+    /// Several missed runs are coalesced into a single one.
+    #[default]
+    CatchUp,
+
+    /// Skip missed runs and wait for the next time the schedule fires.
+    Skip,
+}
+
+/// The state of a [`Mirror`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum MirrorState {
+    /// The mirror has not been started or has been stopped.
+    #[default]
+    Stopped,
+
+    /// The mirror is waiting for the next run.
+    Idle,
+
+    /// The mirror is currently running.
+    Running,
+}
+
+/// Summary of a single [`Mirror`] run.
+#[derive(Debug, Clone)]
+pub struct MirrorRun {
+    pub started_at: SystemTime,
+    pub duration: Duration,
+
+    /// The error message if the run failed.
+    pub error: Option<String>,
+}
+
+impl MirrorRun {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Status of a [`Mirror`], including a summary of the most recent run.
+#[derive(Debug, Clone, Default)]
+pub struct MirrorStatus {
+    pub state: MirrorState,
+
+    /// Number of completed runs (successful or not).
+    pub runs: u64,
+
+    /// Number of failed runs.
+    pub failures: u64,
+
+    /// Number of runs skipped because the lock was held by another owner.
+    pub skipped: u64,
+
+    pub last_run: Option<MirrorRun>,
+}
+
+/// Continuously replicates a source path to a destination on a [`Schedule`].
+///
+/// Every run performs a copy with the configured [`CopyOptions`] on a background tokio task.
+/// With [`Mirror::with_delete_extraneous`], destination files missing from the source are deleted after copying.
+/// Runs overlapping the next scheduled run are handled according to a [`MissedRunPolicy`].
+///
+/// To prevent several processes from mirroring the same paths at the same time, give the mirror a [`Lock`]
+/// with [`Mirror::with_lock`].
+pub struct Mirror {
+    inner: Arc<Inner>,
+    missed_run_policy: MissedRunPolicy,
+    delete_extraneous: bool,
+    lock: Option<Lock>,
+    status: watch::Sender<MirrorStatus>,
+    task: Option<(watch::Sender<bool>, JoinHandle<()>)>,
+}
+
+struct Inner {
+    copier: Copier,
+    source: String,
+    destination: String,
+    options: CopyOptions,
+    schedule: Schedule,
+}
+
+impl Mirror {
+    /// Create a mirror (not started yet).
+    ///
+    /// Fails with [`ErrorKind::ConfigInvalid`] if the schedule is a zero interval.
+    pub fn new(
+        copier: Copier,
+        source: impl Into<String>,
+        destination: impl Into<String>,
+        options: CopyOptions,
+        schedule: Schedule,
+    ) -> Result<Self, Error> {
+        if let Schedule::Interval(period) = &schedule
+            && period.is_zero()
+        {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "Mirror interval must be greater than zero",
+            ));
+        }
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                copier,
+                source: source.into(),
+                destination: destination.into(),
+                options,
+                schedule,
+            }),
+            missed_run_policy: MissedRunPolicy::default(),
+            delete_extraneous: false,
+            lock: None,
+            status: watch::Sender::new(MirrorStatus::default()),
+            task: None,
+        })
+    }
+
+    /// Set how runs overlapping the next scheduled run are handled (takes effect on the next start).
+    pub fn with_missed_run_policy(mut self, missed_run_policy: MissedRunPolicy) -> Self {
+        self.missed_run_policy = missed_run_policy;
+        self
+    }
+
+    /// Delete destination files that don't exist in the source after every run (takes effect on the next start).
+    pub fn with_delete_extraneous(mut self, delete_extraneous: bool) -> Self {
+        self.delete_extraneous = delete_extraneous;
+        self
+    }
+
+    /// Hold a lock while running (takes effect on the next start).
+    ///
+    /// Runs are skipped while the lock is held by another owner.
+    /// The lock is renewed during long runs and released after every run.
+    pub fn with_lock(mut self, lock: Lock) -> Self {
+        self.lock = Some(lock);
+        self
+    }
+
+    /// The next time the mirror is scheduled to run, if it is running.
+    pub fn next_run(&self) -> Option<SystemTime> {
+        self.task.as_ref()?;
+
+        self.inner.schedule.next_after(SystemTime::now())
+    }
+
+    /// Start running the mirror in the background.
+    ///
+    /// Calling `start` on a mirror that is already running has no effect.
+    pub fn start(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+
+        let (shutdown, shutdown_rx) = watch::channel(false);
+
+        self.status
+            .send_modify(|status| status.state = MirrorState::Idle);
+
+        let task = tokio::spawn(run(
+            self.inner.clone(),
+            self.missed_run_policy,
+            self.delete_extraneous,
+            self.lock.clone(),
+            self.status.clone(),
+            shutdown_rx,
+        ));
+
+        self.task = Some((shutdown, task));
+    }
+
+    /// Stop the mirror.
+    ///
+    /// A run that is already in progress is allowed to finish before this method returns.
+    pub async fn stop(&mut self) {
+        if let Some((shutdown, task)) = self.task.take() {
+            let _ = shutdown.send(true);
+            let _ = task.await;
+        }
+
+        self.status
+            .send_modify(|status| status.state = MirrorState::Stopped);
+    }
+
+    /// Current status of the mirror.
+    pub fn status(&self) -> MirrorStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Subscribe to status changes (e.g. to observe every completed run).
+    pub fn subscribe(&self) -> watch::Receiver<MirrorStatus> {
+        self.status.subscribe()
+    }
+}
+
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        if let Some((shutdown, _)) = self.task.take() {
+            let _ = shutdown.send(true);
+        }
+    }
+}
+
+async fn run(
+    inner: Arc<Inner>,
+    missed_run_policy: MissedRunPolicy,
+    delete_extraneous: bool,
+    lock: Option<Lock>,
+    status: watch::Sender<MirrorStatus>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut ticker = Ticker::new(&inner.schedule, missed_run_policy);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.changed() => break,
+        }
+
+        let started_at = SystemTime::now();
+        let start = tokio::time::Instant::now();
+
+        let guard = match &lock {
+            Some(lock) => match lock.clone().try_acquire_guard().await {
+                Ok(Some(guard)) => Some(Ok(guard)),
+                Ok(None) => {
+                    status.send_modify(|status| status.skipped += 1);
+
+                    continue;
+                }
+                Err(err) => Some(Err(err)),
+            },
+            None => None,
+        };
+
+        status.send_modify(|status| status.state = MirrorState::Running);
+
+        let result = match guard {
+            Some(Err(err)) => Err(err),
+            guard => {
+                let result = inner
+                    .copier
+                    .sync(
+                        &inner.source,
+                        &inner.destination,
+                        inner.options,
+                        delete_extraneous,
+                    )
+                    .await;
+
+                match guard {
+                    Some(Ok(guard)) => result.and(guard.release().await),
+                    _ => result,
+                }
+            }
+        };
+
+        let run = MirrorRun {
+            started_at,
+            duration: start.elapsed(),
+            error: result.err().map(|err| err.to_string()),
+        };
+
+        status.send_modify(|status| {
+            status.state = MirrorState::Idle;
+            status.runs += 1;
+
+            if !run.is_success() {
+                status.failures += 1;
+            }
+
+            status.last_run = Some(run);
+        });
+    }
+}
+
+pub(crate) enum Ticker {
+    Interval(tokio::time::Interval),
+    #[cfg(feature = "cron")]
+    Cron {
+        schedule: Box<cron::Schedule>,
+        missed_run_policy: MissedRunPolicy,
+        last: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl Ticker {
+    pub(crate) fn new(schedule: &Schedule, missed_run_policy: MissedRunPolicy) -> Self {
+        match schedule {
+            Schedule::Interval(period) => {
+                let mut interval = tokio::time::interval(*period);
+                interval.set_missed_tick_behavior(match missed_run_policy {
+                    MissedRunPolicy::CatchUp => MissedTickBehavior::Delay,
+                    MissedRunPolicy::Skip => MissedTickBehavior::Skip,
+                });
+
+                Ticker::Interval(interval)
+            }
+            #[cfg(feature = "cron")]
+            Schedule::Cron(schedule) => Ticker::Cron {
+                schedule: schedule.clone(),
+                missed_run_policy,
+                last: chrono::Utc::now(),
+            },
+        }
+    }
+
+    pub(crate) async fn tick(&mut self) {
+        match self {
+            Ticker::Interval(interval) => {
+                interval.tick().await;
+            }
+            #[cfg(feature = "cron")]
+            Ticker::Cron {
+                schedule,
+                missed_run_policy,
+                last,
+            } => {
+                let now = chrono::Utc::now();
+
+                match next_cron_run(schedule, *missed_run_policy, *last, now) {
+                    Some(next) => {
+                        let delay = (next - now).to_std().unwrap_or_default();
+
+                        tokio::time::sleep(delay).await;
+
+                        *last = next.max(now);
+                    }
+                    // The schedule will never fire again
+                    None => std::future::pending().await,
+                }
+            }
+        }
+    }
+}
+
+// The time of the next run, given the time of the previous one.
+#[cfg(feature = "cron")]
+fn next_cron_run(
+    schedule: &cron::Schedule,
+    missed_run_policy: MissedRunPolicy,
+    last: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    let next = schedule.after(&last).next()?;
+
+    if next > now {
+        return Some(next);
+    }
+
+    match missed_run_policy {
+        MissedRunPolicy::CatchUp => Some(now),
+        MissedRunPolicy::Skip => schedule.after(&now).next(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+    use opendal::{Error, Operator};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mirror_interval() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/file.txt", "foo").await?;
+        destination.write("mirror/stale.txt", "stale").await?;
+
+        let mut mirror = Mirror::new(
+            Copier::new(source, destination.clone()),
+            "data/",
+            "mirror/",
+            CopyOptions::default(),
+            Schedule::Interval(Duration::from_millis(10)),
+        )?
+        .with_delete_extraneous(true);
+
+        let mut status = mirror.subscribe();
+
+        mirror.start();
+
+        // Wait for the first run to complete
+        status
+            .wait_for(|status| status.runs >= 1)
+            .await
+            .expect("mirror should report runs");
+
+        mirror.stop().await;
+
+        let status = mirror.status();
+        assert_eq!(status.state, MirrorState::Stopped);
+        assert_eq!(status.failures, 0);
+        assert!(status.last_run.unwrap().is_success());
+
+        let buffer = destination.read("mirror/file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+        assert!(!destination.exists("mirror/stale.txt").await?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirror_zero_interval() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let result = Mirror::new(
+            Copier::new(operator.clone(), operator),
+            "data/",
+            "mirror/",
+            CopyOptions::default(),
+            Schedule::Interval(Duration::ZERO),
+        );
+
+        assert_eq!(result.err().unwrap().kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mirror_lock() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/file.txt", "foo").await?;
+
+        let lock = |owner| {
+            Lock::new(
+                destination.clone(),
+                "locks/mirror",
+                owner,
+                Duration::from_secs(60),
+            )
+        };
+
+        let other = lock("other");
+        assert!(other.try_acquire().await?);
+
+        let mut mirror = Mirror::new(
+            Copier::new(source, destination.clone()),
+            "data/",
+            "mirror/",
+            CopyOptions::default(),
+            Schedule::Interval(Duration::from_millis(10)),
+        )?
+        .with_lock(lock("mirror"));
+
+        let mut status = mirror.subscribe();
+
+        mirror.start();
+
+        status
+            .wait_for(|status| status.skipped >= 1)
+            .await
+            .expect("mirror should skip runs");
+
+        assert_eq!(mirror.status().runs, 0);
+        assert!(!destination.exists("mirror/file.txt").await?);
+
+        other.release().await?;
+
+        status
+            .wait_for(|status| status.runs >= 1)
+            .await
+            .expect("mirror should report runs");
+
+        mirror.stop().await;
+
+        assert!(mirror.status().last_run.unwrap().is_success());
+        assert!(destination.exists("mirror/file.txt").await?);
+
+        // Released after the run
+        assert!(!destination.exists("locks/mirror").await?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cron")]
+    #[test]
+    fn test_schedule_cron() {
+        assert!(Schedule::cron("0 */5 * * * *").is_ok());
+        assert!(Schedule::cron("not a cron expression").is_err());
+    }
+
+    #[cfg(feature = "cron")]
+    #[test]
+    fn test_next_cron_run() {
+        use std::str::FromStr;
+
+        use chrono::{TimeZone, Utc};
+
+        let schedule = cron::Schedule::from_str("0 */5 * * * *").unwrap();
+
+        let last = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+
+        // On time
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 1, 0).unwrap();
+        let expected = Utc.with_ymd_and_hms(2024, 1, 1, 12, 5, 0).unwrap();
+        for policy in [MissedRunPolicy::CatchUp, MissedRunPolicy::Skip] {
+            assert_eq!(next_cron_run(&schedule, policy, last, now), Some(expected));
+        }
+
+        // The previous run overlapped the next two ticks
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 12, 0).unwrap();
+        assert_eq!(
+            next_cron_run(&schedule, MissedRunPolicy::CatchUp, last, now),
+            Some(now)
+        );
+        assert_eq!(
+            next_cron_run(&schedule, MissedRunPolicy::Skip, last, now),
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 15, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_schedule_next_after() {
+        let time = SystemTime::UNIX_EPOCH;
+
+        assert_eq!(
+            Schedule::Interval(Duration::from_secs(60)).next_after(time),
+            Some(time + Duration::from_secs(60))
+        );
+
+        #[cfg(feature = "cron")]
+        assert_eq!(
+            Schedule::cron("0 */5 * * * *").unwrap().next_after(time),
+            Some(time + Duration::from_secs(300))
+        );
+    }
+}
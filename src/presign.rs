@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::{StreamExt, TryStreamExt};
+use opendal::raw::PresignedRequest;
+use opendal::{EntryMode, Error, Operator};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+// Maximum number of presign operations in flight.
+const CONCURRENCY: usize = 16;
+
+/// Operation a presigned request is generated for.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum PresignMethod {
+    #[default]
+    Read,
+    Write,
+    Stat,
+    Delete,
+}
+
+/// Presign an operation for every path concurrently.
+///
+/// Failures are reported per path, so a single unsupported or invalid path
+/// does not prevent the rest from being presigned.
+pub async fn presign_many<I, S>(
+    operator: &Operator,
+    paths: I,
+    expire: Duration,
+    method: PresignMethod,
+) -> BTreeMap<String, Result<PresignedRequest, Error>>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    futures::stream::iter(paths)
+        .map(|path| {
+            let path = path.into();
+
+            async move {
+                let result = presign(operator, &path, expire, method).await;
+
+                (path, result)
+            }
+        })
+        .buffer_unordered(CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Presign an operation for every file matching a glob pattern.
+///
+/// Listing errors fail the whole call, presign errors are reported per path
+/// (see [`presign_many`]).
+pub async fn presign_glob(
+    operator: &Operator,
+    pattern: &str,
+    expire: Duration,
+    method: PresignMethod,
+) -> Result<BTreeMap<String, Result<PresignedRequest, Error>>, Error> {
+    let paths: Vec<String> = list::lister(operator, pattern, None)
+        .await?
+        .try_filter(|entry| futures::future::ready(entry.metadata().mode() == EntryMode::FILE))
+        .map_ok(|entry| entry.path().to_string())
+        .try_collect()
+        .await?;
+
+    Ok(presign_many(operator, paths, expire, method).await)
+}
+
+async fn presign(
+    operator: &Operator,
+    path: &str,
+    expire: Duration,
+    method: PresignMethod,
+) -> Result<PresignedRequest, Error> {
+    match method {
+        PresignMethod::Read => operator.presign_read(path, expire).await,
+        PresignMethod::Write => operator.presign_write(path, expire).await,
+        PresignMethod::Stat => operator.presign_stat(path, expire).await,
+        PresignMethod::Delete => operator.presign_delete(path, expire).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::ErrorKind;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_presign_glob_reports_errors_per_path() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("public/a.txt", "a").await?;
+        operator.write("public/b.txt", "b").await?;
+        operator.write("public/c.bin", "c").await?;
+
+        let result = presign_glob(
+            &operator,
+            "public/*.txt",
+            Duration::from_secs(3600),
+            PresignMethod::Read,
+        )
+        .await?;
+
+        let paths: Vec<_> = result.keys().map(String::as_str).collect();
+        assert_eq!(paths, vec!["public/a.txt", "public/b.txt"]);
+
+        // The memory service does not support presigning
+        for presigned in result.values() {
+            let err = presigned.as_ref().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Unsupported);
+        }
+
+        Ok(())
+    }
+}
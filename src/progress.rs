@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+use crate::{CopyEvent, CopyObserver};
+
+const TOTAL_TEMPLATE: &str =
+    "{prefix:>8} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}";
+const FILE_TEMPLATE: &str = "{prefix:>8} [{bar:40}] {bytes}/{total_bytes} {wide_msg}";
+
+/// Drives `indicatif` progress bars from [`CopyEvent`]s: one bar per file in flight and a total bar.
+///
+/// The total grows as files are discovered, since copies don't know their size upfront.
+///
+/// ```no_run
+/// # async fn example(source: opendal::Operator, destination: opendal::Operator) -> Result<(), opendal::Error> {
+/// use std::sync::Arc;
+///
+/// use opendal_util::{Copier, CopyProgress};
+///
+/// let progress = Arc::new(CopyProgress::new());
+///
+/// let copier = Copier::new(source, destination).with_observer(progress.clone());
+/// copier.copy("path/", "other/").await?;
+///
+/// progress.finish();
+/// # Ok(())
+/// # }
+/// ```
+pub struct CopyProgress {
+    multi: MultiProgress,
+    total: ProgressBar,
+    files: Mutex<HashMap<String, ProgressBar>>,
+}
+
+impl CopyProgress {
+    pub fn new() -> Self {
+        Self::with_multi_progress(MultiProgress::new())
+    }
+
+    /// Draw the bars in an existing [`MultiProgress`] (e.g. to share it with other bars).
+    pub fn with_multi_progress(multi: MultiProgress) -> Self {
+        let total = multi.add(ProgressBar::new(0));
+        total.set_style(style(TOTAL_TEMPLATE));
+        total.set_prefix("total");
+
+        Self {
+            multi,
+            total,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn multi_progress(&self) -> &MultiProgress {
+        &self.multi
+    }
+
+    /// Finish the total bar, leaving it on screen.
+    pub fn finish(&self) {
+        self.total.finish();
+    }
+
+    fn files(&self) -> std::sync::MutexGuard<'_, HashMap<String, ProgressBar>> {
+        self.files.lock().expect("progress lock poisoned")
+    }
+}
+
+impl Default for CopyProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CopyObserver for CopyProgress {
+    fn on_event(&self, event: &CopyEvent) {
+        match event {
+            CopyEvent::FileStarted { source, size, .. } => {
+                let bar = self.multi.add(ProgressBar::new(*size));
+                bar.set_style(style(FILE_TEMPLATE));
+                bar.set_prefix("copying");
+                bar.set_message(source.clone());
+
+                self.total.inc_length(*size);
+
+                // A restarted transfer replaces the bar of the failed attempt
+                if let Some(previous) = self.files().insert(source.clone(), bar) {
+                    self.total.dec_length(previous.length().unwrap_or_default());
+                    self.total
+                        .set_position(self.total.position().saturating_sub(previous.position()));
+                    self.multi.remove(&previous);
+                }
+            }
+            CopyEvent::FileProgress { source, bytes } => {
+                if let Some(bar) = self.files().get(source) {
+                    // The size is unknown for some services
+                    if bar.position() + bytes > bar.length().unwrap_or_default() {
+                        bar.inc_length(*bytes);
+                        self.total.inc_length(*bytes);
+                    }
+
+                    bar.inc(*bytes);
+                }
+
+                self.total.inc(*bytes);
+            }
+            CopyEvent::FileCompleted { source, .. } => {
+                if let Some(bar) = self.files().remove(source) {
+                    bar.finish_and_clear();
+                    self.multi.remove(&bar);
+                }
+
+                self.total.set_message(source.clone());
+            }
+            CopyEvent::FileSkipped { source, .. } => self.total.set_message(source.clone()),
+            CopyEvent::FileFailed { source, error, .. } => {
+                if let Some(bar) = self.files().get(source) {
+                    bar.abandon_with_message(format!("{}: {}", source, error));
+                }
+            }
+        }
+    }
+}
+
+fn style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template)
+        .expect("valid progress template")
+        .progress_chars("=> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indicatif::ProgressDrawTarget;
+    use opendal::services::Memory;
+    use opendal::{Error, Operator};
+
+    use super::*;
+    use crate::{Copier, CopyOptions};
+
+    #[tokio::test]
+    async fn test_copy_progress() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("dir/a.txt", "foo").await?;
+        source.write("dir/sub/b.txt", "barbaz").await?;
+
+        let progress = Arc::new(CopyProgress::with_multi_progress(
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+        ));
+
+        let copier = Copier::new(source, destination).with_observer(progress.clone());
+        copier
+            .copy_options(
+                "dir/",
+                "out/",
+                CopyOptions {
+                    recursive: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        progress.finish();
+
+        assert_eq!(progress.total.position(), 9);
+        assert_eq!(progress.total.length(), Some(9));
+        assert!(progress.files().is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use opendal::{Error, ErrorKind};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use crate::Copier;
+
+/// Identifier of a transfer in a [`TransferQueue`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransferId(u64);
+
+impl fmt::Display for TransferId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// State of a transfer in a [`TransferQueue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueuedTransferState {
+    /// Waiting for a worker.
+    Queued,
+    Running,
+
+    /// Not scheduled until resumed.
+    Paused,
+    Succeeded,
+    Failed(String),
+    Cancelled,
+}
+
+impl QueuedTransferState {
+    /// Whether the transfer finished (successfully or not).
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            QueuedTransferState::Succeeded
+                | QueuedTransferState::Failed(_)
+                | QueuedTransferState::Cancelled
+        )
+    }
+}
+
+/// A transfer of a [`TransferQueue`], as returned by [`TransferQueue::get`] and [`TransferQueue::list`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedTransfer {
+    pub id: TransferId,
+    pub priority: i32,
+    pub state: QueuedTransferState,
+    pub source: String,
+    pub destination: String,
+}
+
+/// Runs copies in priority order on a bounded number of workers.
+///
+/// Transfers with a higher priority start first; transfers with the same priority start in the order they were enqueued.
+/// With preemption enabled, enqueuing a transfer with a higher priority than a running one while all workers are busy
+/// interrupts the running transfer with the lowest priority and puts it back in the queue
+/// (it restarts from the beginning, unless the copier resumes from a journal).
+///
+/// Transfers are executed by Tokio tasks, so the queue must be used within a Tokio runtime.
+///
+/// ```no_run
+/// # async fn example(background: opendal_util::Copier, user: opendal_util::Copier) -> Result<(), opendal::Error> {
+/// use opendal_util::TransferQueue;
+///
+/// let queue = TransferQueue::new(2).with_preemption(true);
+///
+/// queue.enqueue(background, "media/", "mirror/media/", 0);
+/// let id = queue.enqueue(user, "uploads/video.mp4", "media/video.mp4", 10);
+///
+/// queue.wait(id).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TransferQueue {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    changed: Notify,
+}
+
+struct State {
+    concurrency: usize,
+    preemption: bool,
+    next_id: u64,
+    transfers: BTreeMap<TransferId, Entry>,
+}
+
+struct Entry {
+    copier: Copier,
+    source: String,
+    destination: String,
+    priority: i32,
+    state: QueuedTransferState,
+
+    // Identifies the current run, so that interrupted runs don't record their outcome
+    run: u64,
+    task: Option<JoinHandle<()>>,
+}
+
+impl TransferQueue {
+    /// Create a queue running at most `concurrency` transfers at the same time.
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    concurrency: concurrency.max(1),
+                    preemption: false,
+                    next_id: 0,
+                    transfers: BTreeMap::new(),
+                }),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    /// Interrupt running transfers for transfers with a higher priority (disabled by default).
+    pub fn with_preemption(self, preemption: bool) -> Self {
+        self.shared.state().preemption = preemption;
+        self
+    }
+
+    /// Add a copy (see [`Copier::copy`]) to the queue.
+    pub fn enqueue(
+        &self,
+        copier: Copier,
+        source: impl Into<String>,
+        destination: impl Into<String>,
+        priority: i32,
+    ) -> TransferId {
+        let mut state = self.shared.state();
+
+        let id = TransferId(state.next_id);
+        state.next_id += 1;
+
+        state.transfers.insert(
+            id,
+            Entry {
+                copier,
+                source: source.into(),
+                destination: destination.into(),
+                priority,
+                state: QueuedTransferState::Queued,
+                run: 0,
+                task: None,
+            },
+        );
+
+        self.shared.schedule(&mut state);
+
+        id
+    }
+
+    /// A transfer of the queue.
+    pub fn get(&self, id: TransferId) -> Option<QueuedTransfer> {
+        self.shared
+            .state()
+            .transfers
+            .get(&id)
+            .map(|entry| entry.describe(id))
+    }
+
+    /// The transfers of the queue (including finished ones), in the order they were enqueued.
+    pub fn list(&self) -> Vec<QueuedTransfer> {
+        self.shared
+            .state()
+            .transfers
+            .iter()
+            .map(|(id, entry)| entry.describe(*id))
+            .collect()
+    }
+
+    /// Stop scheduling a transfer until it is resumed.
+    ///
+    /// A running transfer is interrupted (it restarts from the beginning when resumed,
+    /// unless the copier resumes from a journal).
+    pub fn pause(&self, id: TransferId) -> Result<(), Error> {
+        let mut state = self.shared.state();
+        let entry = state.unfinished(id)?;
+
+        entry.interrupt();
+        entry.state = QueuedTransferState::Paused;
+
+        self.shared.schedule(&mut state);
+
+        Ok(())
+    }
+
+    /// Put a paused transfer back in the queue.
+    pub fn resume(&self, id: TransferId) -> Result<(), Error> {
+        let mut state = self.shared.state();
+        let entry = state.unfinished(id)?;
+
+        if entry.state == QueuedTransferState::Paused {
+            entry.state = QueuedTransferState::Queued;
+        }
+
+        self.shared.schedule(&mut state);
+
+        Ok(())
+    }
+
+    /// Change the priority of a transfer.
+    ///
+    /// Affects when a queued transfer starts; a running transfer may be preempted if preemption is enabled.
+    pub fn reprioritize(&self, id: TransferId, priority: i32) -> Result<(), Error> {
+        let mut state = self.shared.state();
+
+        state.unfinished(id)?.priority = priority;
+
+        self.shared.schedule(&mut state);
+
+        Ok(())
+    }
+
+    /// Cancel a transfer, interrupting it if it is running.
+    ///
+    /// Files already copied by an interrupted transfer are left in the destination.
+    pub fn cancel(&self, id: TransferId) -> Result<(), Error> {
+        let mut state = self.shared.state();
+        let entry = state.unfinished(id)?;
+
+        entry.interrupt();
+        entry.state = QueuedTransferState::Cancelled;
+
+        self.shared.schedule(&mut state);
+
+        Ok(())
+    }
+
+    /// Wait for a transfer to finish, returning its final state.
+    pub async fn wait(&self, id: TransferId) -> Result<QueuedTransferState, Error> {
+        loop {
+            let changed = self.shared.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            let state = self.get(id).ok_or_else(|| not_found(id))?.state;
+
+            if state.is_finished() {
+                return Ok(state);
+            }
+
+            changed.await;
+        }
+    }
+
+    /// Remove finished transfers from the queue.
+    pub fn clear_finished(&self) {
+        self.shared
+            .state()
+            .transfers
+            .retain(|_, entry| !entry.state.is_finished());
+    }
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("transfer queue lock poisoned")
+    }
+
+    // Start queued transfers while workers are available (preempting lower priority transfers if enabled).
+    fn schedule(self: &Arc<Self>, state: &mut State) {
+        while let Some((id, priority)) = state.next_queued() {
+            let running = state
+                .transfers
+                .values()
+                .filter(|entry| entry.state == QueuedTransferState::Running)
+                .count();
+
+            if running >= state.concurrency {
+                if !state.preemption {
+                    break;
+                }
+
+                // Lowest priority first, then the newest
+                let Some(victim) = state
+                    .transfers
+                    .values_mut()
+                    .rev()
+                    .filter(|entry| entry.state == QueuedTransferState::Running)
+                    .min_by_key(|entry| entry.priority)
+                    .filter(|entry| entry.priority < priority)
+                else {
+                    break;
+                };
+
+                victim.interrupt();
+                victim.state = QueuedTransferState::Queued;
+            }
+
+            let entry = state.transfers.get_mut(&id).expect("queued transfer");
+
+            entry.run += 1;
+            entry.state = QueuedTransferState::Running;
+            entry.task = Some(tokio::spawn(run(
+                self.clone(),
+                id,
+                entry.run,
+                entry.copier.clone(),
+                entry.source.clone(),
+                entry.destination.clone(),
+            )));
+        }
+
+        self.changed.notify_waiters();
+    }
+}
+
+impl State {
+    // The queued transfer to start next: the highest priority first, then the oldest.
+    fn next_queued(&self) -> Option<(TransferId, i32)> {
+        self.transfers
+            .iter()
+            .filter(|(_, entry)| entry.state == QueuedTransferState::Queued)
+            .max_by(|(a_id, a), (b_id, b)| a.priority.cmp(&b.priority).then(b_id.cmp(a_id)))
+            .map(|(id, entry)| (*id, entry.priority))
+    }
+
+    fn unfinished(&mut self, id: TransferId) -> Result<&mut Entry, Error> {
+        let entry = self.transfers.get_mut(&id).ok_or_else(|| not_found(id))?;
+
+        if entry.state.is_finished() {
+            return Err(
+                Error::new(ErrorKind::ConditionNotMatch, "Transfer already finished")
+                    .with_context("transfer", id),
+            );
+        }
+
+        Ok(entry)
+    }
+}
+
+impl Entry {
+    fn describe(&self, id: TransferId) -> QueuedTransfer {
+        QueuedTransfer {
+            id,
+            priority: self.priority,
+            state: self.state.clone(),
+            source: self.source.clone(),
+            destination: self.destination.clone(),
+        }
+    }
+
+    fn interrupt(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+async fn run(
+    shared: Arc<Shared>,
+    id: TransferId,
+    run: u64,
+    copier: Copier,
+    source: String,
+    destination: String,
+) {
+    let result = copier.copy(source, destination).await;
+
+    let mut state = shared.state();
+
+    let Some(entry) = state.transfers.get_mut(&id) else {
+        return;
+    };
+
+    if entry.run != run || entry.state != QueuedTransferState::Running {
+        return;
+    }
+
+    entry.task = None;
+    entry.state = match result {
+        Ok(()) => QueuedTransferState::Succeeded,
+        Err(err) => QueuedTransferState::Failed(err.to_string()),
+    };
+
+    shared.schedule(&mut state);
+}
+
+fn not_found(id: TransferId) -> Error {
+    Error::new(ErrorKind::NotFound, "Transfer not found").with_context("transfer", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::Operator;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transfer_queue() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("a.txt", "a").await?;
+        source.write("b.txt", "b").await?;
+
+        let copier = Copier::new(source, destination.clone());
+        let queue = TransferQueue::new(1);
+
+        let first = queue.enqueue(copier.clone(), "a.txt", "out/a.txt", 0);
+        let second = queue.enqueue(copier.clone(), "b.txt", "out/b.txt", 0);
+        let missing = queue.enqueue(copier.clone(), "missing.txt", "out/missing.txt", 5);
+
+        // The first transfer is running, the rest waits for the single worker
+        assert_eq!(
+            queue.get(first).unwrap().state,
+            QueuedTransferState::Running
+        );
+
+        queue.pause(second)?;
+        queue.reprioritize(missing, -1)?;
+
+        assert_eq!(queue.wait(first).await?, QueuedTransferState::Succeeded);
+        assert!(matches!(
+            queue.wait(missing).await?,
+            QueuedTransferState::Failed(_)
+        ));
+        assert_eq!(
+            queue.get(second).unwrap().state,
+            QueuedTransferState::Paused
+        );
+
+        queue.resume(second)?;
+        assert_eq!(queue.wait(second).await?, QueuedTransferState::Succeeded);
+        assert!(destination.exists("out/b.txt").await?);
+
+        let err = queue.cancel(second).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+
+        queue.clear_finished();
+        assert!(queue.list().is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transfer_queue_preemption() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("a.txt", "a").await?;
+
+        let copier = Copier::new(source, destination);
+        let queue = TransferQueue::new(1).with_preemption(true);
+
+        let background = queue.enqueue(copier.clone(), "a.txt", "mirror/a.txt", 0);
+        let user = queue.enqueue(copier.clone(), "a.txt", "user/a.txt", 10);
+
+        assert_eq!(
+            queue.get(background).unwrap().state,
+            QueuedTransferState::Queued
+        );
+        assert_eq!(queue.get(user).unwrap().state, QueuedTransferState::Running);
+
+        let cancelled = queue.enqueue(copier, "a.txt", "other/a.txt", 0);
+        queue.cancel(cancelled)?;
+
+        assert_eq!(queue.wait(user).await?, QueuedTransferState::Succeeded);
+        assert_eq!(
+            queue.wait(background).await?,
+            QueuedTransferState::Succeeded
+        );
+        assert_eq!(queue.wait(cancelled).await?, QueuedTransferState::Cancelled);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,458 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use futures::TryStreamExt;
+use opendal::raw::Timestamp;
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+/// Storage used under a prefix.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Usage {
+    /// Number of files.
+    pub objects: u64,
+
+    /// Total size of the files in bytes.
+    pub bytes: u64,
+}
+
+/// What [`enforce`] does when a prefix exceeds its limit.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum EnforcementPolicy {
+    /// Return an error.
+    #[default]
+    Reject,
+
+    /// Only report the violation.
+    Report,
+
+    /// Delete the oldest files until usage is back under the limit.
+    PruneOldest,
+}
+
+/// Result of [`enforce`].
+#[derive(Debug, Clone, Default)]
+pub struct QuotaReport {
+    /// Usage before enforcement.
+    pub usage: Usage,
+
+    /// Limit in bytes.
+    pub limit: u64,
+
+    /// Paths deleted to bring usage under the limit, oldest first.
+    pub pruned: Vec<String>,
+}
+
+impl QuotaReport {
+    /// Whether usage exceeded the limit (before any pruning).
+    pub fn exceeded(&self) -> bool {
+        self.usage.bytes > self.limit
+    }
+}
+
+/// Compute the storage used by files under `prefix`.
+pub async fn usage(operator: &Operator, prefix: &str) -> Result<Usage, Error> {
+    let files = files(operator, prefix, false).await?;
+
+    Ok(Usage {
+        objects: files.len() as u64,
+        bytes: files.iter().map(|file| file.size).sum(),
+    })
+}
+
+/// Keep the storage used by files under `prefix` under `limit` bytes.
+pub async fn enforce(
+    operator: &Operator,
+    prefix: &str,
+    limit: u64,
+    policy: EnforcementPolicy,
+) -> Result<QuotaReport, Error> {
+    let mut files = files(operator, prefix, policy == EnforcementPolicy::PruneOldest).await?;
+
+    let usage = Usage {
+        objects: files.len() as u64,
+        bytes: files.iter().map(|file| file.size).sum(),
+    };
+
+    let mut report = QuotaReport {
+        usage,
+        limit,
+        pruned: Vec::new(),
+    };
+
+    if !report.exceeded() {
+        return Ok(report);
+    }
+
+    match policy {
+        EnforcementPolicy::Reject => Err(QuotaExceeded {
+            prefix: prefix.to_string(),
+            usage,
+            max_bytes: Some(limit),
+            max_objects: None,
+        }
+        .into()),
+        EnforcementPolicy::Report => Ok(report),
+        EnforcementPolicy::PruneOldest => {
+            // Files without a modification time are considered the oldest
+            files.sort_by(|a, b| {
+                a.last_modified
+                    .cmp(&b.last_modified)
+                    .then_with(|| a.path.cmp(&b.path))
+            });
+
+            let mut bytes = usage.bytes;
+
+            for file in files {
+                if bytes <= limit {
+                    break;
+                }
+
+                operator.delete(&file.path).await?;
+
+                bytes -= file.size;
+                report.pruned.push(file.path);
+            }
+
+            Ok(report)
+        }
+    }
+}
+
+/// Limits checked by a [`Quota`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct QuotaOptions {
+    /// Maximum total size of the files in bytes.
+    pub max_bytes: Option<u64>,
+
+    /// Maximum number of files.
+    pub max_objects: Option<u64>,
+}
+
+/// Error source of copies and [`enforce`] calls rejected because of a quota.
+///
+/// Use [`QuotaExceeded::from_error`] to tell quota violations apart from other errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    pub prefix: String,
+
+    /// Usage including the rejected write.
+    pub usage: Usage,
+
+    pub max_bytes: Option<u64>,
+    pub max_objects: Option<u64>,
+}
+
+impl QuotaExceeded {
+    /// Returns the quota violation that caused an error, if any.
+    pub fn from_error(err: &Error) -> Option<&QuotaExceeded> {
+        std::error::Error::source(err)?.downcast_ref()
+    }
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "quota of {} exceeded ({} bytes, {} objects)",
+            self.prefix, self.usage.bytes, self.usage.objects
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+impl From<QuotaExceeded> for Error {
+    fn from(err: QuotaExceeded) -> Self {
+        Error::new(ErrorKind::Unexpected, "Quota exceeded")
+            .with_context("prefix", &err.prefix)
+            .with_context("usage", err.usage.bytes)
+            .set_source(err)
+    }
+}
+
+/// Guards the storage used under a destination prefix.
+///
+/// A [`Copier`](crate::Copier) given a quota with [`Copier::with_quota`](crate::Copier::with_quota)
+/// probes the usage of the prefix before every copy, then counts the files and bytes it writes,
+/// failing with a [`QuotaExceeded`] error as soon as a limit would be exceeded.
+///
+/// Overwritten files are counted as new files, so the quota errs on the side of rejecting writes.
+/// Files that fail to transfer are not counted.
+///
+/// Clones share the same usage, so a quota can be shared by several copiers writing to the same prefix.
+#[derive(Debug, Clone)]
+pub struct Quota {
+    operator: Operator,
+    prefix: String,
+    options: QuotaOptions,
+    usage: Arc<Mutex<Usage>>,
+}
+
+impl Quota {
+    pub fn new(operator: Operator, prefix: impl Into<String>, options: QuotaOptions) -> Self {
+        Self {
+            operator,
+            prefix: prefix.into(),
+            options,
+            usage: Arc::default(),
+        }
+    }
+
+    pub fn options(&self) -> QuotaOptions {
+        self.options
+    }
+
+    /// Usage as of the last probe, including the writes counted since then.
+    pub fn usage(&self) -> Usage {
+        *self.usage.lock().expect("quota lock poisoned")
+    }
+
+    /// Compute the usage of the prefix and check it against the limits.
+    pub async fn probe(&self) -> Result<Usage, Error> {
+        let current = usage(&self.operator, &self.prefix).await?;
+
+        *self.usage.lock().expect("quota lock poisoned") = current;
+
+        self.check(current)?;
+
+        Ok(current)
+    }
+
+    /// Check whether writing `objects` files of `bytes` bytes would exceed the limits, without counting them.
+    pub fn check_write(&self, objects: u64, bytes: u64) -> Result<(), Error> {
+        let usage = self.usage();
+
+        self.check(Usage {
+            objects: usage.objects + objects,
+            bytes: usage.bytes + bytes,
+        })
+    }
+
+    pub(crate) fn reservation(&self) -> Reservation {
+        Reservation {
+            quota: self.clone(),
+            charged: Usage::default(),
+            committed: false,
+        }
+    }
+
+    fn check(&self, usage: Usage) -> Result<(), Error> {
+        let exceeded = self.options.max_bytes.is_some_and(|max| usage.bytes > max)
+            || self
+                .options
+                .max_objects
+                .is_some_and(|max| usage.objects > max);
+
+        if exceeded {
+            return Err(QuotaExceeded {
+                prefix: self.prefix.clone(),
+                usage,
+                max_bytes: self.options.max_bytes,
+                max_objects: self.options.max_objects,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+// Usage counted for a single file, given back unless committed.
+pub(crate) struct Reservation {
+    quota: Quota,
+    charged: Usage,
+    committed: bool,
+}
+
+impl Reservation {
+    pub(crate) fn charge(&mut self, objects: u64, bytes: u64) -> Result<(), Error> {
+        let mut usage = self.quota.usage.lock().expect("quota lock poisoned");
+
+        let next = Usage {
+            objects: usage.objects + objects,
+            bytes: usage.bytes + bytes,
+        };
+
+        self.quota.check(next)?;
+
+        *usage = next;
+
+        self.charged.objects += objects;
+        self.charged.bytes += bytes;
+
+        Ok(())
+    }
+
+    pub(crate) fn commit(mut self) {
+        self.committed = true;
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+
+        if let Ok(mut usage) = self.quota.usage.lock() {
+            usage.objects = usage.objects.saturating_sub(self.charged.objects);
+            usage.bytes = usage.bytes.saturating_sub(self.charged.bytes);
+        }
+    }
+}
+
+struct File {
+    path: String,
+    size: u64,
+    last_modified: Option<Timestamp>,
+}
+
+async fn files(operator: &Operator, prefix: &str, last_modified: bool) -> Result<Vec<File>, Error> {
+    let options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, prefix, Some(options)).await?;
+
+    let mut files = Vec::new();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let mut meta = entry.metadata().clone();
+
+        // Some services don't return the size or modification time when listing
+        if meta.content_length() == 0 || (last_modified && meta.last_modified().is_none()) {
+            meta = operator.stat(entry.path()).await?;
+        }
+
+        files.push(File {
+            path: entry.path().to_string(),
+            size: meta.content_length(),
+            last_modified: meta.last_modified(),
+        });
+    }
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    async fn operator() -> Result<Operator, Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("tenant/a.txt", "aaaa").await?;
+        operator.write("tenant/b.txt", "bbbb").await?;
+        operator.write("tenant/sub/c.txt", "cc").await?;
+        operator.write("other/d.txt", "dddddddd").await?;
+
+        Ok(operator)
+    }
+
+    #[tokio::test]
+    async fn test_usage() -> Result<(), Error> {
+        let operator = operator().await?;
+
+        let usage = usage(&operator, "tenant/").await?;
+        assert_eq!(
+            usage,
+            Usage {
+                objects: 3,
+                bytes: 10
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_enforce() -> Result<(), Error> {
+        let operator = operator().await?;
+
+        let report = enforce(&operator, "tenant/", 10, EnforcementPolicy::Reject).await?;
+        assert!(!report.exceeded());
+
+        let err = enforce(&operator, "tenant/", 8, EnforcementPolicy::Reject)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert_eq!(QuotaExceeded::from_error(&err).unwrap().max_bytes, Some(8));
+
+        let report = enforce(&operator, "tenant/", 8, EnforcementPolicy::Report).await?;
+        assert!(report.exceeded());
+        assert!(report.pruned.is_empty());
+
+        // The memory service has no modification times, so files are pruned in path order
+        let report = enforce(&operator, "tenant/", 5, EnforcementPolicy::PruneOldest).await?;
+        assert_eq!(report.pruned, vec!["tenant/a.txt", "tenant/b.txt"]);
+
+        assert_eq!(usage(&operator, "tenant/").await?.bytes, 2);
+        assert_eq!(usage(&operator, "other/").await?.bytes, 8);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quota() -> Result<(), Error> {
+        let operator = operator().await?;
+
+        let quota = Quota::new(
+            operator,
+            "tenant/",
+            QuotaOptions {
+                max_bytes: Some(12),
+                max_objects: Some(4),
+            },
+        );
+
+        assert_eq!(quota.probe().await?.bytes, 10);
+        assert!(quota.check_write(1, 2).is_ok());
+        assert!(quota.check_write(1, 3).is_err());
+
+        // Usage is given back unless committed
+        let mut reservation = quota.reservation();
+        reservation.charge(1, 2)?;
+        assert!(reservation.charge(0, 1).is_err());
+        drop(reservation);
+        assert_eq!(quota.usage().bytes, 10);
+
+        let mut reservation = quota.reservation();
+        reservation.charge(1, 1)?;
+        reservation.commit();
+        assert_eq!(quota.usage().objects, 4);
+
+        let err = quota.check_write(1, 0).unwrap_err();
+        assert_eq!(
+            QuotaExceeded::from_error(&err).unwrap().usage,
+            Usage {
+                objects: 5,
+                bytes: 11
+            }
+        );
+
+        Ok(())
+    }
+}
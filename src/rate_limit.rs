@@ -0,0 +1,249 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use opendal::{Error, ErrorKind};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Limits enforced by a [`RateLimiter`].
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RateLimit {
+    /// Maximum number of bytes transferred per second.
+    ///
+    /// `None` means no limit. Zero is invalid.
+    pub bytes_per_second: Option<u64>,
+
+    /// Maximum number of requests issued per second.
+    ///
+    /// `None` means no limit. Zero is invalid.
+    pub requests_per_second: Option<u64>,
+}
+
+/// A token bucket based rate limiter that can be shared between multiple jobs.
+///
+/// Cloning a rate limiter is cheap and every clone draws from the same buckets,
+/// so the aggregate pressure on a backend stays under control
+/// rather than each job throttling independently.
+///
+/// Each bucket allows bursts of up to one second worth of tokens.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bytes: Option<Arc<Mutex<TokenBucket>>>,
+    requests: Option<Arc<Mutex<TokenBucket>>>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter.
+    ///
+    /// Fails with [`ErrorKind::ConfigInvalid`] if a rate is zero (leave it unset for no limit).
+    pub fn new(limit: RateLimit) -> Result<Self, Error> {
+        let bucket = |name: &str, rate: Option<u64>| match rate {
+            Some(0) => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "Rate limit must be greater than zero",
+            )
+            .with_context("limit", name)),
+            rate => Ok(rate.map(|rate| Arc::new(Mutex::new(TokenBucket::new(rate))))),
+        };
+
+        Ok(Self {
+            bytes: bucket("bytes_per_second", limit.bytes_per_second)?,
+            requests: bucket("requests_per_second", limit.requests_per_second)?,
+        })
+    }
+
+    /// Wait until `bytes` bytes may be transferred.
+    pub async fn acquire_bytes(&self, bytes: u64) {
+        if let Some(bucket) = &self.bytes {
+            acquire(bucket, bytes).await;
+        }
+    }
+
+    /// Wait until a single request may be issued.
+    pub async fn acquire_request(&self) {
+        if let Some(bucket) = &self.requests {
+            acquire(bucket, 1).await;
+        }
+    }
+}
+
+/// A budget shared by several [`Copier`](crate::Copier)s: a [`RateLimiter`] and a cap on concurrent file transfers.
+///
+/// Services running many copies at the same time (e.g. triggered by users) can give every copier a clone
+/// of the same budget with [`Copier::with_budget`](crate::Copier::with_budget) to enforce global limits.
+#[derive(Debug, Clone)]
+pub struct TransferBudget {
+    rate_limiter: RateLimiter,
+    transfers: Option<Arc<Semaphore>>,
+}
+
+/// Permission to transfer a file, held until dropped.
+#[derive(Debug)]
+pub struct TransferPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl TransferBudget {
+    /// Create a budget (see [`RateLimiter::new`]).
+    pub fn new(limit: RateLimit) -> Result<Self, Error> {
+        Ok(Self {
+            rate_limiter: RateLimiter::new(limit)?,
+            transfers: None,
+        })
+    }
+
+    /// Limit the number of files transferred at the same time.
+    pub fn with_max_transfers(mut self, max_transfers: usize) -> Self {
+        self.transfers = Some(Arc::new(Semaphore::new(max_transfers)));
+        self
+    }
+
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Wait until a file may be transferred.
+    pub async fn acquire_transfer(&self) -> TransferPermit {
+        let permit = match &self.transfers {
+            Some(transfers) => Some(
+                transfers
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("transfer semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        TransferPermit { _permit: permit }
+    }
+}
+
+async fn acquire(bucket: &Mutex<TokenBucket>, tokens: u64) {
+    let wait = bucket
+        .lock()
+        .expect("rate limiter lock poisoned")
+        .reserve(tokens);
+
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            updated_at: Instant::now(),
+        }
+    }
+
+    // Takes tokens from the bucket and returns how long the caller has to wait before using them.
+    //
+    // Tokens are taken even if there aren't enough of them (the bucket goes into debt),
+    // so requests larger than the bucket capacity are still served and callers are served in order.
+    fn reserve(&mut self, tokens: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated_at = now;
+        self.tokens -= tokens as f64;
+
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(-self.tokens / self.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_bytes() {
+        let limiter = RateLimiter::new(RateLimit {
+            bytes_per_second: Some(10),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let start = Instant::now();
+
+        // The bucket starts full
+        limiter.acquire_bytes(10).await;
+        assert!(start.elapsed() < Duration::from_millis(10));
+
+        // Clones share the same bucket
+        limiter.clone().acquire_bytes(5).await;
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_unlimited() {
+        let limiter = RateLimiter::new(RateLimit::default()).unwrap();
+
+        let start = Instant::now();
+
+        limiter.acquire_bytes(u64::MAX).await;
+        limiter.acquire_request().await;
+
+        assert!(start.elapsed() < Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_rate_limiter_zero() {
+        for limit in [
+            RateLimit {
+                bytes_per_second: Some(0),
+                ..Default::default()
+            },
+            RateLimit {
+                requests_per_second: Some(0),
+                ..Default::default()
+            },
+        ] {
+            let err = RateLimiter::new(limit).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+            assert!(TransferBudget::new(limit).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_budget() {
+        let budget = TransferBudget::new(RateLimit::default())
+            .unwrap()
+            .with_max_transfers(1);
+
+        let permit = budget.acquire_transfer().await;
+
+        // Clones share the same transfers
+        let waiting =
+            tokio::time::timeout(Duration::from_millis(10), budget.clone().acquire_transfer())
+                .await;
+        assert!(waiting.is_err());
+
+        drop(permit);
+
+        budget.clone().acquire_transfer().await;
+    }
+}
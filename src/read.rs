@@ -0,0 +1,220 @@
+use std::ops::Range;
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures::stream::{BoxStream, Stream};
+use futures::{StreamExt, TryStreamExt};
+use opendal::{Buffer, Error, ErrorKind, Operator};
+
+use crate::copy::IoErrorExt;
+
+/// Read the first `n` bytes of a file (or the whole file if it is shorter).
+pub async fn read_head(operator: &Operator, path: &str, n: u64) -> Result<Buffer, Error> {
+    read_range(operator, path, 0..n).await
+}
+
+/// Read the last `n` bytes of a file (or the whole file if it is shorter).
+pub async fn read_tail(operator: &Operator, path: &str, n: u64) -> Result<Buffer, Error> {
+    let size = operator.stat(path).await?.content_length();
+
+    read_clamped(operator, path, size.saturating_sub(n)..size).await
+}
+
+/// Read a range of bytes of a file.
+///
+/// The range is clamped to the size of the file: a range past the end of the file returns no bytes.
+///
+/// Falls back to reading the whole file when the service does not support ranged reads
+/// (or ignores the requested range).
+pub async fn read_range(
+    operator: &Operator,
+    path: &str,
+    range: Range<u64>,
+) -> Result<Buffer, Error> {
+    let size = operator.stat(path).await?.content_length();
+
+    read_clamped(operator, path, range.start.min(size)..range.end.min(size)).await
+}
+
+// Read a range within the bounds of the file.
+async fn read_clamped(operator: &Operator, path: &str, range: Range<u64>) -> Result<Buffer, Error> {
+    if range.is_empty() {
+        return Ok(Buffer::new());
+    }
+
+    let length = range.end - range.start;
+
+    let buffer = match operator.read_with(path).range(range.clone()).await {
+        Ok(buffer) => buffer,
+        Err(err) if err.kind() == ErrorKind::Unsupported => {
+            let buffer = operator.read(path).await?;
+
+            return Ok(slice(buffer, range));
+        }
+        Err(err) => return Err(err),
+    };
+
+    // The whole file was returned
+    if buffer.len() as u64 > length {
+        return Ok(slice(buffer, range));
+    }
+
+    Ok(buffer)
+}
+
+/// Stream the lines of a file (without line terminators).
+///
+/// The file is streamed chunk by chunk and never read into memory as a whole.
+/// Lines that are not valid UTF-8 yield an error.
+///
+/// See [`read_jsonl`](crate::read_jsonl) (with the `serde` feature) for streaming JSON lines.
+pub async fn lines(
+    operator: &Operator,
+    path: &str,
+) -> Result<BoxStream<'static, Result<String, Error>>, Error> {
+    let reader = operator.reader(path).await?;
+    let stream = reader
+        .into_bytes_stream(..)
+        .await?
+        .map_err(IoErrorExt::into_opendal_error);
+
+    let path = path.to_string();
+
+    Ok(split_lines(stream)
+        .and_then(move |line| {
+            let result = String::from_utf8(line.to_vec()).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "Invalid UTF-8 line")
+                    .with_context("path", &path)
+                    .set_source(err)
+            });
+
+            futures::future::ready(result)
+        })
+        .boxed())
+}
+
+// Split a byte stream into lines (without the line terminator), handling lines spanning chunks.
+pub(crate) fn split_lines<S>(stream: S) -> impl Stream<Item = Result<Bytes, Error>>
+where
+    S: Stream<Item = Result<Bytes, Error>> + Unpin,
+{
+    futures::stream::try_unfold(
+        (stream, BytesMut::new(), false),
+        |(mut stream, mut buffer, mut done)| async move {
+            loop {
+                if let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                    let mut line = buffer.split_to(pos + 1);
+                    line.truncate(pos);
+
+                    if line.last() == Some(&b'\r') {
+                        line.truncate(pos - 1);
+                    }
+
+                    return Ok(Some((line.freeze(), (stream, buffer, done))));
+                }
+
+                if done {
+                    if buffer.has_remaining() {
+                        let line = buffer.split().freeze();
+
+                        return Ok(Some((line, (stream, buffer, done))));
+                    }
+
+                    return Ok(None);
+                }
+
+                match stream.try_next().await? {
+                    Some(chunk) => buffer.extend_from_slice(&chunk),
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
+
+fn slice(buffer: Buffer, range: Range<u64>) -> Buffer {
+    let len = buffer.len();
+
+    let start = (range.start as usize).min(len);
+    let end = (range.end as usize).min(len);
+
+    buffer.slice(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_helpers() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("log.txt", "0123456789").await?;
+
+        let buffer = read_head(&operator, "log.txt", 3).await?;
+        assert_eq!(buffer.to_vec(), b"012");
+
+        let buffer = read_head(&operator, "log.txt", 100).await?;
+        assert_eq!(buffer.to_vec(), b"0123456789");
+
+        let buffer = read_tail(&operator, "log.txt", 4).await?;
+        assert_eq!(buffer.to_vec(), b"6789");
+
+        let buffer = read_tail(&operator, "log.txt", 100).await?;
+        assert_eq!(buffer.to_vec(), b"0123456789");
+
+        let buffer = read_range(&operator, "log.txt", 2..5).await?;
+        assert_eq!(buffer.to_vec(), b"234");
+
+        let buffer = read_range(&operator, "log.txt", 20..30).await?;
+        assert!(buffer.is_empty());
+
+        assert!(read_head(&operator, "missing.txt", 3).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lines() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("log.txt", "first\r\nsecond\n\nlast").await?;
+
+        let result: Vec<String> = lines(&operator, "log.txt").await?.try_collect().await?;
+        assert_eq!(result, vec!["first", "second", "", "last"]);
+
+        operator.write("binary", vec![0xff, 0xfe]).await?;
+
+        let result: Result<Vec<String>, Error> =
+            lines(&operator, "binary").await?.try_collect().await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_split_lines_across_chunks() -> Result<(), Error> {
+        let chunks = vec![
+            Ok(Bytes::from("fo")),
+            Ok(Bytes::from("o\r\nba")),
+            Ok(Bytes::from("r\n\nbaz")),
+        ];
+
+        let lines: Vec<Bytes> = split_lines(futures::stream::iter(chunks))
+            .try_collect()
+            .await?;
+
+        assert_eq!(lines, vec!["foo", "bar", "", "baz"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slice() {
+        let buffer = Buffer::from("0123456789");
+
+        assert_eq!(slice(buffer.clone(), 2..5).to_vec(), b"234");
+        assert_eq!(slice(buffer, 8..20).to_vec(), b"89");
+    }
+}
@@ -0,0 +1,194 @@
+use futures::{StreamExt, TryStreamExt, future};
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_dir;
+use crate::{Copier, CopyOptions, list};
+
+/// Options for [`rearrange`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RearrangeOptions {
+    /// Number of files moved at the same time.
+    pub concurrency: usize,
+
+    /// Copy files instead of moving them.
+    pub keep_source: bool,
+
+    /// Count the files that would be moved without moving them.
+    pub dry_run: bool,
+}
+
+impl Default for RearrangeOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            keep_source: false,
+            dry_run: false,
+        }
+    }
+}
+
+/// Files moved by [`rearrange`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RearrangeReport {
+    pub files: u64,
+}
+
+/// Move the files under each source prefix to the corresponding destination prefix within an operator
+/// (e.g. for layout migrations like `year=2023/` to `dt=2023/`).
+///
+/// Files are renamed when the service supports it, copied server-side and deleted otherwise,
+/// falling back to streaming them through the client. Directory markers are not moved.
+///
+/// Every source is listed before anything is moved. Fails without moving anything
+/// if a source prefix overlaps a destination prefix (including the root).
+pub async fn rearrange(
+    operator: &Operator,
+    mapping: Vec<(String, String)>,
+    options: RearrangeOptions,
+) -> Result<RearrangeReport, Error> {
+    let mapping: Vec<(String, String)> = mapping
+        .into_iter()
+        .map(|(from, to)| (normalize_dir(&from), normalize_dir(&to)))
+        .collect();
+
+    for (from, _) in &mapping {
+        if let Some((_, to)) = mapping
+            .iter()
+            .find(|(_, to)| to.starts_with(from.as_str()) || from.starts_with(to.as_str()))
+        {
+            return Err(
+                Error::new(ErrorKind::ConfigInvalid, "Rearrange prefixes overlap")
+                    .with_context("source", from)
+                    .with_context("destination", to),
+            );
+        }
+    }
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut moves = Vec::new();
+
+    for (from, to) in &mapping {
+        let mut lister = list::lister(operator, from, Some(list_options.clone())).await?;
+
+        while let Some(entry) = lister.try_next().await? {
+            if !entry.metadata().is_file() {
+                continue;
+            }
+
+            let path = entry.path().to_string();
+            let relative = path.strip_prefix(from.as_str()).unwrap_or(&path);
+
+            moves.push((format!("{}{}", to, relative), path));
+        }
+    }
+
+    let files = moves.len() as u64;
+
+    if options.dry_run {
+        return Ok(RearrangeReport { files });
+    }
+
+    futures::stream::iter(moves)
+        .map(|(to, from)| async move { move_file(operator, &from, &to, options.keep_source).await })
+        .buffer_unordered(options.concurrency.max(1))
+        .try_for_each(|()| future::ready(Ok(())))
+        .await?;
+
+    Ok(RearrangeReport { files })
+}
+
+async fn move_file(
+    operator: &Operator,
+    from: &str,
+    to: &str,
+    keep_source: bool,
+) -> Result<(), Error> {
+    let capability = operator.info().full_capability();
+
+    if !keep_source && capability.rename {
+        return operator.rename(from, to).await;
+    }
+
+    if capability.copy {
+        operator.copy(from, to).await?;
+    } else {
+        let options = CopyOptions {
+            recursive: false,
+            disable_glob: true,
+            ..Default::default()
+        };
+
+        Copier::new(operator.clone(), operator.clone())
+            .copy_options(from, to, options)
+            .await?;
+    }
+
+    if !keep_source {
+        operator.delete(from).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rearrange() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("year=2023/a.txt", "a").await?;
+        operator.write("year=2023/month=01/b.txt", "b").await?;
+        operator.write("year=2024/c.txt", "c").await?;
+
+        let mapping = vec![
+            ("year=2023".to_string(), "dt=2023/".to_string()),
+            ("/year=2024/".to_string(), "dt=2024".to_string()),
+        ];
+
+        let options = RearrangeOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = rearrange(&operator, mapping.clone(), options).await?;
+        assert_eq!(report.files, 3);
+        assert!(operator.exists("year=2023/a.txt").await?);
+
+        let report = rearrange(&operator, mapping, RearrangeOptions::default()).await?;
+        assert_eq!(report.files, 3);
+        assert_eq!(
+            operator.read("dt=2023/month=01/b.txt").await?.to_vec(),
+            b"b"
+        );
+        assert!(operator.exists("dt=2024/c.txt").await?);
+        assert!(!operator.exists("year=2023/a.txt").await?);
+
+        let mapping = vec![("dt=2023/".to_string(), "dt=2023/copy/".to_string())];
+        let err = rearrange(&operator, mapping, RearrangeOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+}
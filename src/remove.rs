@@ -0,0 +1,219 @@
+use futures::{TryStreamExt, future};
+use opendal::{EntryMode, Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_path;
+use crate::list;
+
+/// Options for [`remove_all`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RemoveAllOptions {
+    /// Number of objects deleted in a single batch (on services supporting batch deletes).
+    pub batch_size: usize,
+
+    /// Number of batches deleted at the same time.
+    pub concurrency: usize,
+
+    /// Count the objects that would be deleted without deleting them.
+    pub dry_run: bool,
+
+    /// Allow deleting everything when the prefix is empty (the root of the operator).
+    pub allow_root: bool,
+}
+
+impl Default for RemoveAllOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 1000,
+            concurrency: 4,
+            dry_run: false,
+            allow_root: false,
+        }
+    }
+}
+
+/// Objects deleted by [`remove_all`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RemoveAllReport {
+    pub files: u64,
+    pub directories: u64,
+}
+
+/// Delete a file, or a directory and everything in it (like `rm -rf`).
+///
+/// Files are deleted in batches (several batches at the same time), then directories are deleted from the deepest one.
+///
+/// Refuses to delete the root of the operator unless [`RemoveAllOptions::allow_root`] is set.
+pub async fn remove_all(
+    operator: &Operator,
+    path: &str,
+    options: RemoveAllOptions,
+) -> Result<RemoveAllReport, Error> {
+    let normalized = normalize_path(path);
+    let mut path = normalized.as_str().to_string();
+
+    if path.trim_matches('/').is_empty() {
+        if !options.allow_root {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "Refusing to delete the root without allow_root",
+            )
+            .with_context("path", path));
+        }
+
+        path.clear();
+    }
+
+    if !path.is_empty() && !path.ends_with('/') {
+        match operator.stat(&path).await {
+            Ok(meta) if meta.is_file() => {
+                if !options.dry_run {
+                    operator.delete(&path).await?;
+                }
+
+                return Ok(RemoveAllReport {
+                    files: 1,
+                    directories: 0,
+                });
+            }
+            Ok(_) => path.push('/'),
+            Err(err) if err.kind() == ErrorKind::NotFound => path.push('/'),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let lister = list::lister(
+        operator,
+        &path,
+        Some(ListOptions {
+            recursive: true,
+            ..Default::default()
+        }),
+    )
+    .await?;
+
+    let mut directories = Vec::new();
+
+    let files = lister
+        .try_filter_map(|entry| {
+            let file = match entry.metadata().mode() {
+                EntryMode::DIR => {
+                    directories.push(entry.path().to_string());
+                    None
+                }
+                _ => Some(entry.path().to_string()),
+            };
+
+            future::ready(Ok(file))
+        })
+        .try_chunks(options.batch_size.max(1))
+        .map_err(|err| err.1)
+        .map_ok(|batch| async move {
+            let count = batch.len() as u64;
+
+            if !options.dry_run {
+                operator.delete_iter(batch).await?;
+            }
+
+            Ok::<_, Error>(count)
+        })
+        .try_buffer_unordered(options.concurrency.max(1))
+        .try_fold(0, |total, count| future::ready(Ok(total + count)))
+        .await?;
+
+    if !path.is_empty() && !directories.contains(&path) && operator.exists(&path).await? {
+        directories.push(path);
+    }
+
+    // Children before their parents
+    directories.sort_by_key(|dir| std::cmp::Reverse(dir.matches('/').count()));
+
+    if !options.dry_run {
+        for dir in &directories {
+            operator.delete(dir).await?;
+        }
+    }
+
+    Ok(RemoveAllReport {
+        files,
+        directories: directories.len() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_remove_all() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data/a.txt", "a").await?;
+        operator.write("data/sub/b.txt", "b").await?;
+        operator.write("data/sub/c.txt", "c").await?;
+        operator.write("other.txt", "d").await?;
+
+        let options = RemoveAllOptions {
+            batch_size: 2,
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = remove_all(&operator, "data", options.clone()).await?;
+        assert_eq!(report.files, 3);
+        assert!(operator.exists("data/a.txt").await?);
+
+        let options = RemoveAllOptions {
+            dry_run: false,
+            ..options
+        };
+
+        let report = remove_all(&operator, "/data/", options.clone()).await?;
+        assert_eq!(report.files, 3);
+        assert!(!operator.exists("data/sub/b.txt").await?);
+        assert!(operator.exists("other.txt").await?);
+
+        let report = remove_all(&operator, "other.txt", options.clone()).await?;
+        assert_eq!(report.files, 1);
+        assert!(!operator.exists("other.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_all_root() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("a.txt", "a").await?;
+
+        let err = remove_all(&operator, "/", RemoveAllOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        assert!(operator.exists("a.txt").await?);
+
+        let options = RemoveAllOptions {
+            allow_root: true,
+            ..Default::default()
+        };
+
+        let report = remove_all(&operator, "", options).await?;
+        assert_eq!(report.files, 1);
+        assert!(!operator.exists("a.txt").await?);
+
+        Ok(())
+    }
+}
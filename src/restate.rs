@@ -1,23 +1,451 @@
-use opendal::ErrorKind;
-use restate_sdk::errors::{HandlerError, TerminalError};
-
-pub fn to_restate_error(err: opendal::Error) -> HandlerError {
-    if err.is_permanent() {
-        let status_code = match err.kind() {
-            ErrorKind::Unsupported => 501,
-            ErrorKind::ConfigInvalid => 400,
-            ErrorKind::NotFound => 404,
-            ErrorKind::PermissionDenied => 403,
-            ErrorKind::IsADirectory => 422,
-            ErrorKind::NotADirectory => 422,
-            ErrorKind::AlreadyExists => 409,
-            _ => 500,
+use std::sync::Arc;
+use std::time::Duration;
+
+use opendal::options::ListOptions;
+use opendal::{Error, ErrorKind, Operator};
+use restate_sdk::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_path;
+use crate::{Copier, CopyOptions, OperatorFactory, ResolvedLocation, list};
+
+// Number of attempts made by `run_with_restate_retries` before handing a transient error to Restate.
+const LOCAL_ATTEMPTS: usize = 3;
+
+/// How a handler should react to an [`opendal::Error`].
+#[derive(Debug)]
+pub enum RetryDecision {
+    /// The error is transient: retry after (at least) the suggested backoff.
+    Retry { error: Error, backoff: Duration },
+
+    /// The error is permanent: fail the invocation.
+    Terminal(TerminalError),
+}
+
+impl From<RetryDecision> for HandlerError {
+    fn from(decision: RetryDecision) -> Self {
+        match decision {
+            RetryDecision::Retry { error, .. } => error.into(),
+            RetryDecision::Terminal(err) => err.into(),
+        }
+    }
+}
+
+/// Maps [`opendal::Error`]s to Restate errors.
+///
+/// Permanent errors become a [`TerminalError`] with an HTTP status code matching the error kind
+/// (overridable per kind), transient errors are retryable.
+///
+/// ```
+/// use opendal::ErrorKind;
+/// use opendal_util::restate::ErrorMapper;
+///
+/// let mapper = ErrorMapper::new()
+///     .with_status(ErrorKind::AlreadyExists, 412)
+///     .with_default_status(502)
+///     .with_context(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorMapper {
+    statuses: Vec<(ErrorKind, u16)>,
+    default_status: u16,
+    include_context: bool,
+}
+
+impl Default for ErrorMapper {
+    fn default() -> Self {
+        Self {
+            statuses: vec![
+                (ErrorKind::Unsupported, 501),
+                (ErrorKind::ConfigInvalid, 400),
+                (ErrorKind::NotFound, 404),
+                (ErrorKind::PermissionDenied, 403),
+                (ErrorKind::IsADirectory, 422),
+                (ErrorKind::NotADirectory, 422),
+                (ErrorKind::AlreadyExists, 409),
+            ],
+            default_status: 500,
+            include_context: true,
+        }
+    }
+}
+
+impl ErrorMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Status code of terminal errors of a kind.
+    pub fn with_status(mut self, kind: ErrorKind, status: u16) -> Self {
+        self.statuses.retain(|(k, _)| *k != kind);
+        self.statuses.push((kind, status));
+        self
+    }
+
+    /// Status code of terminal errors of kinds without a specific status code (defaults to 500).
+    pub fn with_default_status(mut self, status: u16) -> Self {
+        self.default_status = status;
+        self
+    }
+
+    /// Whether terminal error messages include the operation, the context (e.g. path) and the source
+    /// of the error, or just its message (defaults to `true`).
+    pub fn with_context(mut self, include_context: bool) -> Self {
+        self.include_context = include_context;
+        self
+    }
+
+    /// Status code of terminal errors of a kind.
+    pub fn status(&self, kind: ErrorKind) -> u16 {
+        self.statuses
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, status)| *status)
+            .unwrap_or(self.default_status)
+    }
+
+    /// Classify an error as retryable (with a suggested backoff) or terminal.
+    pub fn classify(&self, err: Error) -> RetryDecision {
+        if err.is_permanent() {
+            let message = if self.include_context {
+                err.to_string()
+            } else {
+                err.message().to_string()
+            };
+
+            return RetryDecision::Terminal(
+                TerminalError::new(message).with_code(self.status(err.kind())),
+            );
+        }
+
+        let backoff = match err.kind() {
+            ErrorKind::RateLimited => Duration::from_secs(1),
+            _ if err.is_temporary() => Duration::from_millis(100),
+            _ => Duration::from_millis(500),
         };
 
-        return TerminalError::new(err.to_string())
-            .with_code(status_code)
-            .into();
+        RetryDecision::Retry {
+            error: err,
+            backoff,
+        }
     }
 
-    err.into()
+    pub fn map(&self, err: Error) -> HandlerError {
+        self.classify(err).into()
+    }
+}
+
+/// Classify an error as retryable (with a suggested backoff) or terminal using the default [`ErrorMapper`].
+pub fn classify(err: Error) -> RetryDecision {
+    ErrorMapper::default().classify(err)
+}
+
+pub fn to_restate_error(err: Error) -> HandlerError {
+    classify(err).into()
+}
+
+/// Run an operation as a journaled step, retrying it according to [`classify`].
+///
+/// Transient errors are retried in place (waiting for the suggested backoff) a few times,
+/// then handed to Restate, which retries the step with its own retry policy.
+/// Permanent errors fail the step with a [`TerminalError`] right away.
+pub async fn run_with_restate_retries<'ctx, C, F, Fut, T>(ctx: &C, f: F) -> Result<T, TerminalError>
+where
+    C: ContextSideEffects<'ctx>,
+    F: Fn() -> Fut + Send + Sync + 'ctx,
+    Fut: Future<Output = Result<T, Error>> + Send + 'ctx,
+    T: restate_sdk::serde::Serialize + restate_sdk::serde::Deserialize + Send + 'static,
+{
+    ctx.run(|| async move {
+        let mut attempt = 1;
+
+        loop {
+            match f().await.map_err(classify) {
+                Ok(value) => return Ok(value),
+                Err(RetryDecision::Retry { backoff, .. }) if attempt < LOCAL_ATTEMPTS => {
+                    tokio::time::sleep(backoff).await;
+
+                    attempt += 1;
+                }
+                Err(decision) => return Err(decision.into()),
+            }
+        }
+    })
+    .await
+}
+
+/// A copy performed by [`CopyService`].
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyRequest {
+    /// URI of the file or directory to copy.
+    pub source: String,
+
+    /// URI of the destination.
+    pub destination: String,
+
+    #[serde(default)]
+    pub options: CopyOptions,
+}
+
+/// Progress of the copy performed by a [`CopyService`] object.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyStatus {
+    /// Number of files to copy.
+    pub total: u64,
+
+    /// Number of files copied so far.
+    pub completed: u64,
+}
+
+const TOTAL_KEY: &str = "total";
+const COMPLETED_KEY: &str = "completed";
+
+/// A Restate virtual object performing copies between locations resolved by an [`OperatorFactory`].
+///
+/// The files to copy are listed in a journaled step, then every file is copied in its own journaled step,
+/// so a crashed invocation resumes without copying finished files again.
+///
+/// Glob patterns are not interpreted: the source is either a file or a directory.
+///
+/// ```no_run
+/// use opendal_util::DefaultOperatorFactory;
+/// use opendal_util::restate::CopyService;
+/// use restate_sdk::prelude::*;
+///
+/// let endpoint = Endpoint::builder()
+///     .bind(CopyService::new(DefaultOperatorFactory::new()))
+///     .build();
+/// ```
+pub struct CopyService {
+    factory: Arc<dyn OperatorFactory>,
+    error_mapper: ErrorMapper,
+}
+
+impl CopyService {
+    pub fn new(factory: impl OperatorFactory + 'static) -> Self {
+        Self {
+            factory: Arc::new(factory),
+            error_mapper: ErrorMapper::default(),
+        }
+    }
+
+    /// Map errors to Restate errors using an [`ErrorMapper`].
+    pub fn with_error_mapper(mut self, error_mapper: ErrorMapper) -> Self {
+        self.error_mapper = error_mapper;
+        self
+    }
+}
+
+#[restate_sdk::object]
+impl CopyService {
+    /// Copy a file or a directory, returning the number of copied files.
+    #[handler]
+    async fn copy(
+        &self,
+        ctx: ObjectContext<'_>,
+        Json(request): Json<CopyRequest>,
+    ) -> Result<u64, HandlerError> {
+        let ResolvedLocation {
+            operator: source,
+            path: source_path,
+        } = self
+            .factory
+            .resolve(&request.source)
+            .map_err(|err| self.error_mapper.map(err))?;
+        let ResolvedLocation {
+            operator: destination,
+            path: destination_path,
+        } = self
+            .factory
+            .resolve(&request.destination)
+            .map_err(|err| self.error_mapper.map(err))?;
+
+        let Json(files) = ctx
+            .run(|| {
+                let source = source.clone();
+                let error_mapper = self.error_mapper.clone();
+                let (source_path, destination_path) =
+                    (source_path.clone(), destination_path.clone());
+
+                async move {
+                    plan(&source, &source_path, &destination_path, request.options)
+                        .await
+                        .map(Json)
+                        .map_err(|err| error_mapper.map(err))
+                }
+            })
+            .name("plan")
+            .await?;
+
+        let total = files.len() as u64;
+
+        ctx.set(TOTAL_KEY, total);
+        ctx.set(COMPLETED_KEY, 0u64);
+
+        let options = CopyOptions {
+            disable_glob: true,
+            ..request.options
+        };
+
+        for (completed, (file, target)) in files.into_iter().enumerate() {
+            let copier = Copier::new(source.clone(), destination.clone());
+            let error_mapper = self.error_mapper.clone();
+
+            ctx.run(|| async move {
+                copier
+                    .copy_options(file, target, options)
+                    .await
+                    .map_err(|err| error_mapper.map(err.into()))
+            })
+            .name("copy")
+            .await?;
+
+            ctx.set(COMPLETED_KEY, completed as u64 + 1);
+        }
+
+        Ok(total)
+    }
+
+    /// Progress of the last (or current) copy.
+    #[handler]
+    async fn status(&self, ctx: SharedObjectContext<'_>) -> Result<Json<CopyStatus>, HandlerError> {
+        Ok(Json(CopyStatus {
+            total: ctx.get(TOTAL_KEY).await?.unwrap_or_default(),
+            completed: ctx.get(COMPLETED_KEY).await?.unwrap_or_default(),
+        }))
+    }
+}
+
+// List the files to copy as (source, destination) path pairs.
+async fn plan(
+    operator: &Operator,
+    source: &str,
+    destination: &str,
+    options: CopyOptions,
+) -> Result<Vec<(String, String)>, Error> {
+    let source = normalize_path(source);
+    let destination = normalize_path(destination);
+
+    let stat = operator.stat(source.as_str()).await?;
+
+    if stat.is_file() {
+        return Ok(vec![(source.to_string(), destination.to_string())]);
+    }
+
+    let entries = list(
+        operator,
+        source.as_str(),
+        Some(ListOptions {
+            recursive: options.recursive,
+            ..Default::default()
+        }),
+    )
+    .await?;
+
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.metadata().is_file())
+        .map(|entry| {
+            let relative = entry
+                .path()
+                .strip_prefix(source.as_str())
+                .unwrap_or(entry.path());
+
+            (
+                entry.path().to_string(),
+                destination.join(relative).to_string(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let decision = classify(Error::new(ErrorKind::NotFound, "Not found"));
+        assert!(matches!(decision, RetryDecision::Terminal(err) if err.code() == 404));
+
+        let decision = classify(Error::new(ErrorKind::RateLimited, "Slow down").set_temporary());
+        assert!(matches!(
+            decision,
+            RetryDecision::Retry { backoff, .. } if backoff == Duration::from_secs(1)
+        ));
+
+        let decision = classify(Error::new(ErrorKind::Unexpected, "Reset").set_temporary());
+        assert!(matches!(
+            decision,
+            RetryDecision::Retry { backoff, .. } if backoff == Duration::from_millis(100)
+        ));
+    }
+
+    #[test]
+    fn test_error_mapper() {
+        let mapper = ErrorMapper::new()
+            .with_status(ErrorKind::NotFound, 410)
+            .with_default_status(502)
+            .with_context(false);
+
+        let err = Error::new(ErrorKind::NotFound, "Gone").with_context("path", "a.txt");
+        assert!(matches!(
+            mapper.classify(err),
+            RetryDecision::Terminal(err) if err.code() == 410 && err.message() == "Gone"
+        ));
+
+        let err = Error::new(ErrorKind::IsSameFile, "Same file");
+        assert_eq!(mapper.status(err.kind()), 502);
+
+        let err = Error::new(ErrorKind::NotFound, "Not found").with_context("path", "a.txt");
+        assert!(matches!(
+            ErrorMapper::new().classify(err),
+            RetryDecision::Terminal(err) if err.code() == 404 && err.message().contains("path: a.txt")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_plan() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("data/a.txt", "foo").await?;
+        operator.write("data/sub/b.txt", "bar").await?;
+
+        let files = plan(&operator, "/data/a.txt", "/backup/", CopyOptions::default()).await?;
+        assert_eq!(
+            files,
+            vec![("data/a.txt".to_string(), "backup/".to_string())]
+        );
+
+        let files = plan(
+            &operator,
+            "data/",
+            "backup/",
+            CopyOptions {
+                recursive: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(
+            files,
+            vec![
+                ("data/a.txt".to_string(), "backup/a.txt".to_string()),
+                ("data/sub/b.txt".to_string(), "backup/sub/b.txt".to_string()),
+            ]
+        );
+
+        assert!(
+            plan(&operator, "missing", "backup/", CopyOptions::default())
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
 }
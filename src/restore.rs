@@ -0,0 +1,214 @@
+use futures::{TryFutureExt, TryStreamExt};
+use opendal::{EntryMode, Error, ErrorKind, Metadata, Operator};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+
+use crate::copy::{IoErrorExt, open_writer, resolve_root};
+use crate::{Hasher, Manifest, ManifestEntry};
+
+/// Options for controlling [`restore`] behavior.
+#[derive(Debug, Copy, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RestoreOptions {
+    /// Whether to verify the content of every file against the digest recorded in the manifest.
+    ///
+    /// Files that fail verification are not written to the destination.
+    pub verify: bool,
+}
+
+/// Outcome of a [`restore`] call.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreReport {
+    /// Files restored successfully.
+    pub restored: Vec<String>,
+
+    /// Files referenced by the manifest but missing from the source.
+    pub missing: Vec<String>,
+
+    /// Files whose content does not match the manifest.
+    pub corrupt: Vec<String>,
+}
+
+impl RestoreReport {
+    /// Returns `true` if every file referenced by the manifest was restored.
+    pub fn is_success(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+}
+
+/// Restore exactly the files referenced by a [`Manifest`] from a source tree to a destination.
+///
+/// Paths in the manifest are resolved relative to the source and destination paths:
+/// paths escaping them (e.g. `../other.txt`) fail the restore with [`ErrorKind::PermissionDenied`].
+/// Missing and corrupt files are reported rather than aborting the restore;
+/// any other error does abort it.
+pub async fn restore(
+    manifest: (Operator, String),
+    source: (Operator, String),
+    destination: (Operator, String),
+    options: RestoreOptions,
+) -> Result<RestoreReport, Error> {
+    let (manifest_operator, manifest_path) = manifest;
+    let manifest = Manifest::read(&manifest_operator, &manifest_path).await?;
+
+    let (source, source_root) = source;
+    let (destination, destination_root) = destination;
+
+    let mut report = RestoreReport::default();
+
+    for (path, entry) in &manifest.entries {
+        let source_path = join(&source_root, path)?;
+        let destination_path = join(&destination_root, path)?;
+
+        let outcome = restore_file(
+            &source,
+            &source_path,
+            &destination,
+            &destination_path,
+            entry,
+            options.verify,
+        )
+        .await;
+
+        match outcome {
+            Ok(true) => report.restored.push(path.clone()),
+            Ok(false) => report.corrupt.push(path.clone()),
+            Err(err) if err.kind() == ErrorKind::NotFound => report.missing.push(path.clone()),
+            Err(err) => return Err(err.with_context("path", path)),
+        }
+    }
+
+    Ok(report)
+}
+
+// Returns `false` if the content does not match the manifest entry.
+async fn restore_file(
+    source: &Operator,
+    source_path: &str,
+    destination: &Operator,
+    destination_path: &str,
+    entry: &ManifestEntry,
+    verify: bool,
+) -> Result<bool, Error> {
+    let reader = source.reader(source_path).await?;
+    let mut stream = reader.into_bytes_stream(..).await?;
+
+    let mut meta = Metadata::new(EntryMode::FILE);
+    if let Some(content_type) = &entry.content_type {
+        meta = meta.with_content_type(content_type.clone());
+    }
+
+    let mut writer = open_writer(destination, destination_path, &meta).await?;
+    let mut hasher = verify.then(|| Hasher::new(entry.digest.algorithm));
+    let mut size = 0;
+
+    while let Some(chunk) = stream
+        .try_next()
+        .map_err(IoErrorExt::into_opendal_error)
+        .await?
+    {
+        if let Some(hasher) = &mut hasher {
+            hasher.update(&chunk);
+        }
+
+        size += chunk.len() as u64;
+        writer.write(chunk).await?;
+    }
+
+    if let Some(hasher) = hasher
+        && (size != entry.size || hasher.finalize() != entry.digest)
+    {
+        let _ = writer.abort().await;
+
+        return Ok(false);
+    }
+
+    writer.close().await?;
+
+    Ok(true)
+}
+
+fn join(root: &str, path: &str) -> Result<String, Error> {
+    resolve_root(root, path).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_restore() -> Result<(), Error> {
+        let backup = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        backup.write("snapshot/a.txt", "foo").await?;
+        backup.write("snapshot/nested/b.txt", "bar").await?;
+        backup.write("snapshot/c.txt", "baz").await?;
+
+        Manifest::capture(&backup, "snapshot")
+            .await?
+            .write(&backup, "manifest.json")
+            .await?;
+
+        // Not part of the manifest
+        backup.write("snapshot/extra.txt", "extra").await?;
+
+        // Break the backup
+        backup.write("snapshot/nested/b.txt", "qux").await?;
+        backup.delete("snapshot/c.txt").await?;
+
+        let report = restore(
+            (backup.clone(), "manifest.json".to_string()),
+            (backup.clone(), "snapshot/".to_string()),
+            (destination.clone(), "restored".to_string()),
+            RestoreOptions { verify: true },
+        )
+        .await?;
+
+        assert!(!report.is_success());
+        assert_eq!(report.restored, vec!["a.txt"]);
+        assert_eq!(report.missing, vec!["c.txt"]);
+        assert_eq!(report.corrupt, vec!["nested/b.txt"]);
+
+        let buffer = destination.read("restored/a.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        assert!(!destination.exists("restored/nested/b.txt").await?);
+        assert!(!destination.exists("restored/extra.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_escaping_path() -> Result<(), Error> {
+        let backup = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        backup.write("snapshot/a.txt", "foo").await?;
+        backup.write("secret.txt", "secret").await?;
+
+        let mut manifest = Manifest::capture(&backup, "snapshot").await?;
+        let entry = manifest.entries["a.txt"].clone();
+        manifest.entries.insert("../secret.txt".to_string(), entry);
+        manifest.write(&backup, "manifest.json").await?;
+
+        let err = restore(
+            (backup.clone(), "manifest.json".to_string()),
+            (backup.clone(), "snapshot/".to_string()),
+            (destination.clone(), "restored".to_string()),
+            RestoreOptions::default(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!destination.exists("secret.txt").await?);
+
+        Ok(())
+    }
+}
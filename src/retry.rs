@@ -0,0 +1,229 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use opendal::Error;
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry;
+
+/// Decides whether an error is worth retrying.
+#[derive(Clone)]
+pub struct Retryable(Arc<dyn Fn(&Error) -> bool + Send + Sync>);
+
+impl Retryable {
+    pub fn new(f: impl Fn(&Error) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl Default for Retryable {
+    /// Retries errors that OpenDAL marks as temporary.
+    fn default() -> Self {
+        Self::new(Error::is_temporary)
+    }
+}
+
+impl fmt::Debug for Retryable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Retryable")
+    }
+}
+
+/// Policy for retrying fallible operations with exponential backoff.
+///
+/// The same policy is used by [`Copier`](crate::Copier) and can be reused for arbitrary operator calls:
+///
+/// ```no_run
+/// # async fn example(operator: opendal::Operator) -> Result<(), opendal::Error> {
+/// use opendal_util::RetryPolicy;
+///
+/// let policy = RetryPolicy::default();
+/// let buffer = policy.retry(|| operator.read("path/to/file.txt")).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: usize,
+
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+
+    /// Upper bound for the delay between attempts.
+    pub max_delay: Duration,
+
+    /// Factor the delay is multiplied by after every attempt.
+    pub factor: f32,
+
+    /// Whether to randomize delays (between half and the full delay) to avoid thundering herds.
+    pub jitter: bool,
+
+    /// Decides which errors are retried.
+    ///
+    /// Defaults to errors marked as temporary by OpenDAL.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub retryable: Retryable,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            factor: 2.0,
+            jitter: true,
+            retryable: Retryable::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_retryable(mut self, f: impl Fn(&Error) -> bool + Send + Sync + 'static) -> Self {
+        self.retryable = Retryable::new(f);
+        self
+    }
+
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        (self.retryable.0)(err)
+    }
+
+    /// Delay before the given retry (starting from 1 for the first retry).
+    pub fn delay(&self, retry: usize) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as usize) as i32;
+
+        let multiplier = f64::from(self.factor).powi(exponent).max(1.0);
+
+        // Large exponents overflow the delay: cap it at the maximum
+        let delay = Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * multiplier)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+
+        if self.jitter {
+            let half = delay / 2;
+
+            return half + half.mul_f64(fastrand::f64());
+        }
+
+        delay
+    }
+
+    /// Run an operation, retrying it according to the policy.
+    pub async fn retry<T, F, Fut>(&self, operation: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.retry_by(operation, |err| err).await
+    }
+
+    // Run an operation failing with an error wrapping an OpenDAL error, retrying it according to the policy.
+    pub(crate) async fn retry_by<T, E, F, Fut>(
+        &self,
+        mut operation: F,
+        error: impl Fn(&E) -> &Error,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < self.max_attempts && self.is_retryable(error(&err)) => {
+                    let delay = self.delay(attempt);
+
+                    telemetry::record_retry(attempt, delay, error(&err));
+
+                    tokio::time::sleep(delay).await;
+
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use opendal::ErrorKind;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry() {
+        let attempts = AtomicUsize::new(0);
+
+        let result = RetryPolicy::default()
+            .retry(|| async {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(Error::new(ErrorKind::Unexpected, "flaky").set_temporary());
+                }
+
+                Ok("done")
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_permanent_error() {
+        let attempts = AtomicUsize::new(0);
+
+        let result: Result<(), Error> = RetryPolicy::default()
+            .retry(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+
+                Err(Error::new(ErrorKind::NotFound, "not found"))
+            })
+            .await;
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            jitter: false,
+            max_delay: Duration::from_millis(300),
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay(70), policy.max_delay);
+        assert_eq!(policy.delay(usize::MAX), policy.max_delay);
+    }
+}
@@ -0,0 +1,753 @@
+use std::convert::Infallible;
+use std::future::Future;
+use std::io;
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::TryStreamExt;
+use http::header::{self, HeaderValue};
+use http::request::Parts;
+use http::{Method, Request, Response, StatusCode};
+use http_body::Frame;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Empty, Full, StreamBody};
+use opendal::{EntryMode, Error, ErrorKind, Metadata, Operator};
+use percent_encoding::percent_decode_str;
+use tower_service::Service;
+
+use crate::copy::normalize_path;
+use crate::list;
+
+/// Response body of [`ServeOperator`].
+pub type ServeBody = UnsyncBoxBody<Bytes, io::Error>;
+
+/// A [`tower_service::Service`] serving `GET` and `HEAD` requests from an [`Operator`].
+///
+/// The request path is resolved relative to the configured prefix (`..` segments cannot escape it).
+/// Responses carry the content type, length, ETag and last modification time of the object,
+/// and `If-None-Match` requests are answered with `304 Not Modified` when the ETag matches.
+///
+/// Directory listings are rendered as JSON instead of HTML when requested with `Accept: application/json`
+/// (requires the `serde` feature).
+///
+/// The service can be mounted in an axum router directly
+/// (see `router` with the `axum` feature for a router that also handles writes):
+///
+/// ```ignore
+/// let app = axum::Router::new().nest_service("/files", ServeOperator::new(operator));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServeOperator {
+    operator: Operator,
+    prefix: String,
+    list_directories: bool,
+}
+
+impl ServeOperator {
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            prefix: String::new(),
+            list_directories: false,
+        }
+    }
+
+    /// Serve objects under `prefix` instead of the root of the operator.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Render an HTML listing for directory requests (disabled by default).
+    pub fn with_directory_listing(mut self, list_directories: bool) -> Self {
+        self.list_directories = list_directories;
+        self
+    }
+
+    async fn serve<B>(self, request: Request<B>) -> Response<ServeBody> {
+        // The body is never used
+        let (request, _) = request.into_parts();
+
+        if request.method != Method::GET && request.method != Method::HEAD {
+            return Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .header(header::ALLOW, "GET, HEAD")
+                .body(empty())
+                .expect("valid response");
+        }
+
+        let request_path = percent_decode_str(request.uri.path()).decode_utf8_lossy();
+        let path = self.resolve(&request_path);
+
+        let result = if path.is_empty() || path.ends_with('/') {
+            self.serve_dir(&request, &path).await
+        } else {
+            self.serve_file(&request, &path).await
+        };
+
+        result.unwrap_or_else(error_response)
+    }
+
+    fn resolve(&self, request_path: &str) -> String {
+        let path = normalize_path(&format!("/{}", request_path));
+        let path = path.as_str().trim_start_matches('/');
+        let prefix = self.prefix.trim_matches('/');
+
+        if prefix.is_empty() {
+            return path.to_string();
+        }
+
+        if path.is_empty() {
+            return format!("{}/", prefix);
+        }
+
+        format!("{}/{}", prefix, path)
+    }
+
+    async fn serve_file(&self, request: &Parts, path: &str) -> Result<Response<ServeBody>, Error> {
+        let meta = match self.operator.stat(path).await {
+            Ok(meta) if meta.mode() != EntryMode::DIR => meta,
+            Ok(_) if self.list_directories => return Ok(redirect_to_dir(request)),
+            Err(err) if err.kind() == ErrorKind::NotFound && self.list_directories => {
+                // Most services only know about the directory with a trailing slash
+                match self.operator.stat(&format!("{}/", path)).await {
+                    Ok(meta) if meta.is_dir() => return Ok(redirect_to_dir(request)),
+                    _ => return Err(err),
+                }
+            }
+            Ok(_) => return Err(Error::new(ErrorKind::NotFound, "Not found")),
+            Err(err) => return Err(err),
+        };
+
+        let size = meta.content_length();
+
+        let mut response = Response::builder()
+            .header(
+                header::CONTENT_TYPE,
+                meta.content_type().unwrap_or("application/octet-stream"),
+            )
+            .header(header::ACCEPT_RANGES, "bytes");
+
+        for (name, value) in cache_headers(&meta) {
+            response = response.header(name, value);
+        }
+
+        if let (Some(etag), Some(if_none_match)) =
+            (meta.etag(), request.headers.get(header::IF_NONE_MATCH))
+            && if_none_match
+                .to_str()
+                .is_ok_and(|value| value.split(',').any(|tag| tag.trim() == etag))
+        {
+            return Ok(response
+                .status(StatusCode::NOT_MODIFIED)
+                .header(header::CONTENT_LENGTH, 0)
+                .body(empty())
+                .expect("valid response"));
+        }
+
+        let range = match byte_range(request, &meta) {
+            ByteRange::Full => 0..size,
+            ByteRange::Partial(range) => {
+                response = response.status(StatusCode::PARTIAL_CONTENT).header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end - 1, size),
+                );
+
+                range
+            }
+            ByteRange::Unsatisfiable => {
+                return Ok(response
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                    .header(header::CONTENT_LENGTH, 0)
+                    .body(empty())
+                    .expect("valid response"));
+            }
+        };
+
+        response = response.header(header::CONTENT_LENGTH, range.end - range.start);
+
+        if request.method == Method::HEAD || range.is_empty() {
+            return Ok(response.body(empty()).expect("valid response"));
+        }
+
+        let stream = self
+            .operator
+            .reader(path)
+            .await?
+            .into_bytes_stream(range)
+            .await?
+            .map_ok(Frame::data);
+
+        Ok(response
+            .body(StreamBody::new(stream).boxed_unsync())
+            .expect("valid response"))
+    }
+
+    async fn serve_dir(&self, request: &Parts, path: &str) -> Result<Response<ServeBody>, Error> {
+        if !self.list_directories {
+            return Err(Error::new(ErrorKind::NotFound, "Not found"));
+        }
+
+        let entries = list::list(&self.operator, path, None).await?;
+
+        let mut names: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.path().strip_prefix(path).unwrap_or(entry.path()))
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.sort();
+
+        if names.is_empty() && !path.is_empty() && self.operator.stat(path).await.is_err() {
+            return Err(Error::new(ErrorKind::NotFound, "Not found"));
+        }
+
+        #[cfg(feature = "serde")]
+        if accepts_json(request) {
+            let listing: Vec<_> = names
+                .iter()
+                .map(|name| serde_json::json!({ "name": name, "dir": name.ends_with('/') }))
+                .collect();
+
+            let json = serde_json::Value::Array(listing).to_string();
+
+            return Ok(listing_response(request, "application/json", json));
+        }
+
+        let title = escape_html(request.uri.path());
+
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head><title>Index of {title}</title></head>\n<body>\n<h1>Index of {title}</h1>\n<ul>\n"
+        );
+
+        for name in names {
+            let name = escape_html(name);
+
+            html.push_str(&format!("<li><a href=\"{name}\">{name}</a></li>\n"));
+        }
+
+        html.push_str("</ul>\n</body>\n</html>\n");
+
+        Ok(listing_response(request, "text/html; charset=utf-8", html))
+    }
+}
+
+#[cfg(feature = "axum")]
+impl ServeOperator {
+    /// An axum router serving `GET` and `HEAD` requests like this service,
+    /// and additionally storing (`PUT`) and deleting (`DELETE`) objects.
+    ///
+    /// `PUT` requests to a path with a trailing slash create a directory.
+    pub fn into_router(self) -> axum::Router {
+        axum::Router::new().fallback(move |request: axum::extract::Request| {
+            let this = self.clone();
+
+            async move {
+                let response = match request.method().clone() {
+                    Method::PUT => this.put(request).await,
+                    Method::DELETE => this.delete(request).await,
+                    _ => this.serve(request).await,
+                };
+
+                response.map(axum::body::Body::new)
+            }
+        })
+    }
+
+    async fn put(&self, request: axum::extract::Request) -> Response<ServeBody> {
+        let (request, body) = request.into_parts();
+
+        let request_path = percent_decode_str(request.uri.path()).decode_utf8_lossy();
+        let path = self.resolve(&request_path);
+
+        let result = if path.is_empty() || path.ends_with('/') {
+            self.operator.create_dir(&path).await
+        } else {
+            self.write(&request, &path, body).await
+        };
+
+        result
+            .map(|_| {
+                Response::builder()
+                    .status(StatusCode::CREATED)
+                    .body(empty())
+                    .expect("valid response")
+            })
+            .unwrap_or_else(error_response)
+    }
+
+    async fn write(
+        &self,
+        request: &Parts,
+        path: &str,
+        body: axum::body::Body,
+    ) -> Result<(), Error> {
+        let mut writer = self.operator.writer_with(path);
+
+        if let Some(content_type) = request
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+        {
+            writer = writer.content_type(content_type);
+        }
+
+        let mut writer = writer.await?;
+
+        let mut stream = body.into_data_stream();
+
+        while let Some(chunk) = stream.try_next().await.map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Failed to read request body").set_source(err)
+        })? {
+            writer.write(chunk).await?;
+        }
+
+        writer.close().await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, request: axum::extract::Request) -> Response<ServeBody> {
+        let request_path = percent_decode_str(request.uri().path()).decode_utf8_lossy();
+        let path = self.resolve(&request_path);
+
+        // Never delete the served prefix itself
+        if path.trim_end_matches('/') == self.prefix.trim_matches('/') {
+            return error_response(Error::new(ErrorKind::PermissionDenied, "Forbidden"));
+        }
+
+        match self.operator.delete(&path).await {
+            Ok(()) => Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .body(empty())
+                .expect("valid response"),
+            Err(err) => error_response(err),
+        }
+    }
+}
+
+/// An axum router serving the objects of an operator under `prefix`,
+/// including directory listings (see [`ServeOperator::into_router`]).
+#[cfg(feature = "axum")]
+pub fn router(operator: Operator, prefix: impl Into<String>) -> axum::Router {
+    ServeOperator::new(operator)
+        .with_prefix(prefix)
+        .with_directory_listing(true)
+        .into_router()
+}
+
+impl<B> Service<Request<B>> for ServeOperator
+where
+    B: Send + 'static,
+{
+    type Response = Response<ServeBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let this = self.clone();
+
+        Box::pin(async move { Ok(this.serve(request).await) })
+    }
+}
+
+enum ByteRange {
+    Full,
+    Partial(Range<u64>),
+    Unsatisfiable,
+}
+
+// Resolves the `Range` header of a request against the object.
+//
+// Only single ranges are supported: requests for multiple ranges get the full object,
+// which is an acceptable response according to RFC 9110.
+fn byte_range(request: &Parts, meta: &Metadata) -> ByteRange {
+    let Some(range) = request
+        .headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return ByteRange::Full;
+    };
+
+    if let Some(if_range) = request.headers.get(header::IF_RANGE)
+        && !if_range_matches(if_range, meta)
+    {
+        return ByteRange::Full;
+    }
+
+    let Some(spec) = range.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+
+    let size = meta.content_length();
+
+    let range = match (start.trim(), end.trim()) {
+        // Suffix range: the last N bytes
+        ("", suffix) => match suffix.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(suffix) => size.saturating_sub(suffix)..size,
+            Err(_) => return ByteRange::Full,
+        },
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) => start..size,
+            Err(_) => return ByteRange::Full,
+        },
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => start..(end + 1).min(size),
+            _ => return ByteRange::Full,
+        },
+    };
+
+    if range.start >= size {
+        return ByteRange::Unsatisfiable;
+    }
+
+    ByteRange::Partial(range)
+}
+
+// `If-Range` holds either a strong ETag or an HTTP date that must match exactly.
+fn if_range_matches(if_range: &HeaderValue, meta: &Metadata) -> bool {
+    let Ok(if_range) = if_range.to_str() else {
+        return false;
+    };
+
+    let if_range = if_range.trim();
+
+    if if_range.starts_with('"') {
+        return meta.etag() == Some(if_range);
+    }
+
+    if if_range.starts_with("W/") {
+        return false;
+    }
+
+    meta.last_modified()
+        .is_some_and(|ts| ts.format_http_date() == if_range)
+}
+
+fn cache_headers(meta: &Metadata) -> Vec<(header::HeaderName, HeaderValue)> {
+    let mut headers = Vec::new();
+
+    if let Some(etag) = meta
+        .etag()
+        .and_then(|etag| HeaderValue::from_str(etag).ok())
+    {
+        headers.push((header::ETAG, etag));
+    }
+
+    if let Some(last_modified) = meta
+        .last_modified()
+        .and_then(|ts| HeaderValue::from_str(&ts.format_http_date()).ok())
+    {
+        headers.push((header::LAST_MODIFIED, last_modified));
+    }
+
+    headers
+}
+
+#[cfg(feature = "serde")]
+fn accepts_json(request: &Parts) -> bool {
+    request
+        .headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+fn listing_response(request: &Parts, content_type: &str, content: String) -> Response<ServeBody> {
+    let length = content.len();
+
+    let body = if request.method == Method::HEAD {
+        empty()
+    } else {
+        full(content)
+    };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .body(body)
+        .expect("valid response")
+}
+
+// Redirect to the path with a trailing slash so that relative links in the listing work
+fn redirect_to_dir(request: &Parts) -> Response<ServeBody> {
+    Response::builder()
+        .status(StatusCode::MOVED_PERMANENTLY)
+        .header(header::LOCATION, format!("{}/", request.uri.path()))
+        .body(empty())
+        .expect("valid response")
+}
+
+fn error_response(err: Error) -> Response<ServeBody> {
+    let status = match err.kind() {
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+
+    let message = status.canonical_reason().unwrap_or("Error");
+
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(full(message))
+        .expect("valid response")
+}
+
+fn empty() -> ServeBody {
+    Empty::new().map_err(|never| match never {}).boxed_unsync()
+}
+
+fn full(content: impl Into<Bytes>) -> ServeBody {
+    Full::new(content.into())
+        .map_err(|never| match never {})
+        .boxed_unsync()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    async fn call(service: &mut ServeOperator, request: Request<()>) -> (StatusCode, Bytes) {
+        let response = service.call(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        (status, body)
+    }
+
+    fn get(uri: &str) -> Request<()> {
+        Request::get(uri).body(()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serve_file() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write_with("public/hello world.txt", "foo")
+            .content_type("text/plain")
+            .await?;
+        operator.write("secret.txt", "bar").await?;
+
+        let mut service = ServeOperator::new(operator).with_prefix("public");
+
+        let response = service.call(get("/hello%20world.txt")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "3");
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "foo");
+
+        // HEAD has no body
+        let request = Request::head("/hello%20world.txt").body(()).unwrap();
+        let (status, body) = call(&mut service, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+
+        // Cannot escape the prefix
+        let (status, _) = call(&mut service, get("/../secret.txt")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = call(&mut service, get("/missing.txt")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let request = Request::post("/hello%20world.txt").body(()).unwrap();
+        let (status, _) = call(&mut service, request).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serve_range() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("video.mp4", "0123456789").await?;
+
+        let mut service = ServeOperator::new(operator);
+
+        let range = |value: &str| {
+            Request::get("/video.mp4")
+                .header(header::RANGE, value)
+                .body(())
+                .unwrap()
+        };
+
+        let response = service.call(range("bytes=2-4")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-4/10");
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "3");
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "234");
+
+        let (status, body) = call(&mut service, range("bytes=7-")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body, "789");
+
+        let (status, body) = call(&mut service, range("bytes=-2")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body, "89");
+
+        // The end is clamped to the size of the object
+        let (status, body) = call(&mut service, range("bytes=8-100")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body, "89");
+
+        let response = service.call(range("bytes=10-")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */10");
+
+        // Multiple ranges are served as the full object
+        let (status, body) = call(&mut service, range("bytes=0-1,4-5")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "0123456789");
+
+        // The object has no ETag, so the validator cannot match
+        let request = Request::get("/video.mp4")
+            .header(header::RANGE, "bytes=0-1")
+            .header(header::IF_RANGE, "\"abc\"")
+            .body(())
+            .unwrap();
+        let (status, body) = call(&mut service, request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "0123456789");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serve_directory_listing() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("dir/a.txt", "a").await?;
+        operator.write("dir/sub/b.txt", "b").await?;
+
+        let mut service = ServeOperator::new(operator.clone());
+
+        // Listing is disabled by default
+        let (status, _) = call(&mut service, get("/dir/")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let mut service = service.with_directory_listing(true);
+
+        let (status, body) = call(&mut service, get("/dir/")).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("<a href=\"a.txt\">a.txt</a>"));
+        assert!(body.contains("<a href=\"sub/\">sub/</a>"));
+
+        let response = service.call(get("/dir")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()[header::LOCATION], "/dir/");
+
+        let (status, _) = call(&mut service, get("/missing")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = call(&mut service, get("/missing/")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_serve_json_listing() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("dir/a.txt", "a").await?;
+        operator.write("dir/sub/b.txt", "b").await?;
+
+        let mut service = ServeOperator::new(operator).with_directory_listing(true);
+
+        let request = Request::get("/dir/")
+            .header(header::ACCEPT, "application/json")
+            .body(())
+            .unwrap();
+        let (status, body) = call(&mut service, request).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let listing: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            listing,
+            serde_json::json!([
+                { "name": "a.txt", "dir": false },
+                { "name": "sub/", "dir": true },
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn test_router() -> Result<(), Error> {
+        use tower_service::Service as _;
+
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let mut router = router(operator.clone(), "public");
+
+        let request = Request::put("/a.txt")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(axum::body::Body::from("foo"))
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let meta = operator.stat("public/a.txt").await?;
+        assert_eq!(meta.content_type(), Some("text/plain"));
+
+        let request = Request::get("/a.txt")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "foo");
+
+        let request = Request::delete("/a.txt")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(!operator.exists("public/a.txt").await?);
+
+        let request = Request::delete("/")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        Ok(())
+    }
+}
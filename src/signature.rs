@@ -0,0 +1,89 @@
+use ed25519_dalek::{SIGNATURE_LENGTH, Signature, Signer, Verifier};
+use opendal::{Error, ErrorKind, Operator};
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::{Digest, HashAlgorithm, checksum};
+
+/// Path of the detached signature for `path` (`<path>.sig`).
+pub fn signature_path(path: &str) -> String {
+    format!("{}.sig", path)
+}
+
+/// Sign an object and store the detached signature alongside it (see [`signature_path`]).
+///
+/// The signed message is the SHA-256 [`Digest`] of the object in its textual form (`sha256:<hex>`),
+/// so signing and verifying large objects only requires streaming them once.
+/// Checksum manifests can be signed the same way as any other object.
+pub async fn sign(operator: &Operator, path: &str, key: &SigningKey) -> Result<Signature, Error> {
+    let digest = checksum(operator, path, HashAlgorithm::Sha256).await?;
+    let signature = key.sign(message(&digest).as_bytes());
+
+    operator
+        .write(&signature_path(path), signature.to_bytes().to_vec())
+        .await?;
+
+    Ok(signature)
+}
+
+/// Verify an object against the detached signature stored alongside it.
+pub async fn verify(operator: &Operator, path: &str, key: &VerifyingKey) -> Result<(), Error> {
+    let bytes = operator.read(&signature_path(path)).await?.to_vec();
+
+    let bytes: [u8; SIGNATURE_LENGTH] = bytes.try_into().map_err(|_| {
+        Error::new(ErrorKind::Unexpected, "Invalid signature").with_context("path", path)
+    })?;
+
+    let digest = checksum(operator, path, HashAlgorithm::Sha256).await?;
+
+    key.verify(message(&digest).as_bytes(), &Signature::from_bytes(&bytes))
+        .map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Signature verification failed")
+                .with_context("path", path)
+                .set_source(err)
+        })
+}
+
+fn message(digest: &Digest) -> String {
+    digest.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_verify() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let key = SigningKey::from_bytes(&[7; 32]);
+
+        operator.write("release/artifact.tar.gz", "foo").await?;
+
+        sign(&operator, "release/artifact.tar.gz", &key).await?;
+        assert!(operator.exists("release/artifact.tar.gz.sig").await?);
+
+        verify(&operator, "release/artifact.tar.gz", &key.verifying_key()).await?;
+
+        // Tampering with the object invalidates the signature
+        operator.write("release/artifact.tar.gz", "bar").await?;
+        assert!(
+            verify(&operator, "release/artifact.tar.gz", &key.verifying_key())
+                .await
+                .is_err()
+        );
+
+        // So does verifying with a different key
+        sign(&operator, "release/artifact.tar.gz", &key).await?;
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert!(
+            verify(&operator, "release/artifact.tar.gz", &other.verifying_key())
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
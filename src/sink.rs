@@ -0,0 +1,157 @@
+use futures::future::BoxFuture;
+use opendal::{Buffer, Error, Metadata, Operator, Writer};
+
+use crate::copy::{normalize_path, open_writer};
+
+/// Destination of the files written by a [`Copier`](crate::Copier) (see [`Copier::with_sink`](crate::Copier::with_sink)).
+///
+/// Sinks receive the destination path of every file, so the traversal of the copier can feed
+/// non-operator destinations, like a tar stream, a zip builder or an upload session.
+pub trait Sink: Send + Sync {
+    /// Open a writer for a file (with the metadata of the source file).
+    fn writer<'a>(
+        &'a self,
+        path: &'a str,
+        meta: &'a Metadata,
+    ) -> BoxFuture<'a, Result<Box<dyn SinkWriter>, Error>>;
+}
+
+/// Writes a single file to a [`Sink`].
+///
+/// Chunks are written in order, then the writer is either closed or aborted (when the copy fails).
+pub trait SinkWriter: Send {
+    fn write(&mut self, chunk: Buffer) -> BoxFuture<'_, Result<(), Error>>;
+
+    fn close(&mut self) -> BoxFuture<'_, Result<(), Error>>;
+
+    fn abort(&mut self) -> BoxFuture<'_, Result<(), Error>>;
+}
+
+/// Writes files under a prefix of an operator.
+impl Sink for (Operator, String) {
+    fn writer<'a>(
+        &'a self,
+        path: &'a str,
+        meta: &'a Metadata,
+    ) -> BoxFuture<'a, Result<Box<dyn SinkWriter>, Error>> {
+        Box::pin(async move {
+            let (operator, prefix) = self;
+
+            let path = normalize_path(&format!("{}/{}", prefix, path));
+
+            let writer = open_writer(operator, path.as_str(), meta).await?;
+
+            Ok(Box::new(writer) as Box<dyn SinkWriter>)
+        })
+    }
+}
+
+impl SinkWriter for Writer {
+    fn write(&mut self, chunk: Buffer) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(Writer::write(self, chunk))
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(async move { Writer::close(self).await.map(|_| ()) })
+    }
+
+    fn abort(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+        Box::pin(Writer::abort(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    use opendal::services::Memory;
+
+    use super::*;
+    use crate::Copier;
+
+    // Collects closed files in memory (like an archive builder would).
+    #[derive(Default)]
+    struct Collect(Arc<Mutex<BTreeMap<String, Vec<u8>>>>);
+
+    struct CollectWriter {
+        path: String,
+        content: Vec<u8>,
+        files: Arc<Mutex<BTreeMap<String, Vec<u8>>>>,
+    }
+
+    impl Sink for Collect {
+        fn writer<'a>(
+            &'a self,
+            path: &'a str,
+            _: &'a Metadata,
+        ) -> BoxFuture<'a, Result<Box<dyn SinkWriter>, Error>> {
+            Box::pin(async move {
+                Ok(Box::new(CollectWriter {
+                    path: path.to_string(),
+                    content: Vec::new(),
+                    files: self.0.clone(),
+                }) as Box<dyn SinkWriter>)
+            })
+        }
+    }
+
+    impl SinkWriter for CollectWriter {
+        fn write(&mut self, chunk: Buffer) -> BoxFuture<'_, Result<(), Error>> {
+            self.content.extend(chunk.to_vec());
+
+            Box::pin(async { Ok(()) })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            let content = std::mem::take(&mut self.content);
+
+            self.files
+                .lock()
+                .unwrap()
+                .insert(self.path.clone(), content);
+
+            Box::pin(async { Ok(()) })
+        }
+
+        fn abort(&mut self) -> BoxFuture<'_, Result<(), Error>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_copy_to_sink() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "a").await?;
+        source.write("data/sub/b.txt", "b").await?;
+        source.write("data/c.bin", "c").await?;
+
+        let sink = Collect::default();
+        let files = sink.0.clone();
+
+        Copier::new(source.clone(), destination.clone())
+            .with_sink(sink)
+            .copy("data/**/*.txt", "archive/")
+            .await?;
+
+        assert_eq!(
+            *files.lock().unwrap(),
+            BTreeMap::from([
+                ("archive/a.txt".to_string(), b"a".to_vec()),
+                ("archive/sub/b.txt".to_string(), b"b".to_vec()),
+            ])
+        );
+        assert!(destination.list("").await?.is_empty());
+
+        Copier::new(source, destination.clone())
+            .with_sink((destination.clone(), "prefix".to_string()))
+            .copy("data/c.bin", "out/c.bin")
+            .await?;
+
+        assert_eq!(destination.read("prefix/out/c.bin").await?.to_vec(), b"c");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+use futures::TryStreamExt;
+use opendal::raw::Timestamp;
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_dir;
+use crate::{Copier, CopyOptions, list};
+
+/// Options for [`snapshot`] and [`restore`].
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SnapshotOptions {
+    /// Copy files server-side when the service supports it (much cheaper on most object stores,
+    /// some of them deduplicating the data).
+    ///
+    /// Files are streamed through the client otherwise.
+    pub server_side_copy: bool,
+}
+
+/// A point-in-time copy of a prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct Snapshot {
+    /// ID of the snapshot: the time it was taken (e.g. `2024-06-01T00:00:00Z`).
+    pub id: String,
+
+    /// Path of the snapshot.
+    pub path: String,
+
+    /// Number of files in the snapshot (only known when the snapshot is taken).
+    pub files: Option<u64>,
+}
+
+/// Copy everything under a prefix to `<snapshots_prefix>/<timestamp>/` (e.g. `snapshots/2024-06-01T00:00:00Z/`).
+///
+/// The snapshots prefix can't be inside the prefix. Fails if a snapshot with the same timestamp exists.
+pub async fn snapshot(
+    operator: &Operator,
+    prefix: &str,
+    snapshots_prefix: &str,
+    options: SnapshotOptions,
+) -> Result<Snapshot, Error> {
+    let prefix = normalize_dir(prefix);
+    let snapshots_prefix = normalize_dir(snapshots_prefix);
+
+    if snapshots_prefix.starts_with(&prefix) {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "Snapshots prefix cannot be inside the snapshotted prefix",
+        )
+        .with_context("prefix", &prefix)
+        .with_context("snapshots_prefix", &snapshots_prefix));
+    }
+
+    let id = Timestamp::now()
+        .into_inner()
+        .strftime("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+
+    let path = format!("{}{}/", snapshots_prefix, id);
+
+    if !operator.list(&path).await?.is_empty() {
+        return Err(
+            Error::new(ErrorKind::AlreadyExists, "Snapshot already exists")
+                .with_context("path", &path),
+        );
+    }
+
+    let files = copy_tree(operator, &prefix, &path, options).await?;
+
+    Ok(Snapshot {
+        id,
+        path,
+        files: Some(files),
+    })
+}
+
+/// List the snapshots under a prefix, oldest first.
+pub async fn list_snapshots(
+    operator: &Operator,
+    snapshots_prefix: &str,
+) -> Result<Vec<Snapshot>, Error> {
+    let snapshots_prefix = normalize_dir(snapshots_prefix);
+
+    let mut snapshots: Vec<Snapshot> = operator
+        .list(&snapshots_prefix)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.metadata().is_dir() && entry.path() != snapshots_prefix)
+        .map(|entry| Snapshot {
+            id: entry.name().trim_end_matches('/').to_string(),
+            path: entry.path().to_string(),
+            files: None,
+        })
+        .collect();
+
+    // Timestamps sort chronologically
+    snapshots.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(snapshots)
+}
+
+/// Copy the files of a snapshot into a target prefix, returning the number of files restored.
+///
+/// Existing files of the target are overwritten, other files are left alone.
+pub async fn restore(
+    operator: &Operator,
+    snapshots_prefix: &str,
+    snapshot_id: &str,
+    target: &str,
+    options: SnapshotOptions,
+) -> Result<u64, Error> {
+    let path = format!(
+        "{}{}/",
+        normalize_dir(snapshots_prefix),
+        snapshot_id.trim_matches('/')
+    );
+
+    if operator.list(&path).await?.is_empty() {
+        return Err(
+            Error::new(ErrorKind::NotFound, "Snapshot not found").with_context("path", &path)
+        );
+    }
+
+    copy_tree(operator, &path, &normalize_dir(target), options).await
+}
+
+async fn copy_tree(
+    operator: &Operator,
+    from: &str,
+    to: &str,
+    options: SnapshotOptions,
+) -> Result<u64, Error> {
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let files: Vec<String> = list::lister(operator, from, Some(list_options))
+        .await?
+        .try_filter(|entry| futures::future::ready(entry.metadata().is_file()))
+        .map_ok(|entry| entry.path().to_string())
+        .try_collect()
+        .await?;
+
+    if options.server_side_copy && operator.info().full_capability().copy {
+        for file in &files {
+            let relative = file.strip_prefix(from).unwrap_or(file);
+
+            operator.copy(file, &format!("{}{}", to, relative)).await?;
+        }
+    } else if !files.is_empty() {
+        let copy_options = CopyOptions {
+            recursive: true,
+            disable_glob: true,
+            ..Default::default()
+        };
+
+        Copier::new(operator.clone(), operator.clone())
+            .copy_options(from, to, copy_options)
+            .await?;
+    }
+
+    Ok(files.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snapshot() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+        let options = SnapshotOptions::default();
+
+        operator.write("data/a.txt", "a").await?;
+        operator.write("data/sub/b.txt", "b").await?;
+
+        let taken = snapshot(&operator, "data", "snapshots/", options).await?;
+        assert_eq!(taken.files, Some(2));
+        assert!(taken.path.starts_with("snapshots/"));
+
+        operator.write("data/a.txt", "changed").await?;
+
+        let snapshots = list_snapshots(&operator, "snapshots").await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].id, taken.id);
+
+        let restored = restore(&operator, "snapshots", &taken.id, "data/", options).await?;
+        assert_eq!(restored, 2);
+        assert_eq!(operator.read("data/a.txt").await?.to_vec(), b"a");
+
+        let err = restore(&operator, "snapshots", "missing", "data/", options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let err = snapshot(&operator, "", "snapshots/", options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+}
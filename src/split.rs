@@ -0,0 +1,231 @@
+use std::fmt;
+use std::str::FromStr;
+
+use futures::{TryFutureExt, TryStreamExt};
+use opendal::{Error, ErrorKind, Writer};
+
+use crate::Copier;
+use crate::copy::{IoErrorExt, normalize_path};
+
+/// Name of the index object written next to the parts by [`Copier::split`].
+pub const SPLIT_INDEX_NAME: &str = "index";
+
+/// A single part produced by [`Copier::split`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitPart {
+    /// Name of the part, relative to the destination prefix.
+    pub name: String,
+
+    /// Size of the part in bytes.
+    pub size: u64,
+}
+
+/// Index describing how an object was split into parts.
+///
+/// The index is stored as a plain text object (see [`SPLIT_INDEX_NAME`]) so that it can be
+/// inspected by hand and consumed by tools that know nothing about this crate:
+///
+/// ```text
+/// size 10
+/// part_size 4
+/// part-00000 4
+/// part-00001 4
+/// part-00002 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitIndex {
+    /// Total size of the original object in bytes.
+    pub size: u64,
+
+    /// Maximum size of a single part in bytes.
+    pub part_size: u64,
+
+    /// Parts in the order they need to be concatenated.
+    pub parts: Vec<SplitPart>,
+}
+
+impl fmt::Display for SplitIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size {}", self.size)?;
+        writeln!(f, "part_size {}", self.part_size)?;
+
+        for part in &self.parts {
+            writeln!(f, "{} {}", part.name, part.size)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for SplitIndex {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut index = SplitIndex {
+            size: 0,
+            part_size: 0,
+            parts: Vec::new(),
+        };
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .split_once(' ')
+                .and_then(|(key, value)| Some((key, value.trim().parse::<u64>().ok()?)))
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Unexpected, "Invalid split index line")
+                        .with_context("line", line)
+                })?;
+
+            match key {
+                "size" => index.size = value,
+                "part_size" => index.part_size = value,
+                name => index.parts.push(SplitPart {
+                    name: name.to_string(),
+                    size: value,
+                }),
+            }
+        }
+
+        Ok(index)
+    }
+}
+
+impl Copier {
+    /// Split a single source object into `part-00000`-style objects of at most `part_size` bytes.
+    ///
+    /// Parts are written under `destination_prefix` together with an index (see [`SplitIndex`]).
+    /// This makes it possible to move oversized files to backends with per-object size limits.
+    pub async fn split(
+        &self,
+        source: impl Into<String>,
+        destination_prefix: impl Into<String>,
+        part_size: u64,
+    ) -> Result<SplitIndex, Error> {
+        if part_size == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "Part size must be greater than zero",
+            ));
+        }
+
+        let source = normalize_path(&source.into());
+        let prefix = normalize_path(&destination_prefix.into());
+
+        let stat = self.source.stat(source.as_str()).await?;
+        if !stat.is_file() {
+            return Err(Error::new(
+                ErrorKind::IsADirectory,
+                "Cannot split a directory",
+            ));
+        }
+
+        if !prefix.as_str().is_empty() {
+            self.destination
+                .create_dir(&format!("{}/", prefix.as_str().trim_end_matches('/')))
+                .await?;
+        }
+
+        let reader = self.source.reader(source.as_str()).await?;
+        let mut stream = reader.into_bytes_stream(..).await?;
+
+        let mut parts: Vec<SplitPart> = Vec::new();
+        let mut writer: Option<Writer> = None;
+
+        while let Some(mut chunk) = stream
+            .try_next()
+            .map_err(IoErrorExt::into_opendal_error)
+            .await?
+        {
+            while !chunk.is_empty() {
+                let current = match writer.as_mut() {
+                    Some(writer) => writer,
+                    None => {
+                        let name = format!("part-{:05}", parts.len());
+                        let w = self.destination.writer(prefix.join(&name).as_str()).await?;
+
+                        parts.push(SplitPart { name, size: 0 });
+
+                        writer.insert(w)
+                    }
+                };
+
+                // A part is pushed whenever a new writer is opened, so there is always a last part here.
+                let part = parts.last_mut().expect("current part");
+
+                let n = (part_size - part.size).min(chunk.len() as u64) as usize;
+                current.write(chunk.split_to(n)).await?;
+                part.size += n as u64;
+
+                if part.size == part_size {
+                    current.close().await?;
+                    writer = None;
+                }
+            }
+        }
+
+        if let Some(mut writer) = writer {
+            writer.close().await?;
+        }
+
+        let index = SplitIndex {
+            size: parts.iter().map(|part| part.size).sum(),
+            part_size,
+            parts,
+        };
+
+        self.destination
+            .write(prefix.join(SPLIT_INDEX_NAME).as_str(), index.to_string())
+            .await?;
+
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::Operator;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_split() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("path/to/file.bin", "0123456789").await?;
+
+        let copier = Copier::new(source, destination.clone());
+        let index = copier.split("path/to/file.bin", "parts/", 4).await?;
+
+        assert_eq!(index.size, 10);
+        assert_eq!(index.parts.len(), 3);
+
+        let part = destination.read("parts/part-00000").await?;
+        assert_eq!(part.to_vec(), b"0123");
+
+        let part = destination.read("parts/part-00002").await?;
+        assert_eq!(part.to_vec(), b"89");
+
+        let stored = destination.read("parts/index").await?;
+        let stored: SplitIndex = String::from_utf8_lossy(&stored.to_vec()).parse()?;
+        assert_eq!(stored, index);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_split_zero_part_size_should_error() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file.bin", "0123456789").await?;
+
+        let copier = Copier::new(source, destination);
+        let result = copier.split("file.bin", "parts/", 0).await;
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::ConfigInvalid);
+
+        Ok(())
+    }
+}
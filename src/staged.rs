@@ -0,0 +1,290 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::TryStreamExt;
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_dir;
+use crate::{
+    Copier, CopyOptions, RemoveAllOptions, SampleOptions, list, remove_all, verify_sample_options,
+};
+
+/// Outcome of a [`StagedCopy`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct StagedCopyReport {
+    /// The staging area the files were copied to.
+    pub staging: String,
+
+    /// Number of files promoted to the destination.
+    pub promoted: u64,
+}
+
+/// Copies into a staging area first, then promotes the files into the destination,
+/// so consumers don't observe partially copied files or a half-finished transfer.
+///
+/// Promotion moves the files one by one, so it is not atomic:
+/// consumers may observe a mix of old and new files while promoting.
+///
+/// The files are copied to `<destination>/.staging-<id>/`, optionally verified,
+/// then moved to the destination (renamed, copied server-side or streamed, depending on the service).
+/// The staging area is deleted once every file is promoted.
+///
+/// If the copy or the verification fails, the staging area is deleted and the destination is left untouched.
+/// If promoting fails, the staging area is left in place so that [`StagedCopy::promote`] can be retried.
+#[derive(Clone)]
+pub struct StagedCopy {
+    copier: Copier,
+    id: String,
+    verify: Option<(usize, SampleOptions)>,
+}
+
+impl StagedCopy {
+    pub fn new(copier: Copier) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+
+        Self {
+            copier,
+            id: format!("{}-{:08x}", timestamp, fastrand::u32(..)),
+            verify: None,
+        }
+    }
+
+    /// Use a fixed ID for the staging area (e.g. to resume promoting after a failure).
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Compare `n` randomly sampled staged files with the source before promoting them
+    /// (see [`verify_sample`](crate::verify_sample)).
+    ///
+    /// Verification requires the source to be a directory.
+    pub fn with_verify_sample(mut self, n: usize, options: SampleOptions) -> Self {
+        self.verify = Some((n, options));
+        self
+    }
+
+    /// ID of the staging area.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The staging area used for a destination (relative to the destination root of the copier).
+    pub fn staging(&self, destination: &str) -> String {
+        format!("{}.staging-{}/", normalize_dir(destination), self.id)
+    }
+
+    pub async fn run(
+        &self,
+        source: &str,
+        destination: &str,
+        options: CopyOptions,
+    ) -> Result<StagedCopyReport, Error> {
+        let staging = self.staging(destination);
+
+        if let Err(err) = self.stage(source, &staging, options).await {
+            // Best effort: the original error is more useful than a cleanup error
+            if let Ok(path) = self.copier.destination_path(&staging) {
+                let _ =
+                    remove_all(&self.copier.destination, &path, RemoveAllOptions::default()).await;
+            }
+
+            return Err(err.with_context("staging", &staging));
+        }
+
+        self.promote(destination).await
+    }
+
+    /// Move the files of the staging area into the destination and delete the staging area.
+    pub async fn promote(&self, destination: &str) -> Result<StagedCopyReport, Error> {
+        let operator = &self.copier.destination;
+
+        // Staged files are listed and moved on the operator, outside of the copier roots
+        let staging = self.staging(destination);
+        let staged = normalize_dir(&self.copier.destination_path(&staging)?);
+        let destination = normalize_dir(&self.copier.destination_path(destination)?);
+
+        let list_options = ListOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let files: Vec<String> = list::lister(operator, &staged, Some(list_options))
+            .await?
+            .try_filter(|entry| futures::future::ready(entry.metadata().is_file()))
+            .map_ok(|entry| entry.path().to_string())
+            .try_collect()
+            .await?;
+
+        let mut report = StagedCopyReport {
+            staging: staging.clone(),
+            promoted: 0,
+        };
+
+        for file in files {
+            let relative = file.strip_prefix(&staged).unwrap_or(&file);
+            let target = format!("{}{}", destination, relative);
+
+            promote_file(operator, &file, &target)
+                .await
+                .map_err(|err| err.with_context("staging", &staging))?;
+
+            report.promoted += 1;
+        }
+
+        remove_all(operator, &staged, RemoveAllOptions::default()).await?;
+
+        Ok(report)
+    }
+
+    async fn stage(&self, source: &str, staging: &str, options: CopyOptions) -> Result<(), Error> {
+        self.copier.copy_options(source, staging, options).await?;
+
+        let Some((n, sample_options)) = self.verify else {
+            return Ok(());
+        };
+
+        let report = verify_sample_options(
+            (self.copier.source.clone(), self.copier.source_path(source)?),
+            (
+                self.copier.destination.clone(),
+                self.copier.destination_path(staging)?,
+            ),
+            n,
+            sample_options,
+        )
+        .await?;
+
+        if let Some((path, mismatch)) = report.mismatches.first() {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "Staged file does not match the source",
+            )
+            .with_context("path", path)
+            .with_context("mismatch", format!("{:?}", mismatch)));
+        }
+
+        Ok(())
+    }
+}
+
+async fn promote_file(operator: &Operator, from: &str, to: &str) -> Result<(), Error> {
+    let capability = operator.info().full_capability();
+
+    if capability.rename {
+        return operator.rename(from, to).await;
+    }
+
+    if capability.copy {
+        return operator.copy(from, to).await.map(|_| ());
+    }
+
+    let options = CopyOptions {
+        recursive: false,
+        disable_glob: true,
+        ..Default::default()
+    };
+
+    Copier::new(operator.clone(), operator.clone())
+        .copy_options(from, to, options)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_staged_copy() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "new a").await?;
+        source.write("data/sub/b.txt", "b").await?;
+        destination.write("dataset/a.txt", "old a").await?;
+
+        let options = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let staged = StagedCopy::new(Copier::new(source.clone(), destination.clone()))
+            .with_id("test")
+            .with_verify_sample(10, SampleOptions::default());
+
+        let report = staged.run("data/", "/dataset", options).await?;
+        assert_eq!(
+            report,
+            StagedCopyReport {
+                staging: "dataset/.staging-test/".to_string(),
+                promoted: 2,
+            }
+        );
+
+        assert_eq!(destination.read("dataset/a.txt").await?.to_vec(), b"new a");
+        assert_eq!(destination.read("dataset/sub/b.txt").await?.to_vec(), b"b");
+        assert!(destination.list("dataset/.staging-test/").await?.is_empty());
+
+        // A failed copy leaves the destination untouched
+        let err = staged
+            .run("missing/", "dataset/", options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(destination.read("dataset/a.txt").await?.to_vec(), b"new a");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_staged_copy_rooted() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("tenant/data/a.txt", "a").await?;
+
+        let copier = Copier::builder(source, destination.clone())
+            .source_root("tenant/")
+            .destination_root("tenant/")
+            .build();
+
+        let options = CopyOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let report = StagedCopy::new(copier)
+            .with_id("test")
+            .with_verify_sample(10, SampleOptions::default())
+            .run("data/", "dataset/", options)
+            .await?;
+        assert_eq!(report.promoted, 1);
+
+        assert_eq!(
+            destination.read("tenant/dataset/a.txt").await?.to_vec(),
+            b"a"
+        );
+        assert!(
+            destination
+                .list("tenant/dataset/.staging-test/")
+                .await?
+                .is_empty()
+        );
+
+        Ok(())
+    }
+}
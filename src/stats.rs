@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::{CopyEvent, CopyObserver};
+
+// Length of the window over which throughput is measured.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(1);
+
+// Weight of the latest sample in latency averages.
+const LATENCY_WEIGHT: f64 = 0.2;
+
+/// A file being transferred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlightFile {
+    pub destination: String,
+
+    /// Size reported by the source (may be zero if the service does not report it).
+    pub size: u64,
+
+    /// Bytes transferred so far.
+    pub bytes: u64,
+}
+
+/// Point-in-time view of a [`TransferStats`].
+#[derive(Debug, Clone, Default)]
+pub struct TransferSnapshot {
+    pub files_completed: u64,
+
+    /// Number of failed file transfers (including attempts that were retried).
+    pub files_failed: u64,
+
+    /// Bytes transferred (excluding failed attempts).
+    pub bytes: u64,
+
+    /// Bytes expected from the files started so far.
+    ///
+    /// Grows as files are discovered, since copies don't know their size upfront.
+    pub bytes_expected: u64,
+
+    /// Files currently being transferred, keyed by source path.
+    pub in_flight: BTreeMap<String, InFlightFile>,
+
+    /// Bytes per second, measured over the last second.
+    pub throughput: f64,
+
+    /// Estimated time until the files started so far are transferred.
+    pub eta: Option<Duration>,
+
+    /// Average time until the first byte is read from the source.
+    pub source_latency: Option<Duration>,
+
+    /// Average time to commit a file to the destination.
+    pub destination_latency: Option<Duration>,
+}
+
+/// Live statistics of the transfers performed by a [`Copier`](crate::Copier).
+///
+/// Obtain a handle with [`Copier::stats`](crate::Copier::stats) and [`subscribe`](TransferStats::subscribe)
+/// to receive a [`TransferSnapshot`] every time the statistics change (e.g. to drive a dashboard).
+///
+/// Handles are cheap to clone; clones share the same statistics.
+#[derive(Debug, Clone, Default)]
+pub struct TransferStats {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    snapshot: watch::Sender<TransferSnapshot>,
+    window: Mutex<Option<Window>>,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    bytes: u64,
+}
+
+pub(crate) enum Backend {
+    Source,
+    Destination,
+}
+
+impl TransferStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current statistics.
+    pub fn snapshot(&self) -> TransferSnapshot {
+        self.inner.snapshot.borrow().clone()
+    }
+
+    /// Subscribe to statistics changes.
+    pub fn subscribe(&self) -> watch::Receiver<TransferSnapshot> {
+        self.inner.snapshot.subscribe()
+    }
+
+    pub(crate) fn record_latency(&self, backend: Backend, latency: Duration) {
+        self.inner.snapshot.send_modify(|snapshot| {
+            let average = match backend {
+                Backend::Source => &mut snapshot.source_latency,
+                Backend::Destination => &mut snapshot.destination_latency,
+            };
+
+            *average = Some(match average {
+                Some(average) => {
+                    average.mul_f64(1.0 - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT)
+                }
+                None => latency,
+            });
+        });
+    }
+
+    fn record_bytes(&self, bytes: u64) -> Option<f64> {
+        let mut window = self.inner.window.lock().expect("stats lock poisoned");
+
+        let now = Instant::now();
+
+        let window = window.get_or_insert(Window {
+            start: now,
+            bytes: 0,
+        });
+
+        window.bytes += bytes;
+
+        let elapsed = now - window.start;
+
+        if elapsed < THROUGHPUT_WINDOW {
+            return None;
+        }
+
+        let throughput = window.bytes as f64 / elapsed.as_secs_f64();
+
+        window.start = now;
+        window.bytes = 0;
+
+        Some(throughput)
+    }
+}
+
+impl CopyObserver for TransferStats {
+    fn on_event(&self, event: &CopyEvent) {
+        let throughput = match event {
+            CopyEvent::FileProgress { bytes, .. } => self.record_bytes(*bytes),
+            _ => None,
+        };
+
+        self.inner.snapshot.send_modify(|snapshot| {
+            match event {
+                CopyEvent::FileStarted {
+                    source,
+                    destination,
+                    size,
+                } => {
+                    let file = InFlightFile {
+                        destination: destination.clone(),
+                        size: *size,
+                        bytes: 0,
+                    };
+
+                    snapshot.bytes_expected += size;
+
+                    // A restarted transfer replaces the failed attempt
+                    if let Some(previous) = snapshot.in_flight.insert(source.clone(), file) {
+                        snapshot.bytes_expected -= previous.size;
+                        snapshot.bytes -= previous.bytes;
+                    }
+                }
+                CopyEvent::FileProgress { source, bytes } => {
+                    snapshot.bytes += bytes;
+
+                    if let Some(file) = snapshot.in_flight.get_mut(source) {
+                        file.bytes += bytes;
+
+                        // The size is unknown for some services
+                        if file.bytes > file.size {
+                            snapshot.bytes_expected += file.bytes - file.size;
+                            file.size = file.bytes;
+                        }
+                    }
+                }
+                CopyEvent::FileCompleted { source, .. } => {
+                    snapshot.in_flight.remove(source);
+                    snapshot.files_completed += 1;
+                }
+                CopyEvent::FileSkipped { .. } => (),
+                CopyEvent::FileFailed { source, .. } => {
+                    if let Some(file) = snapshot.in_flight.remove(source) {
+                        snapshot.bytes_expected -= file.size;
+                        snapshot.bytes -= file.bytes;
+                    }
+
+                    snapshot.files_failed += 1;
+                }
+            }
+
+            if let Some(throughput) = throughput {
+                snapshot.throughput = throughput;
+            }
+
+            let remaining = snapshot.bytes_expected.saturating_sub(snapshot.bytes);
+
+            snapshot.eta = if remaining == 0 {
+                Some(Duration::ZERO)
+            } else if snapshot.throughput > 0.0 {
+                Some(Duration::from_secs_f64(
+                    remaining as f64 / snapshot.throughput,
+                ))
+            } else {
+                None
+            };
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+    use opendal::{Error, Operator};
+
+    use super::*;
+    use crate::Copier;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_transfer_stats() {
+        let stats = TransferStats::new();
+
+        stats.on_event(&CopyEvent::FileStarted {
+            source: "a.txt".to_string(),
+            destination: "b.txt".to_string(),
+            size: 300,
+        });
+
+        stats.on_event(&CopyEvent::FileProgress {
+            source: "a.txt".to_string(),
+            bytes: 50,
+        });
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+
+        stats.on_event(&CopyEvent::FileProgress {
+            source: "a.txt".to_string(),
+            bytes: 50,
+        });
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.bytes, 100);
+        assert_eq!(snapshot.bytes_expected, 300);
+        assert_eq!(snapshot.in_flight["a.txt"].bytes, 100);
+        assert_eq!(snapshot.throughput, 100.0);
+        assert_eq!(snapshot.eta, Some(Duration::from_secs(2)));
+
+        // A failed attempt doesn't count
+        stats.on_event(&CopyEvent::FileFailed {
+            source: "a.txt".to_string(),
+            destination: "b.txt".to_string(),
+            error: "boom".to_string(),
+        });
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.files_failed, 1);
+        assert_eq!(snapshot.bytes, 0);
+        assert_eq!(snapshot.bytes_expected, 0);
+        assert!(snapshot.in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_copier_stats() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("file1.txt", "foo").await?;
+        source.write("file2.txt", "barbaz").await?;
+
+        let copier = Copier::new(source, destination);
+
+        let stats = copier.stats();
+        let mut snapshots = stats.subscribe();
+
+        copier.copy("file1.txt", "copy1.txt").await?;
+        copier.copy("file2.txt", "copy2.txt").await?;
+
+        assert!(snapshots.has_changed().unwrap());
+
+        let snapshot = snapshots.borrow_and_update().clone();
+        assert_eq!(snapshot.files_completed, 2);
+        assert_eq!(snapshot.bytes, 9);
+        assert_eq!(snapshot.bytes_expected, 9);
+        assert_eq!(snapshot.eta, Some(Duration::ZERO));
+        assert!(snapshot.in_flight.is_empty());
+        assert!(snapshot.source_latency.is_some());
+        assert!(snapshot.destination_latency.is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime};
+
+use opendal::{Error, ErrorKind, Operator};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::{CopyEvent, CopyObserver};
+
+/// State of the copy described by a [`TransferStatus`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferState {
+    #[default]
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Progress of a copy, written as JSON by a [`StatusWriter`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TransferStatus {
+    pub state: TransferState,
+
+    pub files_completed: u64,
+
+    /// Files skipped because the destination already had the same content.
+    pub files_skipped: u64,
+
+    /// Number of failed file transfers (including attempts that were retried).
+    pub files_failed: u64,
+
+    /// Files discovered so far (completed, skipped or in flight).
+    ///
+    /// Grows as files are discovered, since copies don't know the number of files upfront.
+    pub files_total: u64,
+
+    /// Bytes read from the source (including failed attempts).
+    pub bytes: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    pub updated_at: SystemTime,
+}
+
+impl Default for TransferStatus {
+    fn default() -> Self {
+        Self {
+            state: TransferState::default(),
+            files_completed: 0,
+            files_skipped: 0,
+            files_failed: 0,
+            files_total: 0,
+            bytes: 0,
+            last_error: None,
+            updated_at: SystemTime::now(),
+        }
+    }
+}
+
+/// Periodically writes the [`TransferStatus`] of a copier to an object,
+/// so external monitors can poll the progress of long copies.
+///
+/// The status is written every interval while it changes, and once a copy finishes
+/// (see [`Copier::with_status`](crate::Copier::with_status)).
+/// Requires a Tokio runtime.
+#[derive(Clone)]
+pub struct StatusWriter {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    operator: Operator,
+    path: String,
+    status: Mutex<Tracker>,
+    dirty: AtomicBool,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Default)]
+struct Tracker {
+    status: TransferStatus,
+    in_flight: HashSet<String>,
+}
+
+impl StatusWriter {
+    pub fn new(operator: Operator, path: impl Into<String>, interval: Duration) -> Self {
+        let inner = Arc::new(Inner {
+            operator,
+            path: path.into(),
+            status: Mutex::new(Tracker::default()),
+            dirty: AtomicBool::new(false),
+            task: Mutex::new(None),
+        });
+
+        let task = tokio::spawn(run(Arc::downgrade(&inner), interval));
+
+        *inner.task.lock().expect("status task lock poisoned") = Some(task);
+
+        Self { inner }
+    }
+
+    /// The current status.
+    pub fn status(&self) -> TransferStatus {
+        self.inner.tracker().status.clone()
+    }
+
+    /// Record the outcome of a copy and write the status.
+    pub async fn finish(&self, error: Option<&Error>) -> Result<(), Error> {
+        {
+            let mut tracker = self.inner.tracker();
+
+            tracker.status.state = match error {
+                Some(_) => TransferState::Failed,
+                None => TransferState::Succeeded,
+            };
+
+            if let Some(error) = error {
+                tracker.status.last_error = Some(error.to_string());
+            }
+
+            tracker.status.updated_at = SystemTime::now();
+        }
+
+        self.inner.dirty.store(false, Ordering::SeqCst);
+
+        self.inner.write().await
+    }
+}
+
+impl CopyObserver for StatusWriter {
+    fn on_event(&self, event: &CopyEvent) {
+        let mut tracker = self.inner.tracker();
+        let Tracker { status, in_flight } = &mut *tracker;
+
+        status.state = TransferState::Running;
+
+        match event {
+            CopyEvent::FileStarted { source, .. } => {
+                in_flight.insert(source.clone());
+            }
+            CopyEvent::FileProgress { bytes, .. } => status.bytes += bytes,
+            CopyEvent::FileCompleted { source, .. } => {
+                in_flight.remove(source);
+                status.files_completed += 1;
+            }
+            CopyEvent::FileSkipped { .. } => status.files_skipped += 1,
+            CopyEvent::FileFailed { source, error, .. } => {
+                in_flight.remove(source);
+                status.files_failed += 1;
+                status.last_error = Some(error.clone());
+            }
+        }
+
+        status.files_total = status.files_completed + status.files_skipped + in_flight.len() as u64;
+        status.updated_at = SystemTime::now();
+
+        self.inner.dirty.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Inner {
+    fn tracker(&self) -> std::sync::MutexGuard<'_, Tracker> {
+        self.status.lock().expect("status lock poisoned")
+    }
+
+    async fn write(&self) -> Result<(), Error> {
+        let status = self.tracker().status.clone();
+
+        let content = serde_json::to_vec(&status).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "Failed to serialize status").set_source(err)
+        })?;
+
+        self.operator
+            .write_with(&self.path, content)
+            .content_type("application/json")
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.get_mut().ok().and_then(Option::take) {
+            task.abort();
+        }
+    }
+}
+
+async fn run(inner: Weak<Inner>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+
+        if inner.dirty.swap(false, Ordering::SeqCst) {
+            // Monitoring must not interrupt the copy, the next write may succeed
+            let _ = inner.write().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+    use crate::Copier;
+
+    #[tokio::test]
+    async fn test_status_writer() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        source.write("data/a.txt", "aaa").await?;
+        source.write("data/b.txt", "bb").await?;
+
+        let status = StatusWriter::new(
+            destination.clone(),
+            "jobs/status.json",
+            Duration::from_secs(3600),
+        );
+
+        let copier = Copier::new(source, destination.clone()).with_status(status.clone());
+
+        copier.copy("data/", "out/").await?;
+
+        let read = async || -> Result<TransferStatus, Error> {
+            let content = destination.read("jobs/status.json").await?.to_vec();
+
+            Ok(serde_json::from_slice(&content).unwrap())
+        };
+
+        let written = read().await?;
+        assert_eq!(written.state, TransferState::Succeeded);
+        assert_eq!(written.files_completed, 2);
+        assert_eq!(written.files_total, 2);
+        assert_eq!(written.bytes, 5);
+        assert_eq!(written, status.status());
+
+        assert!(copier.copy("missing.txt", "out/").await.is_err());
+
+        let written = read().await?;
+        assert_eq!(written.state, TransferState::Failed);
+        assert!(written.last_error.is_some());
+
+        Ok(())
+    }
+}
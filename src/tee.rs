@@ -0,0 +1,249 @@
+use futures::future::join_all;
+use futures::{Stream, TryStreamExt};
+use opendal::{Buffer, Error, Metadata, Operator, Writer};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Controls how [`tee`] reacts when one of the destinations fails.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum TeeMode {
+    /// Abort every destination as soon as one of them fails.
+    ///
+    /// Writers are aborted before they are closed, so a failure while writing leaves no
+    /// destination behind. A failure while closing cannot roll back destinations that were
+    /// already committed.
+    #[default]
+    AllOrNothing,
+
+    /// Keep writing to the remaining destinations and report failures at the end.
+    BestEffort,
+}
+
+/// Outcome of a [`tee`] call.
+#[derive(Debug)]
+pub struct TeeReport {
+    /// Result for each destination, in the order the destinations were given.
+    pub results: Vec<Result<Metadata, Error>>,
+}
+
+impl TeeReport {
+    /// Returns `true` if every destination was written successfully.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(Result::is_ok)
+    }
+}
+
+/// Write a single stream to multiple destinations concurrently.
+///
+/// Every chunk of the stream is written to all destinations before the next one is read,
+/// so a single ingest can replicate to several operators (e.g. a primary and a DR bucket) in one pass.
+///
+/// An error from the source stream always aborts every destination.
+pub async fn tee<S>(
+    mut stream: S,
+    destinations: impl IntoIterator<Item = (Operator, String)>,
+    mode: TeeMode,
+) -> Result<TeeReport, Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    let destinations: Vec<(Operator, String)> = destinations.into_iter().collect();
+
+    let mut writers: Vec<Option<Writer>> = Vec::with_capacity(destinations.len());
+    let mut results: Vec<Option<Result<Metadata, Error>>> = Vec::with_capacity(destinations.len());
+
+    let opened = join_all(destinations.iter().map(|(op, path)| op.writer(path))).await;
+
+    for (writer, (_, path)) in opened.into_iter().zip(&destinations) {
+        match writer {
+            Ok(writer) => {
+                writers.push(Some(writer));
+                results.push(None);
+            }
+            Err(err) if mode == TeeMode::AllOrNothing => {
+                abort_all(&mut writers).await;
+
+                return Err(err.with_context("destination", path));
+            }
+            Err(err) => {
+                writers.push(None);
+                results.push(Some(Err(err)));
+            }
+        }
+    }
+
+    loop {
+        let chunk = match stream.try_next().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(err) => {
+                abort_all(&mut writers).await;
+
+                return Err(err);
+            }
+        };
+
+        let outcomes = join_all(writers.iter_mut().map(|writer| {
+            let chunk = chunk.clone();
+
+            async move {
+                match writer {
+                    Some(writer) => Some(writer.write(chunk).await),
+                    None => None,
+                }
+            }
+        }))
+        .await;
+
+        for (i, outcome) in outcomes.into_iter().enumerate() {
+            let Some(Err(err)) = outcome else {
+                continue;
+            };
+
+            let err = err.with_context("destination", &destinations[i].1);
+
+            if mode == TeeMode::AllOrNothing {
+                abort_all(&mut writers).await;
+
+                return Err(err);
+            }
+
+            if let Some(mut writer) = writers[i].take() {
+                let _ = writer.abort().await;
+            }
+
+            results[i] = Some(Err(err));
+        }
+    }
+
+    let closed = join_all(writers.iter_mut().map(|writer| async move {
+        match writer {
+            Some(writer) => Some(writer.close().await),
+            None => None,
+        }
+    }))
+    .await;
+
+    for (i, outcome) in closed.into_iter().enumerate() {
+        if let Some(outcome) = outcome {
+            results[i] =
+                Some(outcome.map_err(|err| err.with_context("destination", &destinations[i].1)));
+        }
+    }
+
+    let results: Vec<Result<Metadata, Error>> = results
+        .into_iter()
+        .map(|result| result.expect("every destination has a result"))
+        .collect();
+
+    if mode == TeeMode::AllOrNothing
+        && let Some(index) = results.iter().position(Result::is_err)
+    {
+        let mut results = results;
+
+        return Err(results.swap_remove(index).unwrap_err());
+    }
+
+    Ok(TeeReport { results })
+}
+
+async fn abort_all(writers: &mut [Option<Writer>]) {
+    join_all(
+        writers
+            .iter_mut()
+            .filter_map(Option::as_mut)
+            .map(Writer::abort),
+    )
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use opendal::ErrorKind;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    fn chunks() -> impl Stream<Item = Result<Buffer, Error>> + Unpin {
+        stream::iter(vec![Ok(Buffer::from("foo")), Ok(Buffer::from("bar"))])
+    }
+
+    #[tokio::test]
+    async fn test_tee() -> Result<(), Error> {
+        let primary = Operator::new(Memory::default())?.finish();
+        let dr = Operator::new(Memory::default())?.finish();
+
+        let report = tee(
+            chunks(),
+            [
+                (primary.clone(), "file.txt".to_string()),
+                (dr.clone(), "backup/file.txt".to_string()),
+            ],
+            TeeMode::AllOrNothing,
+        )
+        .await?;
+
+        assert!(report.is_success());
+        assert_eq!(primary.read("file.txt").await?.to_vec(), b"foobar");
+        assert_eq!(dr.read("backup/file.txt").await?.to_vec(), b"foobar");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tee_all_or_nothing_should_error() -> Result<(), Error> {
+        let primary = Operator::new(Memory::default())?.finish();
+        let dr = Operator::new(Memory::default())?.finish();
+
+        // Writing to a directory path fails
+        let result = tee(
+            chunks(),
+            [
+                (primary.clone(), "file.txt".to_string()),
+                (dr.clone(), "dir/".to_string()),
+            ],
+            TeeMode::AllOrNothing,
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        // No destination should be written
+        assert!(!primary.exists("file.txt").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tee_best_effort() -> Result<(), Error> {
+        let primary = Operator::new(Memory::default())?.finish();
+        let dr = Operator::new(Memory::default())?.finish();
+
+        let report = tee(
+            chunks(),
+            [
+                (primary.clone(), "file.txt".to_string()),
+                (dr.clone(), "dir/".to_string()),
+            ],
+            TeeMode::BestEffort,
+        )
+        .await?;
+
+        assert!(!report.is_success());
+        assert!(report.results[0].is_ok());
+        assert_eq!(
+            report.results[1].as_ref().unwrap_err().kind(),
+            ErrorKind::IsADirectory
+        );
+        assert_eq!(primary.read("file.txt").await?.to_vec(), b"foobar");
+
+        Ok(())
+    }
+}
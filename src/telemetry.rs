@@ -0,0 +1,134 @@
+// Instrumentation hooks shared by the copy and list machinery.
+// Every hook compiles to a no-op unless the corresponding feature is enabled.
+
+use std::time::Duration;
+
+use opendal::Error;
+
+// Schemes of the source and destination operators of a copy, used as metric labels.
+pub(crate) type Schemes = (&'static str, &'static str);
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_copy(schemes: Schemes, duration: Duration, error: Option<&Error>) {
+    #[cfg(feature = "metrics")]
+    {
+        let (source, destination) = schemes;
+
+        metrics::histogram!(
+            "opendal_util_copy_duration_seconds",
+            "source" => source,
+            "destination" => destination
+        )
+        .record(duration.as_secs_f64());
+
+        if let Some(err) = error {
+            metrics::counter!(
+                "opendal_util_copy_errors_total",
+                "kind" => err.kind().into_static(),
+                "source" => source,
+                "destination" => destination
+            )
+            .increment(1);
+        }
+    }
+}
+
+#[cfg_attr(
+    not(all(feature = "metrics", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn record_file_copied(
+    schemes: Schemes,
+    source: &str,
+    destination: &str,
+    bytes: u64,
+    duration: Duration,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        let (source, destination) = schemes;
+
+        metrics::counter!(
+            "opendal_util_copy_files_total",
+            "source" => source,
+            "destination" => destination
+        )
+        .increment(1);
+        metrics::counter!(
+            "opendal_util_copy_bytes_total",
+            "source" => source,
+            "destination" => destination
+        )
+        .increment(bytes);
+        metrics::histogram!(
+            "opendal_util_copy_file_duration_seconds",
+            "source" => source,
+            "destination" => destination
+        )
+        .record(duration.as_secs_f64());
+    }
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        source,
+        destination,
+        source_scheme = schemes.0,
+        destination_scheme = schemes.1,
+        bytes,
+        duration = ?duration,
+        "copied file"
+    );
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_file_failed(schemes: Schemes, err: &Error) {
+    #[cfg(feature = "metrics")]
+    {
+        let (source, destination) = schemes;
+
+        metrics::counter!(
+            "opendal_util_copy_file_errors_total",
+            "kind" => err.kind().into_static(),
+            "source" => source,
+            "destination" => destination
+        )
+        .increment(1);
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_lister<T>(duration: Duration, result: &Result<T, Error>) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!("opendal_util_list_total").increment(1);
+        metrics::histogram!("opendal_util_list_open_duration_seconds")
+            .record(duration.as_secs_f64());
+
+        if let Err(err) = result {
+            metrics::counter!("opendal_util_list_errors_total", "kind" => err.kind().into_static())
+                .increment(1);
+        }
+    }
+}
+
+#[cfg_attr(
+    not(all(feature = "metrics", feature = "tracing")),
+    allow(unused_variables)
+)]
+pub(crate) fn record_retry(attempt: usize, delay: Duration, err: &Error) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!("opendal_util_retries_total", "kind" => err.kind().into_static())
+        .increment(1);
+
+    #[cfg(feature = "tracing")]
+    tracing::warn!(attempt, delay = ?delay, error = %err, "retrying operation");
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_list(duration: Duration, entries: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::histogram!("opendal_util_list_duration_seconds").record(duration.as_secs_f64());
+        metrics::counter!("opendal_util_list_entries_total").increment(entries as u64);
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+use opendal::{Error, ErrorKind, Metadata};
+use sha2::{Digest, Sha256};
+use typed_path::Utf8UnixPath;
+
+/// A destination path containing placeholders expanded for every copied file.
+///
+/// Supported placeholders:
+/// - `{name}`: file name of the source (e.g. `report.csv`)
+/// - `{stem}`: file name without the extension (e.g. `report`)
+/// - `{ext}`: extension of the file name, without the dot (e.g. `csv`)
+/// - `{path}`: path of the source relative to the copied directory or glob prefix
+/// - `{date:<format>}`: modification time of the source in UTC, formatted with strftime (e.g. `{date:%Y/%m/%d}`)
+/// - `{hash}` / `{hash:<n>}`: SHA-256 of the source path in hex (truncated to `n` characters)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DestinationTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Name,
+    Stem,
+    Ext,
+    Path,
+    Date(String),
+    Hash(usize),
+}
+
+impl DestinationTemplate {
+    /// Parse a destination, returning `None` if it does not contain placeholders.
+    pub(crate) fn parse(destination: &str) -> Result<Option<Self>, Error> {
+        if !destination.contains('{') {
+            return Ok(None);
+        }
+
+        let invalid = |reason: &str| {
+            Error::new(ErrorKind::ConfigInvalid, "Invalid destination template")
+                .with_context("destination", destination)
+                .with_context("reason", reason)
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = destination;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Literal(rest[..start].to_string()));
+            }
+
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| invalid("unclosed placeholder"))?;
+
+            let placeholder = &rest[start + 1..start + end];
+
+            let segment = match placeholder.split_once(':') {
+                None if placeholder == "name" => Segment::Name,
+                None if placeholder == "stem" => Segment::Stem,
+                None if placeholder == "ext" => Segment::Ext,
+                None if placeholder == "path" => Segment::Path,
+                None if placeholder == "hash" => Segment::Hash(64),
+                Some(("date", format)) if !format.is_empty() => Segment::Date(format.to_string()),
+                Some(("hash", length)) => {
+                    Segment::Hash(length.parse().map_err(|_| invalid("invalid hash length"))?)
+                }
+                _ => {
+                    return Err(
+                        invalid("unknown placeholder").with_context("placeholder", placeholder)
+                    );
+                }
+            };
+
+            segments.push(segment);
+
+            rest = &rest[start + end + 1..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Some(Self { segments }))
+    }
+
+    /// Whether expanding the template requires the modification time of the source.
+    pub(crate) fn needs_last_modified(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Date(_)))
+    }
+
+    /// Expand the template for a source file.
+    ///
+    /// `relative` is the path of the source relative to the copied directory (or its file name).
+    pub(crate) fn expand(
+        &self,
+        source: &str,
+        relative: &str,
+        meta: &Metadata,
+    ) -> Result<String, Error> {
+        let name = Utf8UnixPath::new(source).file_name().unwrap_or_default();
+        let (stem, ext) = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, ext),
+            _ => (name, ""),
+        };
+
+        let mut result = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => result.push_str(literal),
+                Segment::Name => result.push_str(name),
+                Segment::Stem => result.push_str(stem),
+                Segment::Ext => result.push_str(ext),
+                Segment::Path => result.push_str(relative.trim_start_matches('/')),
+                Segment::Date(format) => {
+                    let last_modified = meta.last_modified().ok_or_else(|| {
+                        Error::new(ErrorKind::Unexpected, "Source has no modification time")
+                            .with_context("path", source)
+                    })?;
+
+                    write!(result, "{}", last_modified.into_inner().strftime(format)).map_err(
+                        |_| {
+                            Error::new(ErrorKind::ConfigInvalid, "Invalid date format")
+                                .with_context("format", format)
+                        },
+                    )?;
+                }
+                Segment::Hash(length) => {
+                    let hash = hex::encode(Sha256::digest(source.as_bytes()));
+
+                    result.push_str(&hash[..(*length).min(hash.len())]);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::EntryMode;
+    use opendal::raw::Timestamp;
+
+    use super::*;
+
+    #[test]
+    fn test_expand() -> Result<(), Error> {
+        let meta = Metadata::new(EntryMode::FILE)
+            .with_last_modified(Timestamp::from_second(1_700_000_000)?);
+
+        let expand = |template: &str| {
+            DestinationTemplate::parse(template)
+                .unwrap()
+                .unwrap()
+                .expand("incoming/2023/report.csv", "2023/report.csv", &meta)
+                .unwrap()
+        };
+
+        assert_eq!(
+            expand("archive/{date:%Y/%m/%d}/{name}"),
+            "archive/2023/11/14/report.csv"
+        );
+        assert_eq!(expand("{stem}-copy.{ext}"), "report-copy.csv");
+        assert_eq!(expand("out/{path}"), "out/2023/report.csv");
+        assert_eq!(expand("{hash:8}/{name}").len(), "12345678/report.csv".len());
+        assert_eq!(expand("{hash:2}"), expand("{hash}")[..2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(DestinationTemplate::parse("plain/path/").unwrap(), None);
+
+        for template in ["{unknown}", "{name", "{hash:x}", "{date:}"] {
+            let err = DestinationTemplate::parse(template).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        }
+
+        let meta = Metadata::new(EntryMode::FILE);
+        let template = DestinationTemplate::parse("{date:%Y}/{name}")
+            .unwrap()
+            .unwrap();
+
+        assert!(template.needs_last_modified());
+        assert!(template.expand("a.txt", "a.txt", &meta).is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+use crate::{Copier, CopyOptions, list};
+
+/// Check that the copy semantics of this crate hold when copying between two operators.
+///
+/// The suite runs in a randomly named directory of each operator, removed once the suite is done.
+/// The first failing check is returned as an error, with the name of the check in its context.
+pub async fn copy_behavior_tests(source: &Operator, destination: &Operator) -> Result<(), Error> {
+    let root = test_root();
+
+    let result = run_copy_behavior_tests(source, destination, &root).await;
+
+    cleanup(source, &root).await?;
+    cleanup(destination, &root).await?;
+
+    result
+}
+
+/// Check that the listing semantics of this crate hold for an operator.
+///
+/// The suite runs in a randomly named directory, removed once the suite is done.
+/// The first failing check is returned as an error, with the name of the check in its context.
+pub async fn list_behavior_tests(operator: &Operator) -> Result<(), Error> {
+    let root = test_root();
+
+    let result = run_list_behavior_tests(operator, &root).await;
+
+    cleanup(operator, &root).await?;
+
+    result
+}
+
+async fn run_copy_behavior_tests(
+    source: &Operator,
+    destination: &Operator,
+    root: &str,
+) -> Result<(), Error> {
+    let copier = Copier::new(source.clone(), destination.clone());
+
+    source
+        .write(&format!("{}src/file1.txt", root), "content1")
+        .await?;
+    source
+        .write(&format!("{}src/file2.rs", root), "content2")
+        .await?;
+    source
+        .write(&format!("{}src/nested/file3.txt", root), "content3")
+        .await?;
+
+    // Copy a file to a new path
+    copier
+        .copy(
+            format!("{}src/file1.txt", root),
+            format!("{}file/copy.txt", root),
+        )
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}file/copy.txt", root),
+        "content1",
+        "copy_file",
+    )
+    .await?;
+
+    // Overwrite an existing file
+    copier
+        .copy(
+            format!("{}src/file2.rs", root),
+            format!("{}file/copy.txt", root),
+        )
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}file/copy.txt", root),
+        "content2",
+        "copy_file_overwrite",
+    )
+    .await?;
+
+    // Copy a file into an existing directory
+    destination.create_dir(&format!("{}into/", root)).await?;
+    copier
+        .copy(format!("{}src/file1.txt", root), format!("{}into/", root))
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}into/file1.txt", root),
+        "content1",
+        "copy_file_to_directory",
+    )
+    .await?;
+
+    // Copy the immediate contents of a directory
+    copier
+        .copy(format!("{}src/", root), format!("{}flat/", root))
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}flat/file1.txt", root),
+        "content1",
+        "copy_directory_non_recursive",
+    )
+    .await?;
+    expect_missing(
+        destination,
+        &format!("{}flat/nested/file3.txt", root),
+        "copy_directory_non_recursive",
+    )
+    .await?;
+
+    // Copy a directory recursively
+    let options = CopyOptions {
+        recursive: true,
+        ..Default::default()
+    };
+    copier
+        .copy_options(format!("{}src/", root), format!("{}tree/", root), options)
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}tree/nested/file3.txt", root),
+        "content3",
+        "copy_directory_recursive",
+    )
+    .await?;
+
+    // Copy files matching a glob pattern
+    copier
+        .copy(format!("{}src/**/*.txt", root), format!("{}glob/", root))
+        .await?;
+    expect_content(
+        destination,
+        &format!("{}glob/nested/file3.txt", root),
+        "content3",
+        "copy_glob",
+    )
+    .await?;
+    expect_missing(destination, &format!("{}glob/file2.rs", root), "copy_glob").await?;
+
+    // Copying a missing file fails with NotFound
+    let result = copier
+        .copy(
+            format!("{}src/missing.txt", root),
+            format!("{}missing.txt", root),
+        )
+        .await;
+    check(
+        matches!(&result, Err(err) if err.kind() == ErrorKind::NotFound),
+        "copy_missing_source",
+        "expected a NotFound error",
+    )?;
+
+    Ok(())
+}
+
+async fn run_list_behavior_tests(operator: &Operator, root: &str) -> Result<(), Error> {
+    operator.write(&format!("{}file1.txt", root), "").await?;
+    operator.write(&format!("{}file2.rs", root), "").await?;
+    operator
+        .write(&format!("{}dir/file3.txt", root), "")
+        .await?;
+
+    // List the immediate contents of a directory
+    let paths = list_paths(operator, root, None).await?;
+    expect_paths(
+        paths,
+        &[
+            format!("{}dir/", root),
+            format!("{}file1.txt", root),
+            format!("{}file2.rs", root),
+        ],
+        "list_directory",
+    )?;
+
+    // List a directory recursively
+    let options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+    let paths = list_paths(operator, root, Some(options)).await?;
+    expect_paths(
+        paths
+            .into_iter()
+            .filter(|path| !path.ends_with('/'))
+            .collect(),
+        &[
+            format!("{}dir/file3.txt", root),
+            format!("{}file1.txt", root),
+            format!("{}file2.rs", root),
+        ],
+        "list_recursive",
+    )?;
+
+    // List files matching a glob pattern
+    let paths = list_paths(operator, &format!("{}**/*.txt", root), None).await?;
+    expect_paths(
+        paths,
+        &[
+            format!("{}dir/file3.txt", root),
+            format!("{}file1.txt", root),
+        ],
+        "list_glob",
+    )?;
+
+    Ok(())
+}
+
+async fn list_paths(
+    operator: &Operator,
+    path: &str,
+    options: Option<ListOptions>,
+) -> Result<Vec<String>, Error> {
+    let mut paths: Vec<String> = list(operator, path, options)
+        .await?
+        .into_iter()
+        .map(|entry| entry.path().to_string())
+        .filter(|entry| entry != path)
+        .collect();
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+async fn expect_content(
+    operator: &Operator,
+    path: &str,
+    expected: &str,
+    test: &'static str,
+) -> Result<(), Error> {
+    let content = operator
+        .read(path)
+        .await
+        .map_err(|err| failure(test, "failed to read copied file").set_source(err))?;
+
+    check(
+        content.to_vec() == expected.as_bytes(),
+        test,
+        "unexpected content",
+    )
+    .map_err(|err| err.with_context("path", path))
+}
+
+async fn expect_missing(operator: &Operator, path: &str, test: &'static str) -> Result<(), Error> {
+    check(!operator.exists(path).await?, test, "unexpected file")
+        .map_err(|err| err.with_context("path", path))
+}
+
+fn expect_paths(actual: Vec<String>, expected: &[String], test: &'static str) -> Result<(), Error> {
+    check(actual == expected, test, "unexpected paths").map_err(|err| {
+        err.with_context("expected", expected.join(","))
+            .with_context("actual", actual.join(","))
+    })
+}
+
+fn check(condition: bool, test: &'static str, message: &str) -> Result<(), Error> {
+    if condition {
+        return Ok(());
+    }
+
+    Err(failure(test, message))
+}
+
+fn failure(test: &'static str, message: &str) -> Error {
+    Error::new(ErrorKind::Unexpected, "Behavior test failed")
+        .with_context("test", test)
+        .with_context("reason", message)
+}
+
+fn test_root() -> String {
+    format!("opendal-util-testkit-{:08x}/", fastrand::u32(..))
+}
+
+async fn cleanup(operator: &Operator, root: &str) -> Result<(), Error> {
+    operator.delete_with(root).recursive(true).await
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_behavior() -> Result<(), Error> {
+        let source = Operator::new(Memory::default())?.finish();
+        let destination = Operator::new(Memory::default())?.finish();
+
+        copy_behavior_tests(&source, &destination).await?;
+        list_behavior_tests(&source).await?;
+
+        // Suites clean up after themselves
+        assert!(list(&source, "", None).await?.is_empty());
+        assert!(list(&destination, "", None).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use opendal::{Error, ErrorKind, Operator};
+
+/// Create an empty object if it does not exist, or freshen it if it does.
+///
+/// Object stores have no way to bump the modification time of an object in place,
+/// so an existing object is rewritten with its own content and content type,
+/// which updates its modification time on backends that track one.
+/// Rewriting reads the whole object, so touching large objects is expensive.
+///
+/// On backends supporting `if_match` writes, an object modified while it is being touched
+/// is not overwritten with its previous content: touching fails with [`ErrorKind::ConditionNotMatch`] instead.
+pub async fn touch(operator: &Operator, path: &str) -> Result<(), Error> {
+    let capability = operator.info().full_capability();
+
+    let meta = match operator.stat(path).await {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            let result = operator
+                .write_with(path, Vec::<u8>::new())
+                .if_not_exists(capability.write_with_if_not_exists)
+                .await;
+
+            return match result {
+                Ok(_) => Ok(()),
+                // Someone else created the object in the meantime, which is just as fresh
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        ErrorKind::AlreadyExists | ErrorKind::ConditionNotMatch
+                    ) =>
+                {
+                    Ok(())
+                }
+                Err(err) => Err(err),
+            };
+        }
+        Err(err) => return Err(err),
+    };
+
+    if meta.is_dir() {
+        return Err(
+            Error::new(ErrorKind::IsADirectory, "Cannot touch a directory")
+                .with_context("path", path),
+        );
+    }
+
+    let content = operator.read(path).await?;
+
+    let mut write = operator.write_with(path, content);
+
+    if capability.write_with_if_match
+        && let Some(etag) = meta.etag()
+    {
+        write = write.if_match(etag);
+    }
+
+    if let Some(content_type) = meta.content_type() {
+        write = write.content_type(content_type);
+    }
+
+    write.await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_touch() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        touch(&operator, "markers/_SUCCESS").await?;
+
+        let stat = operator.stat("markers/_SUCCESS").await?;
+        assert!(stat.is_file());
+        assert_eq!(stat.content_length(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_touch_existing() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator
+            .write_with("file.txt", "foo")
+            .content_type("text/plain")
+            .await?;
+
+        touch(&operator, "file.txt").await?;
+
+        let buffer = operator.read("file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"foo");
+
+        let stat = operator.stat("file.txt").await?;
+        assert_eq!(stat.content_type(), Some("text/plain"));
+
+        Ok(())
+    }
+}
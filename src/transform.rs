@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures::stream::{BoxStream, Stream};
+use futures::{StreamExt, TryStreamExt};
+use opendal::{Buffer, Error, Operator};
+
+use crate::copy::IoErrorExt;
+use crate::write::write_stream;
+
+/// A stateful transformation applied to a byte stream chunk by chunk
+/// (eg. compression, encryption, recoding or redaction).
+///
+/// A transform may buffer input and return an empty chunk,
+/// as long as it emits the buffered output from [`ByteTransform::finish`].
+///
+/// Closures taking and returning a chunk implement this trait.
+pub trait ByteTransform: Send {
+    /// Transform the next chunk of the stream.
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error>;
+
+    /// Emit any remaining output once the stream is exhausted.
+    fn finish(&mut self) -> Result<Bytes, Error> {
+        Ok(Bytes::new())
+    }
+}
+
+impl<F> ByteTransform for F
+where
+    F: FnMut(Bytes) -> Result<Bytes, Error> + Send,
+{
+    fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error> {
+        self(chunk)
+    }
+}
+
+type TransformFactory = Arc<dyn Fn() -> Box<dyn ByteTransform> + Send + Sync>;
+
+/// An ordered chain of [`ByteTransform`]s.
+///
+/// Transforms are stateful, so a pipeline holds factories
+/// and instantiates a fresh chain for every stream it is applied to.
+/// Cloning a pipeline is cheap.
+///
+/// Pipelines plug into [`Copier::with_pipeline`](crate::Copier::with_pipeline),
+/// [`UploadOptions`](crate::UploadOptions), [`DownloadOptions`](crate::DownloadOptions),
+/// [`read_transformed`] and [`write_transformed`].
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<TransformFactory>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a transform to the end of the pipeline.
+    pub fn with<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: ByteTransform + 'static,
+    {
+        self.stages.push(Arc::new(move || {
+            Box::new(factory()) as Box<dyn ByteTransform>
+        }));
+        self
+    }
+
+    // Insert a transform at the start of the pipeline.
+    #[cfg(feature = "serde")]
+    pub(crate) fn prepend<F, T>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: ByteTransform + 'static,
+    {
+        self.stages.insert(
+            0,
+            Arc::new(move || Box::new(factory()) as Box<dyn ByteTransform>),
+        );
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Instantiate the transforms of the pipeline as a single transform.
+    pub fn build(&self) -> Box<dyn ByteTransform> {
+        Box::new(Chain {
+            stages: self.stages.iter().map(|factory| factory()).collect(),
+        })
+    }
+
+    /// Apply the pipeline to a byte stream.
+    pub fn apply<S>(&self, stream: S) -> BoxStream<'static, Result<Bytes, Error>>
+    where
+        S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+    {
+        futures::stream::try_unfold(
+            (stream.boxed(), Some(self.build())),
+            |(mut stream, mut transform)| async move {
+                while let Some(current) = transform.as_mut() {
+                    let chunk = match stream.try_next().await? {
+                        Some(chunk) => current.transform(chunk)?,
+                        None => {
+                            let chunk = current.finish()?;
+                            transform = None;
+                            chunk
+                        }
+                    };
+
+                    if !chunk.is_empty() {
+                        return Ok(Some((chunk, (stream, transform))));
+                    }
+                }
+
+                Ok(None)
+            },
+        )
+        .boxed()
+    }
+}
+
+impl std::fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
+
+struct Chain {
+    stages: Vec<Box<dyn ByteTransform>>,
+}
+
+impl ByteTransform for Chain {
+    fn transform(&mut self, mut chunk: Bytes) -> Result<Bytes, Error> {
+        for stage in &mut self.stages {
+            chunk = stage.transform(chunk)?;
+        }
+
+        Ok(chunk)
+    }
+
+    fn finish(&mut self) -> Result<Bytes, Error> {
+        let mut output = BytesMut::new();
+
+        // Output flushed by a stage still has to pass through the stages after it
+        for i in 0..self.stages.len() {
+            let mut chunk = self.stages[i].finish()?;
+
+            for stage in &mut self.stages[i + 1..] {
+                chunk = stage.transform(chunk)?;
+            }
+
+            output.extend_from_slice(&chunk);
+        }
+
+        Ok(output.freeze())
+    }
+}
+
+/// Stream an object through a [`Pipeline`].
+pub async fn read_transformed(
+    operator: &Operator,
+    path: &str,
+    pipeline: &Pipeline,
+) -> Result<BoxStream<'static, Result<Bytes, Error>>, Error> {
+    let stream = operator
+        .reader(path)
+        .await?
+        .into_bytes_stream(..)
+        .await?
+        .map_err(IoErrorExt::into_opendal_error);
+
+    Ok(pipeline.apply(stream))
+}
+
+/// Write a byte stream to an object through a [`Pipeline`].
+///
+/// The write is aborted if the stream or any of the transforms fail.
+pub async fn write_transformed<S>(
+    operator: &Operator,
+    path: &str,
+    stream: S,
+    pipeline: &Pipeline,
+) -> Result<(), Error>
+where
+    S: Stream<Item = Result<Bytes, Error>> + Send + 'static,
+{
+    let writer = operator.writer(path).await?;
+
+    write_stream(writer, pipeline.apply(stream).map_ok(Buffer::from)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    // Collects everything and emits it reversed at the end of the stream.
+    #[derive(Default)]
+    struct Reverse(Vec<u8>);
+
+    impl ByteTransform for Reverse {
+        fn transform(&mut self, chunk: Bytes) -> Result<Bytes, Error> {
+            self.0.extend_from_slice(&chunk);
+
+            Ok(Bytes::new())
+        }
+
+        fn finish(&mut self) -> Result<Bytes, Error> {
+            self.0.reverse();
+
+            Ok(Bytes::from(std::mem::take(&mut self.0)))
+        }
+    }
+
+    fn redact(chunk: Bytes) -> Result<Bytes, Error> {
+        let content = String::from_utf8_lossy(&chunk).replace("secret", "******");
+
+        Ok(Bytes::from(content))
+    }
+
+    fn uppercase(chunk: Bytes) -> Result<Bytes, Error> {
+        Ok(Bytes::from(chunk.to_ascii_uppercase()))
+    }
+
+    #[tokio::test]
+    async fn test_pipeline() -> Result<(), Error> {
+        let pipeline = Pipeline::new().with(Reverse::default).with(|| uppercase);
+
+        let chunks = vec![Ok(Bytes::from("foo")), Ok(Bytes::from("bar"))];
+
+        let output: Vec<Bytes> = pipeline
+            .apply(futures::stream::iter(chunks))
+            .try_collect()
+            .await?;
+
+        assert_eq!(output, vec!["RABOOF"]);
+
+        // Every application gets fresh transforms
+        let output: Vec<Bytes> = pipeline
+            .apply(futures::stream::iter(vec![Ok(Bytes::from("baz"))]))
+            .try_collect()
+            .await?;
+
+        assert_eq!(output, vec!["ZAB"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_write_transformed() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let redact = Pipeline::new().with(|| redact);
+
+        write_transformed(
+            &operator,
+            "log.txt",
+            futures::stream::iter(vec![Ok(Bytes::from("token=secret"))]),
+            &redact,
+        )
+        .await?;
+
+        let content = operator.read("log.txt").await?;
+        assert_eq!(content.to_vec(), b"token=******");
+
+        let output: Vec<Bytes> =
+            read_transformed(&operator, "log.txt", &Pipeline::new().with(|| uppercase))
+                .await?
+                .try_collect()
+                .await?;
+
+        assert_eq!(output.concat(), b"TOKEN=******");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::TryStreamExt;
+use opendal::{Error, ErrorKind, Operator};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::copy::normalize_path;
+use crate::{Copier, CopyOptions, RemoveAllOptions, RemoveAllReport, list, remove_all};
+
+/// Options for the trash helpers.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TrashOptions {
+    /// Where deleted objects are moved to (defaults to `.trash/`).
+    pub trash_prefix: String,
+
+    /// How long deleted objects are kept by [`purge_expired`] (defaults to 30 days).
+    pub ttl: Duration,
+}
+
+impl Default for TrashOptions {
+    fn default() -> Self {
+        Self {
+            trash_prefix: ".trash/".to_string(),
+            ttl: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// Move a file or a directory to the trash instead of deleting it.
+///
+/// Objects are moved to `<trash_prefix>/<timestamp>/<path>`, where the timestamp is the time of the deletion
+/// in milliseconds since the Unix epoch. Returns the path of the object in the trash, to be passed to [`restore`].
+///
+/// Objects are renamed when the service supports it, otherwise they are copied and deleted.
+pub async fn delete(
+    operator: &Operator,
+    path: &str,
+    options: &TrashOptions,
+) -> Result<String, Error> {
+    let path = normalize_path(path);
+    let path = path.as_str().trim_end_matches('/');
+
+    let prefix = trash_prefix(options);
+
+    if path.is_empty() || format!("{}/", path).starts_with(&prefix) {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "Cannot move the trash or the root to the trash",
+        )
+        .with_context("path", path));
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let trashed = format!("{}{}/{}", prefix, timestamp, path);
+
+    move_path(operator, path, &trashed).await
+}
+
+/// Move an object returned by [`delete`] back to its original location.
+///
+/// Returns the original path. Fails if an object already exists at the original location.
+pub async fn restore(
+    operator: &Operator,
+    trashed: &str,
+    options: &TrashOptions,
+) -> Result<String, Error> {
+    let trashed = normalize_path(trashed);
+    let trashed = trashed.as_str().trim_end_matches('/');
+
+    let invalid = || {
+        Error::new(ErrorKind::ConfigInvalid, "Path is not in the trash")
+            .with_context("path", trashed)
+    };
+
+    let (_, original) = trashed
+        .strip_prefix(&trash_prefix(options))
+        .and_then(|path| path.split_once('/'))
+        .ok_or_else(invalid)?;
+
+    if original.is_empty() {
+        return Err(invalid());
+    }
+
+    if stat(operator, original).await?.is_some() {
+        return Err(
+            Error::new(ErrorKind::AlreadyExists, "Original location already exists")
+                .with_context("path", original),
+        );
+    }
+
+    move_path(operator, trashed, original).await
+}
+
+/// Permanently delete the objects moved to the trash more than `older_than` ago.
+pub async fn purge(
+    operator: &Operator,
+    older_than: Duration,
+    options: &TrashOptions,
+) -> Result<RemoveAllReport, Error> {
+    let prefix = trash_prefix(options);
+
+    let entries: Vec<_> = list::lister(operator, &prefix, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut report = RemoveAllReport::default();
+
+    for entry in entries {
+        let Ok(timestamp) = entry.name().trim_end_matches('/').parse::<u64>() else {
+            continue; // Not created by delete
+        };
+
+        let deleted_at = UNIX_EPOCH + Duration::from_millis(timestamp);
+
+        let age = SystemTime::now()
+            .duration_since(deleted_at)
+            .unwrap_or_default();
+
+        if age < older_than {
+            continue;
+        }
+
+        let removed = remove_all(operator, entry.path(), RemoveAllOptions::default()).await?;
+
+        report.files += removed.files;
+        report.directories += removed.directories;
+    }
+
+    Ok(report)
+}
+
+/// Permanently delete the objects moved to the trash more than [`TrashOptions::ttl`] ago.
+pub async fn purge_expired(
+    operator: &Operator,
+    options: &TrashOptions,
+) -> Result<RemoveAllReport, Error> {
+    purge(operator, options.ttl, options).await
+}
+
+fn trash_prefix(options: &TrashOptions) -> String {
+    let prefix = normalize_path(&options.trash_prefix);
+
+    format!("{}/", prefix.as_str().trim_end_matches('/'))
+}
+
+// Whether a path is a directory (or None if it does not exist).
+async fn stat(operator: &Operator, path: &str) -> Result<Option<bool>, Error> {
+    for candidate in [path.to_string(), format!("{}/", path)] {
+        match operator.stat(&candidate).await {
+            Ok(meta) => return Ok(Some(meta.is_dir())),
+            Err(err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(None)
+}
+
+async fn move_path(operator: &Operator, from: &str, to: &str) -> Result<String, Error> {
+    let is_dir = stat(operator, from).await?.ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, "Path not found").with_context("path", from)
+    })?;
+
+    if !is_dir && operator.info().full_capability().rename {
+        operator.rename(from, to).await?;
+
+        return Ok(to.to_string());
+    }
+
+    let (from, to) = if is_dir {
+        (format!("{}/", from), format!("{}/", to))
+    } else {
+        (from.to_string(), to.to_string())
+    };
+
+    let options = CopyOptions {
+        recursive: true,
+        disable_glob: true,
+        ..Default::default()
+    };
+
+    Copier::new(operator.clone(), operator.clone())
+        .copy_options(from.as_str(), to.as_str(), options)
+        .await?;
+
+    remove_all(operator, &from, RemoveAllOptions::default()).await?;
+
+    Ok(to)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trash() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+        let options = TrashOptions::default();
+
+        operator.write("data/a.txt", "a").await?;
+        operator.write("data/sub/b.txt", "b").await?;
+        operator.write("c.txt", "c").await?;
+
+        let trashed_dir = delete(&operator, "data", &options).await?;
+        let trashed_file = delete(&operator, "/c.txt", &options).await?;
+
+        assert!(trashed_dir.starts_with(".trash/"));
+        assert!(!operator.exists("data/a.txt").await?);
+        assert!(!operator.exists("c.txt").await?);
+        assert!(
+            operator
+                .exists(&format!("{}sub/b.txt", trashed_dir))
+                .await?
+        );
+
+        assert_eq!(restore(&operator, &trashed_file, &options).await?, "c.txt");
+        assert_eq!(operator.read("c.txt").await?.to_vec(), b"c");
+
+        // Purging only removes old enough objects
+        let report = purge(&operator, Duration::from_secs(60), &options).await?;
+        assert_eq!(report.files, 0);
+
+        let report = purge(&operator, Duration::ZERO, &options).await?;
+        assert_eq!(report.files, 2);
+        assert!(operator.list(".trash/").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trash_invalid_paths() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+        let options = TrashOptions::default();
+
+        operator.write(".trash/1/a.txt", "a").await?;
+        operator.write("a.txt", "a").await?;
+
+        let err = delete(&operator, "/", &options).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = delete(&operator, ".trash/1", &options).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = restore(&operator, "a.txt", &options).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = restore(&operator, ".trash/1/a.txt", &options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        let err = delete(&operator, "missing.txt", &options)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        Ok(())
+    }
+}
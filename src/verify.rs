@@ -0,0 +1,272 @@
+use futures::TryStreamExt;
+use opendal::{Error, ErrorKind, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{HashAlgorithm, checksum, list};
+
+// Size of the ranges compared when comparing bytes.
+const COMPARE_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// How sampled files are compared.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub enum CompareMode {
+    /// Compare the SHA-256 digests of both files.
+    #[default]
+    Hash,
+
+    /// Compare both files range by range, stopping at the first difference.
+    Bytes,
+}
+
+/// Options for controlling sampling verification.
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct SampleOptions {
+    pub compare: CompareMode,
+
+    /// Seed for the random sample, making it reproducible.
+    ///
+    /// `None` means a random seed.
+    pub seed: Option<u64>,
+}
+
+/// Why a sampled file does not match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The file does not exist in the second tree.
+    Missing,
+
+    /// The files differ in size.
+    Size { expected: u64, actual: u64 },
+
+    /// The files have the same size but different content.
+    Content,
+}
+
+/// Result of [`verify_sample`].
+#[derive(Debug, Clone, Default)]
+pub struct SampleReport {
+    /// Number of files in the first tree.
+    pub total: usize,
+
+    /// Paths (relative to the prefixes) of the sampled files, sorted.
+    pub sampled: Vec<String>,
+
+    /// Sampled files that do not match.
+    pub mismatches: Vec<(String, Mismatch)>,
+}
+
+impl SampleReport {
+    pub fn is_success(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Compare `n` randomly sampled files of the tree under the first prefix
+/// with their counterparts under the second prefix.
+pub async fn verify_sample(
+    a: (Operator, String),
+    b: (Operator, String),
+    n: usize,
+) -> Result<SampleReport, Error> {
+    verify_sample_options(a, b, n, SampleOptions::default()).await
+}
+
+pub async fn verify_sample_options(
+    a: (Operator, String),
+    b: (Operator, String),
+    n: usize,
+    options: SampleOptions,
+) -> Result<SampleReport, Error> {
+    let (a, a_root) = a;
+    let (b, b_root) = b;
+
+    let mut rng = match options.seed {
+        Some(seed) => fastrand::Rng::with_seed(seed),
+        None => fastrand::Rng::new(),
+    };
+
+    let list_options = ListOptions {
+        recursive: true,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(&a, &a_root, Some(list_options)).await?;
+
+    let prefix = a_root.trim_end_matches('/');
+
+    // Reservoir sampling keeps memory bounded by the sample size
+    let mut report = SampleReport::default();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        let path = relative(prefix, entry.path());
+
+        if report.sampled.len() < n {
+            report.sampled.push(path);
+        } else if let Some(slot) = report.sampled.get_mut(rng.usize(..=report.total)) {
+            *slot = path;
+        }
+
+        report.total += 1;
+    }
+
+    report.sampled.sort();
+
+    for path in &report.sampled {
+        let a_path = join(&a_root, path);
+        let b_path = join(&b_root, path);
+
+        if let Some(mismatch) = compare(&a, &a_path, &b, &b_path, options.compare).await? {
+            report.mismatches.push((path.clone(), mismatch));
+        }
+    }
+
+    Ok(report)
+}
+
+async fn compare(
+    a: &Operator,
+    a_path: &str,
+    b: &Operator,
+    b_path: &str,
+    mode: CompareMode,
+) -> Result<Option<Mismatch>, Error> {
+    let expected = a.stat(a_path).await?.content_length();
+
+    let actual = match b.stat(b_path).await {
+        Ok(meta) => meta.content_length(),
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Some(Mismatch::Missing)),
+        Err(err) => return Err(err),
+    };
+
+    if expected != actual {
+        return Ok(Some(Mismatch::Size { expected, actual }));
+    }
+
+    let equal = match mode {
+        CompareMode::Hash => {
+            checksum(a, a_path, HashAlgorithm::Sha256).await?
+                == checksum(b, b_path, HashAlgorithm::Sha256).await?
+        }
+        CompareMode::Bytes => {
+            let mut offset = 0;
+            let mut equal = true;
+
+            while equal && offset < expected {
+                let end = (offset + COMPARE_CHUNK_SIZE).min(expected);
+
+                equal = a.read_with(a_path).range(offset..end).await?.to_bytes()
+                    == b.read_with(b_path).range(offset..end).await?.to_bytes();
+
+                offset = end;
+            }
+
+            equal
+        }
+    };
+
+    Ok((!equal).then_some(Mismatch::Content))
+}
+
+fn relative(prefix: &str, path: &str) -> String {
+    if prefix.is_empty() {
+        return path.to_string();
+    }
+
+    path.strip_prefix(prefix)
+        .unwrap_or(path)
+        .trim_start_matches('/')
+        .to_string()
+}
+
+fn join(root: &str, path: &str) -> String {
+    let root = root.trim_end_matches('/');
+
+    if root.is_empty() {
+        return path.to_string();
+    }
+
+    format!("{}/{}", root, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verify_sample() -> Result<(), Error> {
+        let a = Operator::new(Memory::default())?.finish();
+        let b = Operator::new(Memory::default())?.finish();
+
+        for i in 0..10 {
+            a.write(&format!("src/{}.txt", i), format!("file {}", i))
+                .await?;
+            b.write(&format!("dst/{}.txt", i), format!("file {}", i))
+                .await?;
+        }
+
+        let report = verify_sample(
+            (a.clone(), "src/".to_string()),
+            (b.clone(), "dst".to_string()),
+            3,
+        )
+        .await?;
+
+        assert_eq!(report.total, 10);
+        assert_eq!(report.sampled.len(), 3);
+        assert!(report.is_success());
+
+        b.delete("dst/1.txt").await?;
+        b.write("dst/2.txt", "file 22").await?;
+        b.write("dst/3.txt", "file X").await?;
+
+        for compare in [CompareMode::Hash, CompareMode::Bytes] {
+            let options = SampleOptions {
+                compare,
+                seed: Some(42),
+            };
+
+            // Sample everything
+            let report = verify_sample_options(
+                (a.clone(), "src".to_string()),
+                (b.clone(), "dst".to_string()),
+                100,
+                options,
+            )
+            .await?;
+
+            assert_eq!(report.sampled.len(), 10);
+            assert_eq!(
+                report.mismatches,
+                vec![
+                    ("1.txt".to_string(), Mismatch::Missing),
+                    (
+                        "2.txt".to_string(),
+                        Mismatch::Size {
+                            expected: 6,
+                            actual: 7
+                        }
+                    ),
+                    ("3.txt".to_string(), Mismatch::Content),
+                ]
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use opendal::raw::Timestamp;
+use opendal::{Error, Metadata, Operator, options::ListOptions};
+
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::list;
+
+/// Options for [`watch`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "restate", serde(rename_all = "camelCase"))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+pub struct WatchOptions {
+    /// Delay between two listings.
+    pub interval: Duration,
+
+    /// Whether to watch files in subdirectories.
+    pub recursive: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            recursive: false,
+        }
+    }
+}
+
+/// A change of a file detected by [`watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeEvent {
+    Created(String),
+    Modified(String),
+    Deleted(String),
+}
+
+impl ChangeEvent {
+    pub fn path(&self) -> &str {
+        match self {
+            ChangeEvent::Created(path)
+            | ChangeEvent::Modified(path)
+            | ChangeEvent::Deleted(path) => path,
+        }
+    }
+}
+
+/// Watch the files under a prefix for changes by periodically listing them.
+///
+/// Files are compared by size, ETag and last modification time.
+/// The first listing only records the current state: files existing at that point are not reported as created.
+///
+/// Listing errors are yielded and the next listing is attempted after the interval.
+///
+/// ```no_run
+/// # async fn example(operator: opendal::Operator) {
+/// use futures::StreamExt;
+/// use opendal_util::{WatchOptions, watch};
+///
+/// let mut changes = watch(operator, "incoming/", WatchOptions::default());
+///
+/// while let Some(change) = changes.next().await {
+///     println!("{:?}", change);
+/// }
+/// # }
+/// ```
+pub fn watch(
+    operator: Operator,
+    prefix: impl Into<String>,
+    options: WatchOptions,
+) -> BoxStream<'static, Result<ChangeEvent, Error>> {
+    let state = State {
+        operator,
+        prefix: prefix.into(),
+        options,
+        snapshot: None,
+        pending: VecDeque::new(),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((Ok(event), state));
+            }
+
+            if state.snapshot.is_some() {
+                tokio::time::sleep(state.options.interval).await;
+            }
+
+            let snapshot =
+                match snapshot(&state.operator, &state.prefix, state.options.recursive).await {
+                    Ok(snapshot) => snapshot,
+                    Err(err) => {
+                        // Don't list again right away if the first listing fails
+                        if state.snapshot.is_none() {
+                            tokio::time::sleep(state.options.interval).await;
+                        }
+
+                        return Some((Err(err), state));
+                    }
+                };
+
+            if let Some(previous) = &state.snapshot {
+                state.pending.extend(diff(previous, &snapshot));
+            }
+
+            state.snapshot = Some(snapshot);
+        }
+    })
+    .boxed()
+}
+
+struct State {
+    operator: Operator,
+    prefix: String,
+    options: WatchOptions,
+    snapshot: Option<Snapshot>,
+    pending: VecDeque<ChangeEvent>,
+}
+
+type Snapshot = BTreeMap<String, Fingerprint>;
+
+#[derive(Debug, PartialEq, Eq)]
+struct Fingerprint {
+    size: u64,
+    etag: Option<String>,
+    last_modified: Option<Timestamp>,
+}
+
+impl From<&Metadata> for Fingerprint {
+    fn from(meta: &Metadata) -> Self {
+        Self {
+            size: meta.content_length(),
+            etag: meta.etag().map(String::from),
+            last_modified: meta.last_modified(),
+        }
+    }
+}
+
+async fn snapshot(operator: &Operator, prefix: &str, recursive: bool) -> Result<Snapshot, Error> {
+    let options = ListOptions {
+        recursive,
+        ..Default::default()
+    };
+
+    let mut lister = list::lister(operator, prefix, Some(options)).await?;
+
+    let mut snapshot = BTreeMap::new();
+
+    while let Some(entry) = lister.try_next().await? {
+        if !entry.metadata().is_file() {
+            continue;
+        }
+
+        // Listing does not necessarily return every metadata field
+        let meta = operator.stat(entry.path()).await?;
+
+        snapshot.insert(entry.path().to_string(), Fingerprint::from(&meta));
+    }
+
+    Ok(snapshot)
+}
+
+fn diff(previous: &Snapshot, current: &Snapshot) -> Vec<ChangeEvent> {
+    let mut events = Vec::new();
+
+    for (path, fingerprint) in current {
+        match previous.get(path) {
+            None => events.push(ChangeEvent::Created(path.clone())),
+            Some(previous) if previous != fingerprint => {
+                events.push(ChangeEvent::Modified(path.clone()))
+            }
+            Some(_) => (),
+        }
+    }
+
+    for path in previous.keys() {
+        if !current.contains_key(path) {
+            events.push(ChangeEvent::Deleted(path.clone()));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_watch() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        operator.write("dir/a.txt", "foo").await?;
+        operator.write("dir/b.txt", "bar").await?;
+
+        let mut changes = watch(
+            operator.clone(),
+            "dir/",
+            WatchOptions {
+                interval: Duration::from_secs(1),
+                recursive: true,
+            },
+        );
+
+        // Take the initial snapshot
+        let next = tokio::spawn(async move {
+            let event = changes.next().await;
+
+            (event, changes)
+        });
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        operator.write("dir/a.txt", "changed").await?;
+        operator.delete("dir/b.txt").await?;
+        operator.write("dir/sub/c.txt", "baz").await?;
+
+        let (event, mut changes) = next.await.unwrap();
+
+        let mut events = vec![event.unwrap()?];
+        events.push(changes.next().await.unwrap()?);
+        events.push(changes.next().await.unwrap()?);
+
+        assert_eq!(
+            events,
+            vec![
+                ChangeEvent::Modified("dir/a.txt".to_string()),
+                ChangeEvent::Created("dir/sub/c.txt".to_string()),
+                ChangeEvent::Deleted("dir/b.txt".to_string()),
+            ]
+        );
+        assert_eq!(events[0].path(), "dir/a.txt");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,520 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{Stream, TryStreamExt, stream};
+use opendal::{Buffer, Error, ErrorKind, Operator, Writer};
+use tokio::time::Instant;
+use typed_path::Utf8UnixPathBuf;
+
+use crate::copy::normalize_path;
+use crate::list;
+
+/// Content written by [`atomic`]: bytes or a stream of buffers.
+pub enum AtomicContent {
+    Bytes(Buffer),
+    Stream(BoxStream<'static, Result<Buffer, Error>>),
+}
+
+impl From<Buffer> for AtomicContent {
+    fn from(buffer: Buffer) -> Self {
+        AtomicContent::Bytes(buffer)
+    }
+}
+
+impl From<Bytes> for AtomicContent {
+    fn from(bytes: Bytes) -> Self {
+        AtomicContent::Bytes(bytes.into())
+    }
+}
+
+impl From<Vec<u8>> for AtomicContent {
+    fn from(bytes: Vec<u8>) -> Self {
+        AtomicContent::Bytes(bytes.into())
+    }
+}
+
+impl From<String> for AtomicContent {
+    fn from(string: String) -> Self {
+        AtomicContent::Bytes(string.into())
+    }
+}
+
+impl From<&'static str> for AtomicContent {
+    fn from(string: &'static str) -> Self {
+        AtomicContent::Bytes(string.into())
+    }
+}
+
+impl From<BoxStream<'static, Result<Buffer, Error>>> for AtomicContent {
+    fn from(stream: BoxStream<'static, Result<Buffer, Error>>) -> Self {
+        AtomicContent::Stream(stream)
+    }
+}
+
+impl std::fmt::Debug for AtomicContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtomicContent::Bytes(buffer) => f.debug_tuple("Bytes").field(&buffer.len()).finish(),
+            AtomicContent::Stream(_) => f.debug_tuple("Stream").finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Write bytes or a stream (boxed, see [`AtomicContent`]) to `path` atomically.
+///
+/// Used as `write::atomic(&operator, path, content)`. See [`write_atomic_stream`] for details.
+pub async fn atomic(
+    operator: &Operator,
+    path: &str,
+    content: impl Into<AtomicContent>,
+) -> Result<(), Error> {
+    match content.into() {
+        AtomicContent::Bytes(bytes) => write_atomic(operator, path, bytes).await,
+        AtomicContent::Stream(stream) => write_atomic_stream(operator, path, stream).await,
+    }
+}
+
+/// Write bytes to `path` atomically.
+///
+/// See [`write_atomic_stream`] for details.
+pub async fn write_atomic(
+    operator: &Operator,
+    path: &str,
+    bytes: impl Into<Buffer>,
+) -> Result<(), Error> {
+    write_atomic_stream(operator, path, stream::iter([Ok(bytes.into())])).await
+}
+
+/// Write a stream to `path` atomically: readers either see the previous object or the complete new one.
+///
+/// If the backend supports renaming, the content is written to a temporary sibling object
+/// (`.<name>.<random>.tmp`) which is then renamed to `path`.
+///
+/// Otherwise the content is written to `path` directly and the writer is aborted on failure,
+/// relying on the object only becoming visible once the writer is closed.
+/// The write is made conditional where the backend supports it
+/// (on `path` not existing or on its current ETag), so concurrent writers fail
+/// with [`ErrorKind::ConditionNotMatch`] instead of overwriting each other.
+pub async fn write_atomic_stream<S>(operator: &Operator, path: &str, stream: S) -> Result<(), Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    write_atomic_with(operator, path, stream, None).await
+}
+
+pub(crate) async fn write_atomic_with<S>(
+    operator: &Operator,
+    path: &str,
+    stream: S,
+    content_type: Option<&str>,
+) -> Result<(), Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    let path = normalize_path(path);
+
+    if path.as_str().is_empty() || path.as_str().ends_with('/') {
+        return Err(
+            Error::new(ErrorKind::IsADirectory, "Cannot write to a directory")
+                .with_context("path", path.as_str()),
+        );
+    }
+
+    let capability = operator.info().full_capability();
+
+    if !capability.rename {
+        let mut write = operator.writer_with(path.as_str());
+
+        if let Some(content_type) = content_type {
+            write = write.content_type(content_type);
+        }
+
+        match operator.stat(path.as_str()).await {
+            Ok(meta) => {
+                if capability.write_with_if_match
+                    && let Some(etag) = meta.etag()
+                {
+                    write = write.if_match(etag);
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                write = write.if_not_exists(capability.write_with_if_not_exists);
+            }
+            Err(err) => return Err(err),
+        }
+
+        return write_stream(write.await?, stream).await;
+    }
+
+    let temp_path = temp_path(&path);
+
+    let mut write = operator.writer_with(&temp_path);
+
+    if let Some(content_type) = content_type {
+        write = write.content_type(content_type);
+    }
+
+    let result = match write_stream(write.await?, stream).await {
+        Ok(()) => operator.rename(&temp_path, path.as_str()).await,
+        Err(err) => Err(err),
+    };
+
+    if result.is_err() {
+        let _ = operator.delete(&temp_path).await;
+    }
+
+    result
+}
+
+pub(crate) async fn write_stream<S>(mut writer: Writer, mut stream: S) -> Result<(), Error>
+where
+    S: Stream<Item = Result<Buffer, Error>> + Unpin,
+{
+    loop {
+        let result = match stream.try_next().await {
+            Ok(Some(chunk)) => writer.write(chunk).await,
+            Ok(None) => break,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = result {
+            let _ = writer.abort().await;
+
+            return Err(err);
+        }
+    }
+
+    writer.close().await?;
+
+    Ok(())
+}
+
+/// Appends records (e.g. audit events) to an object.
+///
+/// When the backend supports appending, every record is appended to the object at `path` right away.
+///
+/// Otherwise records are written to numbered segments next to it
+/// (`events-00001.log`, `events-00002.log` for `events.log`), switching to a new segment once the current one
+/// reaches [`Appender::with_max_size`] or [`Appender::with_max_age`].
+/// A segment only becomes visible once it is complete (or the appender is closed),
+/// and numbering continues after the existing segments.
+///
+/// Records written since the last rotation are lost unless [`Appender::close`] is called.
+pub struct Appender {
+    operator: Operator,
+    path: Utf8UnixPathBuf,
+    max_size: u64,
+    max_age: Option<Duration>,
+    segment: Option<Segment>,
+
+    // Index of the last segment
+    index: u64,
+}
+
+struct Segment {
+    writer: Writer,
+    size: u64,
+    opened: Instant,
+}
+
+impl Appender {
+    pub async fn new(operator: Operator, path: &str) -> Result<Self, Error> {
+        let path = normalize_path(path);
+
+        if path.as_str().is_empty() || path.as_str().ends_with('/') {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "Cannot append to a directory")
+                    .with_context("path", path.as_str()),
+            );
+        }
+
+        let mut appender = Self {
+            operator,
+            path,
+            max_size: 64 * 1024 * 1024,
+            max_age: None,
+            segment: None,
+            index: 0,
+        };
+
+        if !appender.can_append() {
+            appender.index = appender.last_segment().await?;
+        }
+
+        Ok(appender)
+    }
+
+    /// Size after which a new segment is started (defaults to 64 MiB).
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Age after which a new segment is started.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Append a record.
+    pub async fn append(&mut self, bytes: impl Into<Buffer>) -> Result<(), Error> {
+        let bytes = bytes.into();
+
+        if self.can_append() {
+            self.operator
+                .write_with(self.path.as_str(), bytes)
+                .append(true)
+                .await?;
+
+            return Ok(());
+        }
+
+        let len = bytes.len() as u64;
+
+        let rotate = self.segment.as_ref().is_some_and(|segment| {
+            (segment.size > 0 && segment.size + len > self.max_size)
+                || self
+                    .max_age
+                    .is_some_and(|max_age| segment.opened.elapsed() >= max_age)
+        });
+
+        if rotate {
+            self.flush().await?;
+        }
+
+        let segment = match &mut self.segment {
+            Some(segment) => segment,
+            None => {
+                self.index += 1;
+
+                let writer = self.operator.writer(&self.segment_path(self.index)).await?;
+
+                self.segment.insert(Segment {
+                    writer,
+                    size: 0,
+                    opened: Instant::now(),
+                })
+            }
+        };
+
+        segment.writer.write(bytes).await?;
+        segment.size += len;
+
+        Ok(())
+    }
+
+    /// Complete the current segment (if any), making it visible.
+    ///
+    /// The next record starts a new segment.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        if let Some(mut segment) = self.segment.take() {
+            segment.writer.close().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn close(mut self) -> Result<(), Error> {
+        self.flush().await
+    }
+
+    fn can_append(&self) -> bool {
+        self.operator.info().full_capability().write_can_append
+    }
+
+    fn segment_path(&self, index: u64) -> String {
+        let (stem, extension) = self.name_parts();
+
+        let name = match extension {
+            Some(extension) => format!("{}-{:05}.{}", stem, index, extension),
+            None => format!("{}-{:05}", stem, index),
+        };
+
+        self.path.with_file_name(name).to_string()
+    }
+
+    fn name_parts(&self) -> (&str, Option<&str>) {
+        let name = self.path.file_name().unwrap_or_default();
+
+        match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+            _ => (name, None),
+        }
+    }
+
+    // Find the index of the last existing segment
+    async fn last_segment(&self) -> Result<u64, Error> {
+        let dir = match self.path.parent().map(|parent| parent.as_str()) {
+            Some("") | None => String::new(),
+            Some(parent) => format!("{}/", parent),
+        };
+
+        let (stem, extension) = self.name_parts();
+        let prefix = format!("{}-", stem);
+        let suffix = extension.map(|extension| format!(".{}", extension));
+
+        let entries = list::list(&self.operator, &dir, None).await?;
+
+        let last = entries
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.name().strip_prefix(&prefix)?;
+
+                let index = match &suffix {
+                    Some(suffix) => name.strip_suffix(suffix.as_str())?,
+                    None => name,
+                };
+
+                index.parse::<u64>().ok()
+            })
+            .max();
+
+        Ok(last.unwrap_or_default())
+    }
+}
+
+impl std::fmt::Debug for Appender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Appender")
+            .field("path", &self.path)
+            .field("max_size", &self.max_size)
+            .field("max_age", &self.max_age)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+// A temporary sibling of a path (hidden, with a random suffix).
+pub(crate) fn temp_path(path: &Utf8UnixPathBuf) -> String {
+    let name = path.file_name().unwrap_or_default();
+
+    path.with_file_name(format!(".{}.{:08x}.tmp", name, fastrand::u32(..)))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use opendal::services::Memory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_atomic() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        write_atomic(&operator, "path/to/file.txt", "foo").await?;
+        write_atomic(&operator, "path/to/file.txt", "bar").await?;
+
+        let buffer = operator.read("path/to/file.txt").await?;
+        assert_eq!(buffer.to_vec(), b"bar");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_atomic() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        atomic(&operator, "file.txt", "foo").await?;
+        assert_eq!(operator.read("file.txt").await?.to_vec(), b"foo");
+
+        let chunks = stream::iter([Ok(Buffer::from("bar")), Ok(Buffer::from("baz"))]);
+
+        atomic(&operator, "file.txt", chunks.boxed()).await?;
+        assert_eq!(operator.read("file.txt").await?.to_vec(), b"barbaz");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_stream_error() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let chunks = vec![
+            Ok(Buffer::from("foo")),
+            Err(Error::new(ErrorKind::Unexpected, "broken stream")),
+        ];
+
+        let result = write_atomic_stream(&operator, "file.txt", stream::iter(chunks)).await;
+        assert!(result.is_err());
+
+        // Nothing is published
+        assert!(!operator.exists("file.txt").await?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_temp_path() {
+        let path = temp_path(&Utf8UnixPathBuf::from("dir/file.txt"));
+
+        assert!(path.starts_with("dir/.file.txt."));
+        assert!(path.ends_with(".tmp"));
+    }
+
+    #[tokio::test]
+    async fn test_appender_rotation() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let mut appender = Appender::new(operator.clone(), "audit/events.log")
+            .await?
+            .with_max_size(10);
+
+        appender.append("event 1\n").await?;
+        appender.append("event 2\n").await?;
+        appender.append("event 3\n").await?;
+        appender.close().await?;
+
+        assert_eq!(
+            operator.read("audit/events-00001.log").await?.to_vec(),
+            b"event 1\n"
+        );
+        assert_eq!(
+            operator.read("audit/events-00003.log").await?.to_vec(),
+            b"event 3\n"
+        );
+
+        // Numbering continues after existing segments
+        let mut appender = Appender::new(operator.clone(), "audit/events.log").await?;
+        appender.append("event 4\n").await?;
+        appender.close().await?;
+
+        assert!(operator.exists("audit/events-00004.log").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_appender_max_age() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let mut appender = Appender::new(operator.clone(), "events")
+            .await?
+            .with_max_age(Duration::from_secs(60));
+
+        appender.append("a").await?;
+        appender.append("b").await?;
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+
+        appender.append("c").await?;
+        appender.close().await?;
+
+        assert_eq!(operator.read("events-00001").await?.to_vec(), b"ab");
+        assert_eq!(operator.read("events-00002").await?.to_vec(), b"c");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_atomic_directory_should_error() -> Result<(), Error> {
+        let operator = Operator::new(Memory::default())?.finish();
+
+        let result = write_atomic(&operator, "dir/", "foo").await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::IsADirectory);
+
+        Ok(())
+    }
+}